@@ -0,0 +1,16 @@
+//! Times the representations on every corpus of `bench_support`
+//!
+//! `cargo run --release --example bench_corpus --features bench_support -- [keys] [seed]`
+
+use ptrie::bench_support::{compare, Corpus};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let keys = args.next().and_then(|n| n.parse().ok()).unwrap_or(100_000);
+    let seed = args.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+    for corpus in Corpus::ALL {
+        for timing in compare(corpus, keys, seed) {
+            println!("{}", timing);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+#ifndef PTRIE_H
+#define PTRIE_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque trie handle
+typedef struct PtrieTrie PtrieTrie;
+
+// Creates an empty trie, to release with `ptrie_free`
+struct PtrieTrie *ptrie_new(void);
+
+// Releases a trie created by `ptrie_new`, doing nothing on null
+//
+// # Safety
+//
+// `trie` must be null or a pointer returned by `ptrie_new` not yet released.
+void ptrie_free(struct PtrieTrie *trie);
+
+// Stores a copy of the value of a key
+//
+// Returns 1 when a previous value was replaced, 0 when the key was new, and -1 when `trie` is
+// null.
+//
+// # Safety
+//
+// `trie` must be null or come from `ptrie_new`, `key` and `value` must be valid for reads of
+// `key_len` and `value_len` bytes.
+int32_t ptrie_insert(struct PtrieTrie *trie,
+                     const uint8_t *key,
+                     size_t key_len,
+                     const uint8_t *value,
+                     size_t value_len);
+
+// Looks up the value of exactly this key
+//
+// Returns whether the key was found, writing the address and length of its value to
+// `out_value` and `out_len`.
+//
+// # Safety
+//
+// `trie` must be null or come from `ptrie_new`, `key` must be valid for reads of `key_len`
+// bytes, and the output pointers must be null or valid for writes.
+bool ptrie_get(const struct PtrieTrie *trie,
+               const uint8_t *key,
+               size_t key_len,
+               const uint8_t **out_value,
+               size_t *out_len);
+
+// Looks up the value of the longest key `key` starts with
+//
+// Returns whether a prefix was found, writing its length to `out_prefix_len` and the address
+// and length of its value to `out_value` and `out_len`.
+//
+// # Safety
+//
+// `trie` must be null or come from `ptrie_new`, `key` must be valid for reads of `key_len`
+// bytes, and the output pointers must be null or valid for writes.
+bool ptrie_longest_prefix(const struct PtrieTrie *trie,
+                          const uint8_t *key,
+                          size_t key_len,
+                          size_t *out_prefix_len,
+                          const uint8_t **out_value,
+                          size_t *out_len);
+
+// Removes a key, returning whether it was present
+//
+// # Safety
+//
+// `trie` must be null or come from `ptrie_new`, `key` must be valid for reads of `key_len`
+// bytes.
+bool ptrie_remove(struct PtrieTrie *trie, const uint8_t *key, size_t key_len);
+
+#endif  /* PTRIE_H */
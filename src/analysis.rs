@@ -0,0 +1,530 @@
+//! Analysis passes summarizing the keys stored in a `Trie`
+
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+use std::collections::BTreeMap;
+
+/// Shared prefix of a group of keys, reported by `Trie::templates`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template<K> {
+    pub prefix: Vec<K>,
+    /// Number of keys grouped under this template
+    pub count: usize,
+}
+
+/// Aggregated outcome of `Trie::classify_report`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassificationReport<K> {
+    /// Number of codes classified by each matched prefix
+    pub matched: BTreeMap<Vec<K>, usize>,
+    /// Number of codes without any matching prefix
+    pub unmatched: usize,
+    /// First unmatched codes, for inspection
+    pub unmatched_samples: Vec<Vec<K>>,
+}
+
+/// Structure of the nodes of a `Trie`, reported by `Trie::shape_report`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeReport<K> {
+    /// Number of nodes, including the root
+    pub nodes: usize,
+    /// Number of nodes by number of children
+    pub fan_out: BTreeMap<usize, usize>,
+    /// Number of chains by length, a chain being a run of nodes without value and with a
+    /// single child, which path compression would merge into one edge
+    pub chain_lengths: BTreeMap<usize, usize>,
+    /// Prefixes holding the most keys with their key count, heaviest first
+    pub heaviest: Vec<(Vec<K>, usize)>,
+}
+
+/// Summary of the node of a prefix, reported by `Trie::prefix_info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefixInfo {
+    /// Whether the prefix is itself a key
+    pub has_value: bool,
+    /// Number of distinct elements following the prefix in the keys
+    pub child_count: usize,
+    /// Number of keys strictly longer than the prefix and starting with it
+    pub descendant_keys: usize,
+}
+
+/// Entry below a prefix, listed by `Trie::list_children`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildEntry<K> {
+    /// Elements following the prefix
+    pub name: Vec<K>,
+    /// Whether the prefix followed by `name` is a key
+    pub has_value: bool,
+    /// Whether longer keys continue below it
+    pub has_children: bool,
+}
+
+/// Where a lookup left the `Trie`, reported by `Trie::explain_miss`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissReport<K> {
+    /// Number of elements of the key matched by the `Trie`
+    pub matched_len: usize,
+    /// Elements which could follow the matched part, in order
+    pub last_node_children: Vec<K>,
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Summary of the node of `prefix`, `None` when no key starts with it
+    ///
+    /// Counts the keys below the prefix without collecting them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for word in ["car", "cart", "carbon", "cat"] {
+    ///     t.insert(word.bytes(), ());
+    /// }
+    ///
+    /// let info = t.prefix_info("car".bytes()).unwrap();
+    /// assert!(info.has_value);
+    /// assert_eq!(info.child_count, 2);
+    /// assert_eq!(info.descendant_keys, 2);
+    /// assert!(t.prefix_info("dog".bytes()).is_none());
+    /// ```
+    pub fn prefix_info<I: Iterator<Item = K>>(&self, prefix: I) -> Option<PrefixInfo> {
+        let node = self.root.find_node(prefix)?;
+        let has_value = node.value.is_some();
+        Some(PrefixInfo {
+            has_value,
+            child_count: node.children.len(),
+            descendant_keys: node.count_values() - usize::from(has_value),
+        })
+    }
+
+    /// Entries `depth` elements below `prefix`, or fewer where the keys end sooner, in key
+    /// order, like the listing of a directory
+    ///
+    /// Keys ending before `depth` with longer keys below them are listed too, ahead of those.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut fs = Trie::new();
+    /// for path in ["/etc/hosts", "/etc/ssh/sshd_config", "/etc/ssh/ssh_config", "/var/log/syslog"] {
+    ///     fs.insert(path.split_inclusive('/'), ());
+    /// }
+    ///
+    /// let listing: Vec<(String, bool)> = fs
+    ///     .list_children(["/", "etc/"].into_iter(), 1)
+    ///     .into_iter()
+    ///     .map(|entry| (entry.name.concat(), entry.has_children))
+    ///     .collect();
+    /// assert_eq!(listing, vec![("hosts".to_string(), false), ("ssh/".to_string(), true)]);
+    /// assert_eq!(fs.list_children(["/"].into_iter(), 2).len(), 3);
+    /// ```
+    pub fn list_children<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+        depth: usize,
+    ) -> Vec<ChildEntry<K>> {
+        let Some(node) = self.root.find_node(prefix) else {
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        let mut stack = vec![(node, Vec::new())];
+        while let Some((node, name)) = stack.pop() {
+            let entry = |name| ChildEntry {
+                name,
+                has_value: node.value.is_some(),
+                has_children: !node.children.is_empty(),
+            };
+            if name.len() == depth || node.children.is_empty() {
+                if !name.is_empty() || depth == 0 {
+                    entries.push(entry(name));
+                }
+                continue;
+            }
+            // Keys ending above `depth` are listed before the entries below them
+            if node.value.is_some() && !name.is_empty() {
+                entries.push(entry(name.clone()));
+            }
+            for (edge, child) in node.children.iter().rev() {
+                let mut child_name = name.clone();
+                child_name.push(edge.clone());
+                stack.push((child, child_name));
+            }
+        }
+        entries
+    }
+
+    /// Where the lookup of `key` fell off the `Trie` and the elements it could have continued
+    /// with, `None` when the key has a value
+    ///
+    /// A key matched whole without a value, being only a prefix of other keys, reports its
+    /// full length.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::analysis::MissReport;
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("color".bytes(), 1);
+    /// t.insert("colon".bytes(), 2);
+    ///
+    /// let miss = t.explain_miss("colour".bytes()).unwrap();
+    /// assert_eq!(miss, MissReport { matched_len: 4, last_node_children: vec![b'n', b'r'] });
+    /// assert!(t.explain_miss("color".bytes()).is_none());
+    /// ```
+    pub fn explain_miss<I: Iterator<Item = K>>(&self, key: I) -> Option<MissReport<K>> {
+        let report = |node: &TrieNode<K, V>, matched_len| MissReport {
+            matched_len,
+            last_node_children: node.children.iter().map(|(k, _)| k.clone()).collect(),
+        };
+        let mut node = &self.root;
+        let mut matched_len = 0;
+        for k in key {
+            match node.children.binary_search_by(|(edge, _)| edge.cmp(&k)) {
+                Ok(ix) => node = &node.children[ix].1,
+                Err(_) => return Some(report(node, matched_len)),
+            }
+            matched_len += 1;
+        }
+        match node.value {
+            Some(_) => None,
+            None => Some(report(node, matched_len)),
+        }
+    }
+
+    /// Groups the keys by their longest shared prefixes, for log deduplication
+    ///
+    /// A template is the deepest prefix of at least `min_len` elements shared by at least
+    /// `min_count` keys. Keys are counted in the deepest template covering them only, and keys
+    /// not covered by any template are left out. Templates are returned in key order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut logs = Trie::new();
+    /// for line in [
+    ///     "user 12 logged in",
+    ///     "user 13 logged in",
+    ///     "user 12 logged out",
+    ///     "disk full on /dev/sda",
+    ///     "disk full on /dev/sdb",
+    ///     "kernel panic",
+    /// ] {
+    ///     logs.insert(line.bytes(), ());
+    /// }
+    ///
+    /// let templates: Vec<(String, usize)> = logs
+    ///     .templates(2, 5)
+    ///     .into_iter()
+    ///     .map(|t| (String::from_utf8(t.prefix).unwrap(), t.count))
+    ///     .collect();
+    /// assert_eq!(
+    ///     templates,
+    ///     vec![
+    ///         ("disk full on /dev/sd".to_string(), 2),
+    ///         ("user 12 logged ".to_string(), 2),
+    ///     ]
+    /// );
+    /// ```
+    pub fn templates(&self, min_count: usize, min_len: usize) -> Vec<Template<K>> {
+        let mut templates = Vec::new();
+        collect_templates(
+            &self.root,
+            &mut Vec::new(),
+            min_count.max(1),
+            min_len,
+            &mut templates,
+        );
+        templates.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+        templates
+    }
+
+    /// Classifies each code by its longest matching prefix, `None` when no prefix matches
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut ranges = Trie::new();
+    /// ranges.insert("400".bytes(), "Germany");
+    /// ranges.insert("50".bytes(), "UK");
+    ///
+    /// let classes = ranges.classify_batch(["4006381333931", "5012345678900", "9781234567897"]
+    ///     .iter()
+    ///     .map(|c| c.bytes()));
+    /// assert_eq!(classes, vec![Some(&"Germany"), Some(&"UK"), None]);
+    /// ```
+    pub fn classify_batch<C, I>(&self, codes: C) -> Vec<Option<&V>>
+    where
+        C: IntoIterator<Item = I>,
+        I: Iterator<Item = K>,
+    {
+        codes.into_iter().map(|code| self.find_longest_prefix(code)).collect()
+    }
+
+    /// Classifies codes by their longest matching prefix and aggregates the results, keeping up
+    /// to `max_samples` unmatched codes
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut ranges = Trie::new();
+    /// ranges.insert("400".bytes(), "Germany");
+    /// ranges.insert("50".bytes(), "UK");
+    ///
+    /// let report = ranges.classify_report(
+    ///     ["4006381333931", "4001234567891", "5012345678900", "9781234567897"]
+    ///         .iter()
+    ///         .map(|c| c.bytes()),
+    ///     10,
+    /// );
+    /// assert_eq!(report.matched.get(&b"400".to_vec()), Some(&2));
+    /// assert_eq!(report.matched.get(&b"50".to_vec()), Some(&1));
+    /// assert_eq!(report.unmatched, 1);
+    /// assert_eq!(report.unmatched_samples, vec![b"9781234567897".to_vec()]);
+    /// ```
+    pub fn classify_report<C, I>(&self, codes: C, max_samples: usize) -> ClassificationReport<K>
+    where
+        C: IntoIterator<Item = I>,
+        I: Iterator<Item = K>,
+    {
+        let mut report = ClassificationReport {
+            matched: BTreeMap::new(),
+            unmatched: 0,
+            unmatched_samples: Vec::new(),
+        };
+        for code in codes {
+            let mut code: Vec<K> = code.collect();
+            match self.find_prefixes(code.iter().cloned()).pop() {
+                Some((ix, _)) => {
+                    code.truncate(ix + 1);
+                    *report.matched.entry(code).or_insert(0) += 1;
+                }
+                None => {
+                    report.unmatched += 1;
+                    if report.unmatched_samples.len() < max_samples {
+                        report.unmatched_samples.push(code);
+                    }
+                }
+            }
+        }
+        report
+    }
+
+    /// Summarizes the shape of the `Trie`: fan-out and chain length histograms, and the `top_n`
+    /// heaviest subtrees
+    ///
+    /// Only nodes ending a chain are ranked among the heaviest subtrees, so a prefix and its
+    /// extensions holding the same keys are reported once, by the longest of them. Ties are
+    /// broken by key order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for key in ["/api/users", "/api/teams", "/static/app.js"] {
+    ///     t.insert(key.bytes(), ());
+    /// }
+    ///
+    /// let report = t.shape_report(2);
+    /// assert_eq!(report.fan_out[&2], 2);
+    /// assert_eq!(report.fan_out[&0], 3);
+    /// let heaviest: Vec<(String, usize)> = report
+    ///     .heaviest
+    ///     .into_iter()
+    ///     .map(|(k, n)| (String::from_utf8(k).unwrap(), n))
+    ///     .collect();
+    /// assert_eq!(heaviest, vec![("/".to_string(), 3), ("/api/".to_string(), 2)]);
+    /// ```
+    pub fn shape_report(&self, top_n: usize) -> ShapeReport<K> {
+        // Nodes in key order, with the index of their parent and their edge
+        let mut nodes: Vec<(usize, Option<&K>, &TrieNode<K, V>)> = Vec::new();
+        let mut stack = vec![(0, None, &self.root)];
+        while let Some((parent, edge, node)) = stack.pop() {
+            let ix = nodes.len();
+            nodes.push((parent, edge, node));
+            stack.extend(node.children.iter().rev().map(|(k, c)| (ix, Some(k), c)));
+        }
+        let mut counts = vec![0usize; nodes.len()];
+        for ix in (0..nodes.len()).rev() {
+            counts[ix] += usize::from(nodes[ix].2.value.is_some());
+            if ix > 0 {
+                counts[nodes[ix].0] += counts[ix];
+            }
+        }
+
+        let is_chain = |node: &TrieNode<K, V>| node.value.is_none() && node.children.len() == 1;
+        let mut fan_out = BTreeMap::new();
+        let mut chain_lengths = BTreeMap::new();
+        for (ix, (parent, _, node)) in nodes.iter().enumerate() {
+            *fan_out.entry(node.children.len()).or_insert(0) += 1;
+            if is_chain(node) && (ix == 0 || !is_chain(nodes[*parent].2)) {
+                let mut len = 1;
+                let mut cur = &node.children[0].1;
+                while is_chain(cur) {
+                    len += 1;
+                    cur = &cur.children[0].1;
+                }
+                *chain_lengths.entry(len).or_insert(0) += 1;
+            }
+        }
+
+        let mut candidates: Vec<usize> =
+            (1..nodes.len()).filter(|ix| !is_chain(nodes[*ix].2)).collect();
+        // Stable sort keeps the key order of the ties
+        candidates.sort_by(|a, b| counts[*b].cmp(&counts[*a]));
+        let heaviest = candidates
+            .into_iter()
+            .take(top_n)
+            .map(|ix| {
+                let mut key = Vec::new();
+                let mut cur = ix;
+                while let (parent, Some(edge), _) = nodes[cur] {
+                    key.push(edge.clone());
+                    cur = parent;
+                }
+                key.reverse();
+                (key, counts[ix])
+            })
+            .collect();
+
+        ShapeReport {
+            nodes: nodes.len(),
+            fan_out,
+            chain_lengths,
+            heaviest,
+        }
+    }
+
+    /// The `k` prefixes of `len` elements with the most keys starting with them, the heaviest
+    /// first
+    ///
+    /// Keys shorter than `len` are not counted. Ties are broken by key order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for key in ["us/a", "us/b", "eu/a", "us/c", "ap/a", "eu/b"] {
+    ///     t.insert(key.bytes(), ());
+    /// }
+    /// let top = t.heaviest_prefixes(2, 2);
+    /// assert_eq!(top, vec![(b"us".to_vec(), 3), (b"eu".to_vec(), 2)]);
+    /// ```
+    pub fn heaviest_prefixes(&self, len: usize, k: usize) -> Vec<(Vec<K>, usize)> {
+        self.heaviest_prefixes_by(len, k, |_| 1)
+            .into_iter()
+            .map(|(prefix, weight)| (prefix, weight as usize))
+            .collect()
+    }
+
+    /// The `k` prefixes of `len` elements with the highest total `weight` of the values of the
+    /// keys starting with them, the heaviest first
+    ///
+    /// Totals saturate at `u64::MAX`. Ties are broken by key order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut bytes = Trie::new();
+    /// bytes.insert("/logs/a".bytes(), 900u64);
+    /// bytes.insert("/home/a".bytes(), 300);
+    /// bytes.insert("/home/b".bytes(), 200);
+    /// let top = bytes.heaviest_prefixes_by(6, 1, |size| *size);
+    /// assert_eq!(top, vec![(b"/logs/".to_vec(), 900)]);
+    /// ```
+    pub fn heaviest_prefixes_by<F: Fn(&V) -> u64>(
+        &self,
+        len: usize,
+        k: usize,
+        weight: F,
+    ) -> Vec<(Vec<K>, u64)> {
+        let mut prefixes = Vec::new();
+        let mut stack = vec![(Vec::new(), &self.root)];
+        while let Some((path, node)) = stack.pop() {
+            if path.len() == len {
+                let total = subtree_weight(node, &weight);
+                prefixes.push((path, total));
+                continue;
+            }
+            for (edge, child) in node.children.iter().rev() {
+                let mut child_path = path.clone();
+                child_path.push(edge.clone());
+                stack.push((child_path, child));
+            }
+        }
+        // Stable sort keeps the key order of the ties
+        prefixes.sort_by(|(_, a), (_, b)| b.cmp(a));
+        prefixes.truncate(k);
+        prefixes
+    }
+}
+
+/// Returns the number of keys under `node` not yet assigned to a template
+fn collect_templates<K: Eq + Ord + Clone, V>(
+    node: &TrieNode<K, V>,
+    path: &mut Vec<K>,
+    min_count: usize,
+    min_len: usize,
+    templates: &mut Vec<Template<K>>,
+) -> usize {
+    // Each frame holds a node, the index of its next child to visit and the keys counted in
+    // its subtree outside of any template so far
+    let mut stack = vec![(node, 0, usize::from(node.value.is_some()))];
+    loop {
+        let (node, next, _) = stack.last_mut().expect("the root is popped last");
+        if let Some((k, child)) = node.children.get(*next) {
+            *next += 1;
+            path.push(k.clone());
+            stack.push((child, 0, usize::from(child.value.is_some())));
+            continue;
+        }
+        let (_, _, mut remaining) = stack.pop().expect("checked above");
+        if path.len() >= min_len && remaining >= min_count {
+            templates.push(Template {
+                prefix: path.clone(),
+                count: remaining,
+            });
+            remaining = 0;
+        }
+        match stack.last_mut() {
+            Some((_, _, parent_remaining)) => {
+                *parent_remaining += remaining;
+                path.pop();
+            }
+            None => return remaining,
+        }
+    }
+}
+
+/// Total `weight` of the values under `node`, saturating
+fn subtree_weight<K: Eq + Ord + Clone, V, F: Fn(&V) -> u64>(
+    node: &TrieNode<K, V>,
+    weight: &F,
+) -> u64 {
+    let mut total = 0u64;
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if let Some(value) = &node.value {
+            total = total.saturating_add(weight(value));
+        }
+        stack.extend(node.children.iter().map(|(_, child)| child));
+    }
+    total
+}
@@ -0,0 +1,167 @@
+//! Export to and import from Arrow record batches with `key` and `value` columns
+//!
+//! Keys are exported in ascending order, which suits dictionary and run-length encodings. A
+//! `RecordBatch` is written to Parquet with the `ArrowWriter` of the `parquet` crate.
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, PrimitiveArray, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// Values stored in an Arrow column
+pub trait ArrowValue: Sized {
+    /// Type of the column
+    fn data_type() -> DataType;
+
+    /// Column of `values`, in order
+    fn to_array<'a, I: Iterator<Item = &'a Self>>(values: I) -> ArrayRef
+    where
+        Self: 'a;
+
+    /// Values of a column, `None` when its type differs or it has nulls
+    fn from_array(array: &dyn Array) -> Option<Vec<Self>>;
+}
+
+macro_rules! primitive_value {
+    ($($t:ty => $arrow:ty),*) => {$(
+        impl ArrowValue for $t {
+            fn data_type() -> DataType {
+                <$arrow as arrow_array::types::ArrowPrimitiveType>::DATA_TYPE
+            }
+
+            fn to_array<'a, I: Iterator<Item = &'a Self>>(values: I) -> ArrayRef {
+                Arc::new(PrimitiveArray::<$arrow>::from_iter_values(values.copied()))
+            }
+
+            fn from_array(array: &dyn Array) -> Option<Vec<Self>> {
+                let array = array.as_primitive_opt::<$arrow>()?;
+                (array.null_count() == 0).then(|| array.values().to_vec())
+            }
+        }
+    )*};
+}
+
+primitive_value!(
+    i8 => Int8Type, i16 => Int16Type, i32 => Int32Type, i64 => Int64Type,
+    u8 => UInt8Type, u16 => UInt16Type, u32 => UInt32Type, u64 => UInt64Type,
+    f32 => Float32Type, f64 => Float64Type
+);
+
+impl ArrowValue for bool {
+    fn data_type() -> DataType {
+        DataType::Boolean
+    }
+
+    fn to_array<'a, I: Iterator<Item = &'a Self>>(values: I) -> ArrayRef {
+        Arc::new(values.map(|value| Some(*value)).collect::<BooleanArray>())
+    }
+
+    fn from_array(array: &dyn Array) -> Option<Vec<Self>> {
+        let array = array.as_boolean_opt()?;
+        (array.null_count() == 0).then(|| array.values().iter().collect())
+    }
+}
+
+impl ArrowValue for String {
+    fn data_type() -> DataType {
+        DataType::Utf8
+    }
+
+    fn to_array<'a, I: Iterator<Item = &'a Self>>(values: I) -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(values))
+    }
+
+    fn from_array(array: &dyn Array) -> Option<Vec<Self>> {
+        let array = array.as_string_opt::<i32>()?;
+        array.iter().map(|value| value.map(str::to_string)).collect()
+    }
+}
+
+impl ArrowValue for Vec<u8> {
+    fn data_type() -> DataType {
+        DataType::Binary
+    }
+
+    fn to_array<'a, I: Iterator<Item = &'a Self>>(values: I) -> ArrayRef {
+        Arc::new(BinaryArray::from_iter_values(values))
+    }
+
+    fn from_array(array: &dyn Array) -> Option<Vec<Self>> {
+        let array = array.as_binary_opt::<i32>()?;
+        array.iter().map(|value| value.map(<[u8]>::to_vec)).collect()
+    }
+}
+
+impl<V: ArrowValue> Trie<u8, V> {
+    /// Record batch of the entries in key order, with a binary `key` column and a `value`
+    /// column, neither nullable
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("b".bytes(), 2i64);
+    /// t.insert("a".bytes(), 1i64);
+    ///
+    /// let batch = t.to_arrow();
+    /// assert_eq!(batch.num_rows(), 2);
+    /// assert_eq!(Trie::<u8, i64>::from_arrow(&batch).unwrap().get("b".bytes()), Some(&2));
+    /// ```
+    pub fn to_arrow(&self) -> RecordBatch {
+        bulk_span!("ptrie::to_arrow");
+        let (keys, values): (Vec<Vec<u8>>, Vec<&V>) = self.iter().unzip();
+        let schema = Schema::new(vec![
+            Field::new("key", DataType::Binary, false),
+            Field::new("value", V::data_type(), false),
+        ]);
+        let columns = vec![
+            Arc::new(BinaryArray::from_iter_values(&keys)) as ArrayRef,
+            V::to_array(values.into_iter()),
+        ];
+        RecordBatch::try_new(Arc::new(schema), columns).expect("columns match the schema")
+    }
+
+    /// Builds a `Trie` from the `key` and `value` columns of a record batch, the last row of a
+    /// key winning
+    ///
+    /// Fails with `TrieError::InvalidData` when a column is missing, has another type, or has
+    /// nulls.
+    pub fn from_arrow(batch: &RecordBatch) -> Result<Self, TrieError> {
+        bulk_span!("ptrie::from_arrow");
+        let column = |name| {
+            batch
+                .column_by_name(name)
+                .ok_or_else(|| TrieError::InvalidData(format!("No `{}` column", name)))
+        };
+        let keys = column("key")?
+            .as_binary_opt::<i32>()
+            .filter(|keys| keys.null_count() == 0)
+            .ok_or_else(|| {
+                TrieError::InvalidData(
+                    "Column `key` is not a binary column without nulls".to_string(),
+                )
+            })?;
+        let values = V::from_array(column("value")?.as_ref()).ok_or_else(|| {
+            TrieError::InvalidData(format!(
+                "Column `value` is not a {} column without nulls",
+                V::data_type()
+            ))
+        })?;
+        let mut trie = Trie::new();
+        for (row, value) in values.into_iter().enumerate() {
+            let key = keys.value(row);
+            trie.insert(key.iter().copied(), value);
+        }
+        Ok(trie)
+    }
+}
@@ -0,0 +1,222 @@
+//! Key corpora and timing entry points, to compare representations on the machine at hand
+//!
+//! The corpora are generated from a seed, so runs on different machines use the same keys.
+//! `cargo run --release --example bench_corpus --features bench_support` times every corpus.
+
+use crate::hash_trie::HashTrie;
+use crate::rng::SplitMix;
+use crate::trie::Trie;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Realistic key sets, as byte keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corpus {
+    /// URLs on a few hosts with nested paths, sharing long prefixes
+    Urls,
+    /// Pronounceable words of one to four syllables
+    Words,
+    /// Random identifiers formatted like UUIDs, sharing almost no prefix
+    Ids,
+    /// IPv4 CIDR prefixes as bit strings, one byte of 0 or 1 per bit
+    Cidrs,
+}
+
+impl Corpus {
+    /// Every corpus
+    pub const ALL: [Corpus; 4] = [Corpus::Urls, Corpus::Words, Corpus::Ids, Corpus::Cidrs];
+
+    /// Name of the corpus in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Corpus::Urls => "urls",
+            Corpus::Words => "words",
+            Corpus::Ids => "ids",
+            Corpus::Cidrs => "cidrs",
+        }
+    }
+
+    /// `n` keys of the corpus generated from `seed`, duplicates included
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::bench_support::Corpus;
+    ///
+    /// let ids = Corpus::Ids.keys(3, 42);
+    /// assert_eq!(ids, Corpus::Ids.keys(3, 42));
+    /// assert_eq!(ids[0].len(), 36);
+    /// assert!(Corpus::Cidrs.keys(10, 1).iter().flatten().all(|bit| *bit <= 1));
+    /// ```
+    pub fn keys(&self, n: usize, seed: u64) -> Vec<Vec<u8>> {
+        let mut rng = SplitMix::new(seed);
+        (0..n).map(|_| self.key(&mut rng)).collect()
+    }
+
+    fn key(&self, rng: &mut SplitMix) -> Vec<u8> {
+        match self {
+            Corpus::Urls => {
+                let hosts = [
+                    "example.org",
+                    "api.example.org",
+                    "cdn.example.net",
+                    "docs.rs",
+                ];
+                let mut url = format!("https://{}", hosts[rng.below(hosts.len())]);
+                for _ in 0..1 + rng.below(4) {
+                    url.push('/');
+                    url.push_str(&word(rng));
+                }
+                if rng.below(3) == 0 {
+                    url.push_str(&format!("?id={}", rng.below(10_000)));
+                }
+                url.into_bytes()
+            }
+            Corpus::Words => word(rng).into_bytes(),
+            Corpus::Ids => {
+                let hex = format!("{:016x}{:016x}", rng.next(), rng.next());
+                let parts = [
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..],
+                ];
+                parts.join("-").into_bytes()
+            }
+            Corpus::Cidrs => {
+                let len = 8 + rng.below(25);
+                let address = rng.next() as u32;
+                (0..len).map(|bit| ((address >> (31 - bit)) & 1) as u8).collect()
+            }
+        }
+    }
+}
+
+/// Pronounceable word of one to four syllables
+fn word(rng: &mut SplitMix) -> String {
+    const ONSETS: [&str; 12] = ["b", "c", "d", "f", "g", "l", "m", "n", "p", "r", "s", "t"];
+    const NUCLEI: [&str; 6] = ["a", "e", "i", "o", "u", "ou"];
+    (0..1 + rng.below(4))
+        .map(|_| {
+            let onset = ONSETS[rng.below(ONSETS.len())];
+            format!("{}{}", onset, NUCLEI[rng.below(NUCLEI.len())])
+        })
+        .collect()
+}
+
+/// Duration of a timed operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    pub name: String,
+    pub iterations: usize,
+    pub total: Duration,
+}
+
+impl Timing {
+    /// Mean duration of one iteration
+    pub fn per_iteration(&self) -> Duration {
+        self.total.div_f64(self.iterations.max(1) as f64)
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<32} {:>8} iterations {:>12?} each",
+            self.name,
+            self.iterations,
+            self.per_iteration()
+        )
+    }
+}
+
+/// Runs `f` `iterations` times, its results kept from being optimized away
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::bench_support::time;
+///
+/// let timing = time("sum", 10, || (0..100u64).sum::<u64>());
+/// assert_eq!(timing.iterations, 10);
+/// ```
+pub fn time<R>(name: &str, iterations: usize, mut f: impl FnMut() -> R) -> Timing {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    Timing {
+        name: name.to_string(),
+        iterations,
+        total: start.elapsed(),
+    }
+}
+
+/// Times building and querying `n` keys of `corpus` with a `Trie`, a `HashTrie`, and the
+/// `BTreeMap` and `HashMap` of the standard library as baselines
+///
+/// Each lookup timing queries every key once per iteration.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::bench_support::{compare, Corpus};
+///
+/// for timing in compare(Corpus::Words, 100, 7) {
+///     println!("{}", timing);
+/// }
+/// ```
+pub fn compare(corpus: Corpus, n: usize, seed: u64) -> Vec<Timing> {
+    let keys = corpus.keys(n, seed);
+    let name = |operation: &str| format!("{}/{}", corpus.name(), operation);
+    let build_trie = || {
+        let mut trie = Trie::new();
+        for key in &keys {
+            trie.insert(key.iter().copied(), ());
+        }
+        trie
+    };
+    let build_hash_trie = || {
+        let mut trie = HashTrie::new();
+        for key in &keys {
+            trie.insert(key.iter().copied(), ());
+        }
+        trie
+    };
+    let trie = build_trie();
+    let hash_trie = build_hash_trie();
+    let btree: BTreeMap<&[u8], ()> = keys.iter().map(|key| (key.as_slice(), ())).collect();
+    let hash_map: HashMap<&[u8], ()> = keys.iter().map(|key| (key.as_slice(), ())).collect();
+    let iterations = 10;
+    vec![
+        time(&name("trie/build"), iterations, build_trie),
+        time(&name("hash_trie/build"), iterations, build_hash_trie),
+        time(&name("trie/get"), iterations, || {
+            keys.iter()
+                .filter(|key| trie.contains_key(key.iter().copied()))
+                .count()
+        }),
+        time(&name("trie/longest_prefix"), iterations, || {
+            keys.iter()
+                .filter_map(|key| trie.find_longest_prefix(key.iter().copied()))
+                .count()
+        }),
+        time(&name("hash_trie/get"), iterations, || {
+            keys.iter()
+                .filter(|key| hash_trie.contains_key(key.iter().copied()))
+                .count()
+        }),
+        time(&name("btree_map/get"), iterations, || {
+            keys.iter().filter(|key| btree.contains_key(key.as_slice())).count()
+        }),
+        time(&name("hash_map/get"), iterations, || {
+            keys.iter()
+                .filter(|key| hash_map.contains_key(key.as_slice()))
+                .count()
+        }),
+    ]
+}
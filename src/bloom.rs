@@ -0,0 +1,105 @@
+//! Bloom filter in front of a `Trie`, answering most lookups of absent keys without a traversal
+
+use crate::trie::Trie;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Trie with a Bloom filter over its keys
+///
+/// `contains_key` and `get` hash the key once and only walk the `Trie` when the filter may
+/// contain it, which pays off when most lookups miss. Removed keys stay in the filter until
+/// `rebuild`, making their lookups traverse again but never giving a wrong answer.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::bloom::BloomTrie;
+///
+/// let mut blocklist = BloomTrie::with_capacity(1000, 0.01);
+/// blocklist.insert("evil.example".bytes(), ());
+/// assert!(blocklist.contains_key("evil.example".bytes()));
+/// assert!(!blocklist.contains_key("good.example".bytes()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BloomTrie<K: Eq + Ord + Clone + Hash, V> {
+    trie: Trie<K, V>,
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl<K: Eq + Ord + Clone + Hash, V> BloomTrie<K, V> {
+    /// Creates an empty trie with a filter sized for `keys` keys at a `false_positive_rate`
+    /// between 0 and 1
+    pub fn with_capacity(keys: usize, false_positive_rate: f64) -> Self {
+        let rate = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(keys.max(1) as f64) * rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let hashes = ((bits as f64 / keys.max(1) as f64) * ln2).round().max(1.0) as u32;
+        BloomTrie {
+            trie: Trie::new(),
+            bits: vec![0; bits.div_ceil(64).max(1)],
+            hashes,
+        }
+    }
+
+    /// The entries, for prefix queries
+    pub fn trie(&self) -> &Trie<K, V> {
+        &self.trie
+    }
+
+    /// Inserts a value, returning the previous one
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V> {
+        let key: Vec<K> = key.collect();
+        for bit in self.bit_positions(&key) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.trie.insert(key.into_iter(), value)
+    }
+
+    /// Removes a key, returning its value
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        self.trie.remove(key)
+    }
+
+    /// Whether the key has a value, rejected by the filter when it surely has not
+    pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Value of a key, rejected by the filter when it surely has none
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        let key: Vec<K> = key.collect();
+        let mut bits = self.bit_positions(&key);
+        if !bits.all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0) {
+            return None;
+        }
+        self.trie.get(key.into_iter())
+    }
+
+    /// Recomputes the filter from the keys left, to drop those of removed keys
+    pub fn rebuild(&mut self) {
+        bulk_span!("ptrie::bloom_rebuild");
+        self.bits.iter_mut().for_each(|word| *word = 0);
+        let keys: Vec<Vec<K>> = self.trie.iter().map(|(key, _)| key).collect();
+        for key in keys {
+            for bit in self.bit_positions(&key) {
+                self.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+    }
+
+    /// Positions of the bits of a key
+    fn bit_positions(&self, key: &[K]) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        bit_positions(hasher.finish(), self.hashes, self.bits.len())
+    }
+}
+
+/// Positions of the `hashes` bits of a key among `words` words of bits, derived from two
+/// halves of the `hash` of the key
+pub(crate) fn bit_positions(hash: u64, hashes: u32, words: usize) -> impl Iterator<Item = usize> {
+    let (h1, h2) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+    let len = (words * 64) as u64;
+    (0..u64::from(hashes)).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+}
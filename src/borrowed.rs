@@ -0,0 +1,132 @@
+//! Lookups with key elements borrowed from the owned elements of the `Trie`
+//!
+//! A `Trie<String, V>` can be queried with `&str` elements, like a `HashMap<String, V>` with
+//! `&str` keys: the elements only need to compare like the `K` they borrow from, as required by
+//! `Borrow`.
+
+use crate::trie::Trie;
+use std::borrow::Borrow;
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Same as `get`, with key elements borrowed from `K`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut phrases = Trie::new();
+    /// phrases.insert(["new", "york"].map(String::from).into_iter(), 1);
+    /// assert_eq!(phrases.get_borrowed("new york".split(' ')), Some(&1));
+    /// assert_eq!(phrases.get_borrowed("new".split(' ')), None);
+    /// ```
+    pub fn get_borrowed<'q, Q, I>(&self, key: I) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+        I: Iterator<Item = &'q Q>,
+    {
+        self.root.find_node_borrowed(key)?.value.as_ref()
+    }
+
+    /// Same as `contains_key`, with key elements borrowed from `K`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut phrases = Trie::new();
+    /// phrases.insert(["new", "york"].map(String::from).into_iter(), 1);
+    /// assert!(phrases.contains_key_borrowed(["new", "york"].into_iter()));
+    /// ```
+    pub fn contains_key_borrowed<'q, Q, I>(&self, key: I) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+        I: Iterator<Item = &'q Q>,
+    {
+        self.get_borrowed(key).is_some()
+    }
+
+    /// Same as `find_prefixes`, with key elements borrowed from `K`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut phrases = Trie::new();
+    /// phrases.insert(["new"].map(String::from).into_iter(), "adj");
+    /// phrases.insert(["new", "york"].map(String::from).into_iter(), "city");
+    /// let found = phrases.find_prefixes_borrowed("new york city".split(' '));
+    /// assert_eq!(found, vec![(0, &"adj"), (1, &"city")]);
+    /// ```
+    pub fn find_prefixes_borrowed<'q, Q, I>(&self, key: I) -> Vec<(usize, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+        I: Iterator<Item = &'q Q>,
+    {
+        let mut node = &self.root;
+        let mut prefixes = Vec::new();
+        for (i, k) in key.enumerate() {
+            let Ok(ix) = node.child_index_borrowed(k) else {
+                break;
+            };
+            node = &node.children[ix].1;
+            if let Some(value) = &node.value {
+                prefixes.push((i, value));
+            }
+        }
+        prefixes
+    }
+
+    /// Same as `find_longest_prefix`, with key elements borrowed from `K`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut phrases = Trie::new();
+    /// phrases.insert(["new"].map(String::from).into_iter(), "adj");
+    /// phrases.insert(["new", "york"].map(String::from).into_iter(), "city");
+    /// assert_eq!(phrases.find_longest_prefix_borrowed("new york city".split(' ')), Some(&"city"));
+    /// ```
+    pub fn find_longest_prefix_borrowed<'q, Q, I>(&self, key: I) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+        I: Iterator<Item = &'q Q>,
+    {
+        self.find_prefixes_borrowed(key).pop().map(|(_, value)| value)
+    }
+
+    /// Same as `find_postfixes`, with key elements borrowed from `K`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut phrases = Trie::new();
+    /// phrases.insert(["new", "york"].map(String::from).into_iter(), 1);
+    /// phrases.insert(["new", "jersey"].map(String::from).into_iter(), 2);
+    /// assert_eq!(phrases.find_postfixes_borrowed(["new"].into_iter()), vec![&2, &1]);
+    /// ```
+    pub fn find_postfixes_borrowed<'q, Q, I>(&self, prefix: I) -> Vec<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+        I: Iterator<Item = &'q Q>,
+    {
+        let mut values = Vec::new();
+        let mut stack: Vec<_> = self.root.find_node_borrowed(prefix).into_iter().collect();
+        while let Some(node) = stack.pop() {
+            values.extend(node.value.as_ref());
+            stack.extend(node.children.iter().rev().map(|(_, child)| child));
+        }
+        values
+    }
+}
@@ -0,0 +1,153 @@
+//! Builder to configure the optional behaviors of a `Trie`
+
+use crate::trie::{ChildOrder, Trie};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Builder for a `Trie`, created with `Trie::builder()`
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::builder::TrieBuilder;
+///
+/// let mut t = TrieBuilder::new().insertion_order(true).build();
+/// t.insert("b".bytes(), 1);
+/// t.insert("a".bytes(), 2);
+/// assert_eq!(t.iter_insertion_order().unwrap().count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrieBuilder<K, V> {
+    insertion_order: bool,
+    max_depth: Option<usize>,
+    memory_budget: Option<MemoryBudget>,
+    child_order: Option<ChildOrder<K, V>>,
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K: Eq + Ord + Clone, V> TrieBuilder<K, V> {
+    /// Creates a builder with every optional behavior disabled
+    pub fn new() -> Self {
+        TrieBuilder {
+            insertion_order: false,
+            max_depth: None,
+            memory_budget: None,
+            child_order: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Records the order in which keys are inserted, to replay it with `iter_insertion_order()`
+    pub fn insertion_order(mut self, enabled: bool) -> Self {
+        self.insertion_order = enabled;
+        self
+    }
+
+    /// Limits the length of the keys accepted by `Trie::insert_checked()`
+    ///
+    /// Only `insert_checked()` rejects longer keys with `TrieError::DepthExceeded`, `insert` and
+    /// the other inserting methods accept them.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Bounds the approximate memory used by the nodes created by `Trie::insert_checked()`,
+    /// applying `policy` to the inserts which would go beyond `bytes`
+    ///
+    /// The estimate counts the storage of the nodes, not the heap owned by keys and values.
+    /// Only `insert_checked()` enforces the budget, `insert` and the other inserting methods
+    /// grow the `Trie` past it and the next `insert_checked()` counts their nodes again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::builder::BudgetPolicy;
+    /// use ptrie::Trie;
+    ///
+    /// let node = ptrie::builder::node_footprint::<u8, u32>();
+    /// let mut t = Trie::builder().memory_budget(4 * node, BudgetPolicy::Reject).build();
+    /// assert!(t.insert_checked("abc".bytes(), 1).is_ok());
+    /// assert!(t.insert_checked("abd".bytes(), 2).is_ok());
+    /// assert!(t.insert_checked("x".bytes(), 3).is_err());
+    /// assert!(!t.contains_key("x".bytes()));
+    /// ```
+    pub fn memory_budget(mut self, bytes: usize, policy: BudgetPolicy) -> Self {
+        self.memory_budget = Some(MemoryBudget {
+            bytes,
+            policy,
+            estimate: None,
+        });
+        self
+    }
+
+    /// Orders the children of each node by `key` in `Trie::iter_presented()`, for instance
+    /// the most frequent completions first, the structure staying in key order
+    ///
+    /// The order is not serialized nor kept by `Trie::clone_map()`.
+    pub fn child_order(mut self, key: ChildOrder<K, V>) -> Self {
+        self.child_order = Some(key);
+        self
+    }
+
+    /// Creates the configured `Trie`
+    ///
+    /// Evicting the oldest keys to stay within the memory budget enables the insertion order.
+    pub fn build(self) -> Trie<K, V> {
+        let mut trie = Trie::new();
+        let evicts = self
+            .memory_budget
+            .as_ref()
+            .is_some_and(|budget| budget.policy == BudgetPolicy::EvictOldest);
+        if self.insertion_order || evicts {
+            trie.insertion_order = Some(Vec::new());
+        }
+        trie.max_depth = self.max_depth;
+        trie.memory_budget = self.memory_budget;
+        trie.child_order = self.child_order;
+        trie
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Default for TrieBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What `Trie::insert_checked()` does with an insert going beyond the memory budget
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetPolicy {
+    /// Rejects the insert with `TrieError::BudgetExceeded`, leaving the `Trie` untouched
+    Reject,
+    /// Removes the keys in insertion order until the new key fits
+    EvictOldest,
+}
+
+/// Memory budget of a `Trie`, with the estimate of the memory used so far
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryBudget {
+    pub(crate) bytes: usize,
+    pub(crate) policy: BudgetPolicy,
+    /// Footprint of the nodes, `None` until counted and after nodes are created outside of
+    /// `insert_checked`. Removals leave it above the actual footprint, so it is counted again
+    /// before rejecting or evicting anything.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) estimate: Option<usize>,
+}
+
+/// Approximate memory taken by a node of a `Trie<K, V>`, the unit of its memory budget
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::builder::node_footprint;
+///
+/// assert!(node_footprint::<u8, u64>() >= std::mem::size_of::<u64>());
+/// ```
+pub fn node_footprint<K: Eq + Ord + Clone, V>() -> usize {
+    std::mem::size_of::<(K, crate::trie_node::TrieNode<K, V>)>()
+}
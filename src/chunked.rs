@@ -0,0 +1,88 @@
+//! Trie whose edges carry a whole chunk of symbols, such as the segments of a path
+//!
+//! Keys are given pre-split in chunks and every edge holds one chunk, so matches are always
+//! aligned on chunk boundaries: `/usr/lib` is not a prefix of `/usr/library`.
+
+use crate::trie::Trie;
+
+/// Trie over keys made of chunks of `K`, backed by a `Trie<Vec<K>, V>`
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::chunked::ChunkedTrie;
+///
+/// let mut mounts = ChunkedTrie::new();
+/// mounts.insert(["usr", "lib"].map(str::as_bytes), "lib");
+/// mounts.insert(["usr"].map(str::as_bytes), "usr");
+///
+/// let found = mounts.longest_prefix(["usr", "library", "x"].map(str::as_bytes));
+/// assert_eq!(found, Some((1, &"usr")));
+/// let found = mounts.longest_prefix(["usr", "lib", "x"].map(str::as_bytes));
+/// assert_eq!(found, Some((2, &"lib")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChunkedTrie<K: Eq + Ord + Clone, V> {
+    trie: Trie<Vec<K>, V>,
+}
+
+/// Owned chunks of a key
+fn chunks<K: Clone, C: AsRef<[K]>, I: IntoIterator<Item = C>>(
+    key: I,
+) -> impl Iterator<Item = Vec<K>> {
+    key.into_iter().map(|chunk| chunk.as_ref().to_vec())
+}
+
+impl<K: Eq + Ord + Clone, V> ChunkedTrie<K, V> {
+    /// Creates an empty trie
+    pub fn new() -> Self {
+        ChunkedTrie { trie: Trie::new() }
+    }
+
+    /// The `Trie` of chunks, for the queries not wrapped here
+    pub fn trie(&self) -> &Trie<Vec<K>, V> {
+        &self.trie
+    }
+
+    /// Inserts a value under a key given as chunks, returning the previous one
+    pub fn insert<C: AsRef<[K]>, I: IntoIterator<Item = C>>(
+        &mut self,
+        key: I,
+        value: V,
+    ) -> Option<V> {
+        self.trie.insert(chunks(key), value)
+    }
+
+    /// Value of a key given as chunks
+    pub fn get<C: AsRef<[K]>, I: IntoIterator<Item = C>>(&self, key: I) -> Option<&V> {
+        self.trie.get(chunks(key))
+    }
+
+    /// Removes a key given as chunks, returning its value
+    pub fn remove<C: AsRef<[K]>, I: IntoIterator<Item = C>>(&mut self, key: I) -> Option<V> {
+        self.trie.remove(chunks(key))
+    }
+
+    /// Number of chunks and value of the longest stored key made of the first chunks of `key`
+    pub fn longest_prefix<C: AsRef<[K]>, I: IntoIterator<Item = C>>(
+        &self,
+        key: I,
+    ) -> Option<(usize, &V)> {
+        let (ix, value) = self.trie.find_prefixes(chunks(key)).pop()?;
+        Some((ix + 1, value))
+    }
+
+    /// Iterate the keys starting with the chunks of `prefix`, in key order
+    pub fn iter_prefix<C: AsRef<[K]>, I: IntoIterator<Item = C>>(
+        &self,
+        prefix: I,
+    ) -> impl Iterator<Item = (Vec<Vec<K>>, &V)> + '_ {
+        self.trie.iter_prefix(chunks(prefix))
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Default for ChunkedTrie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
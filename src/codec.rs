@@ -0,0 +1,143 @@
+//! Values stored encoded, like compressed payloads, and decoded on access
+
+use crate::trie::Trie;
+use std::borrow::Cow;
+
+/// Encoding of the values `V` stored by a `CodecTrie`
+pub trait ValueCodec<V: Clone> {
+    /// Stored form of the values
+    type Encoded;
+
+    /// Stored form of `value`
+    fn encode(&self, value: V) -> Self::Encoded;
+
+    /// Value of `encoded`, borrowed when the stored form is the value itself
+    fn decode<'a>(&self, encoded: &'a Self::Encoded) -> Cow<'a, V>;
+}
+
+/// Codec storing the values as they are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Identity;
+
+impl<V: Clone> ValueCodec<V> for Identity {
+    type Encoded = V;
+
+    fn encode(&self, value: V) -> V {
+        value
+    }
+
+    fn decode<'a>(&self, encoded: &'a V) -> Cow<'a, V> {
+        Cow::Borrowed(encoded)
+    }
+}
+
+/// Codec made of an encoding and a decoding function
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::codec::{CodecTrie, FnCodec};
+///
+/// // Halves the memory of the values, at the cost of their precision
+/// let mut prices = CodecTrie::new(FnCodec::new(|p: f64| p as f32, |p: &f32| f64::from(*p)));
+/// assert_eq!(prices.insert("sku-1".bytes(), 2.5), None);
+/// assert_eq!(*prices.get("sku-1".bytes()).unwrap(), 2.5);
+/// assert_eq!(prices.insert("sku-1".bytes(), 3.0), Some(2.5));
+/// assert_eq!(prices.trie().get("sku-1".bytes()), Some(&3.0f32));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FnCodec<E, D> {
+    encode: E,
+    decode: D,
+}
+
+impl<E, D> FnCodec<E, D> {
+    /// Codec applying `encode` on insertion and `decode` on access
+    pub fn new(encode: E, decode: D) -> Self {
+        FnCodec { encode, decode }
+    }
+}
+
+impl<V: Clone, T, E: Fn(V) -> T, D: Fn(&T) -> V> ValueCodec<V> for FnCodec<E, D> {
+    type Encoded = T;
+
+    fn encode(&self, value: V) -> T {
+        (self.encode)(value)
+    }
+
+    fn decode<'a>(&self, encoded: &'a T) -> Cow<'a, V> {
+        Cow::Owned((self.decode)(encoded))
+    }
+}
+
+/// Trie storing its values encoded by a `ValueCodec`, trading the CPU time of the codec for
+/// the memory of the values
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::codec::{CodecTrie, Identity};
+/// use std::borrow::Cow;
+///
+/// let mut t = CodecTrie::new(Identity);
+/// t.insert("a".bytes(), 1);
+/// assert!(matches!(t.get("a".bytes()), Some(Cow::Borrowed(&1))));
+/// assert_eq!(t.remove("a".bytes()), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CodecTrie<K: Eq + Ord + Clone, V: Clone, C: ValueCodec<V>> {
+    trie: Trie<K, C::Encoded>,
+    codec: C,
+}
+
+impl<K: Eq + Ord + Clone, V: Clone, C: ValueCodec<V>> CodecTrie<K, V, C> {
+    /// Creates an empty trie encoding its values with `codec`
+    pub fn new(codec: C) -> Self {
+        CodecTrie {
+            trie: Trie::new(),
+            codec,
+        }
+    }
+
+    /// The encoded values by key, for prefix queries
+    pub fn trie(&self) -> &Trie<K, C::Encoded> {
+        &self.trie
+    }
+
+    /// The codec of the values
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Encodes and inserts a value, returning the decoded value previously stored for the key
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V> {
+        let encoded = self.trie.insert(key, self.codec.encode(value))?;
+        Some(self.codec.decode(&encoded).into_owned())
+    }
+
+    /// Decoded value of a key
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> Option<Cow<'_, V>> {
+        self.trie.get(key).map(|encoded| self.codec.decode(encoded))
+    }
+
+    /// Whether a key has a value, without decoding it
+    pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.trie.contains_key(key)
+    }
+
+    /// Removes a key, returning its decoded value
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        let encoded = self.trie.remove(key)?;
+        Some(self.codec.decode(&encoded).into_owned())
+    }
+
+    /// Decoded values of the keys starting with `prefix`, in key order
+    pub fn iter_prefix<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+    ) -> impl Iterator<Item = (Vec<K>, Cow<'_, V>)> + '_ {
+        self.trie
+            .iter_prefix(prefix)
+            .map(|(key, encoded)| (key, self.codec.decode(encoded)))
+    }
+}
@@ -0,0 +1,96 @@
+//! Command dispatch with unique-prefix abbreviations, as found in REPLs and CLIs
+
+use crate::trie::Trie;
+
+/// Outcome of `CommandSet::resolve`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution<'a, V> {
+    /// The input is a registered command
+    Exact(&'a V),
+    /// The input abbreviates a single command
+    UnambiguousPrefix { command: String, value: &'a V },
+    /// The input abbreviates several commands, listed in no particular order
+    Ambiguous(Vec<String>),
+    /// No command starts with the input
+    NotFound,
+}
+
+/// Registered commands, resolvable by any unambiguous abbreviation
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::command::{CommandSet, Resolution};
+///
+/// let mut commands = CommandSet::new();
+/// commands.register("status", 1);
+/// commands.register("stash", 2);
+/// commands.register("commit", 3);
+///
+/// assert_eq!(commands.resolve("commit"), Resolution::Exact(&3));
+/// assert_eq!(
+///     commands.resolve("co"),
+///     Resolution::UnambiguousPrefix { command: "commit".to_string(), value: &3 }
+/// );
+/// assert!(matches!(commands.resolve("st"), Resolution::Ambiguous(c) if c.len() == 2));
+/// assert_eq!(commands.resolve("push"), Resolution::NotFound);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandSet<V> {
+    commands: Trie<char, V>,
+}
+
+impl<V> CommandSet<V> {
+    /// Creates an empty command set
+    pub fn new() -> Self {
+        CommandSet {
+            commands: Trie::new(),
+        }
+    }
+
+    /// Registers a command, returning the value previously registered for it
+    pub fn register(&mut self, name: &str, value: V) -> Option<V> {
+        self.commands.insert(name.chars(), value)
+    }
+
+    /// Resolves user input to a command
+    ///
+    /// An exact match wins over longer commands starting with the input.
+    pub fn resolve(&self, input: &str) -> Resolution<'_, V> {
+        if let Some(value) = self.commands.get(input.chars()) {
+            return Resolution::Exact(value);
+        }
+        let mut candidates = self.commands.iter_prefix(input.chars());
+        match (candidates.next(), candidates.next()) {
+            (None, _) => Resolution::NotFound,
+            (Some((command, value)), None) => Resolution::UnambiguousPrefix {
+                command: command.into_iter().collect(),
+                value,
+            },
+            (Some(first), Some(second)) => Resolution::Ambiguous(
+                [first, second]
+                    .into_iter()
+                    .chain(candidates)
+                    .map(|(command, _)| command.into_iter().collect())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Commands starting with `input`, for completion or error messages
+    pub fn completions(&self, input: &str) -> Vec<String> {
+        let mut completions: Vec<String> = self
+            .commands
+            .iter_prefix(input.chars())
+            .map(|(command, _)| command.into_iter().collect())
+            .collect();
+        completions.sort();
+        completions
+    }
+}
+
+impl<V> Default for CommandSet<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,215 @@
+//! Completion queries for search-as-you-type backends
+
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+
+/// Page of completions returned by `Trie::complete_page`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<'a, K, V> {
+    /// Entries of the page, in lexicographic key order
+    pub entries: Vec<(Vec<K>, &'a V)>,
+    /// Key to pass as `after_key` to get the next page, `None` on the last page
+    pub next: Option<Vec<K>>,
+}
+
+/// Partially typed query over a `Trie`, created with `Trie::completion_session`
+///
+/// The session keeps the node of every typed prefix, so typing or erasing a symbol moves one
+/// node instead of walking the query again from the root.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::Trie;
+///
+/// let mut trie = Trie::new();
+/// for word in ["car", "card", "care", "cat", "dog"] {
+///     trie.insert(word.bytes(), ());
+/// }
+///
+/// let mut session = trie.completion_session();
+/// session.push(b'c');
+/// session.push(b'a');
+/// session.push(b'r');
+/// let keys: Vec<Vec<u8>> = session.current_completions(2).into_iter().map(|(k, _)| k).collect();
+/// assert_eq!(keys, vec![b"car".to_vec(), b"card".to_vec()]);
+///
+/// session.push(b'x');
+/// assert!(session.current_completions(2).is_empty());
+/// assert_eq!(session.pop(), Some(b'x'));
+/// assert_eq!(session.pop(), Some(b'r'));
+/// assert_eq!(session.current_completions(10).len(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompletionSession<'a, K: Eq + Ord + Clone, V> {
+    /// Node of every typed prefix matching keys, the root first
+    nodes: Vec<&'a TrieNode<K, V>>,
+    /// Symbols typed so far
+    query: Vec<K>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> CompletionSession<'a, K, V> {
+    /// Creates a session with an empty query over `trie`
+    pub fn new(trie: &'a Trie<K, V>) -> Self {
+        CompletionSession {
+            nodes: vec![&trie.root],
+            query: Vec::new(),
+        }
+    }
+
+    /// Symbols typed so far
+    pub fn query(&self) -> &[K] {
+        &self.query
+    }
+
+    /// Whether some key starts with the query
+    pub fn has_completions(&self) -> bool {
+        self.nodes.len() == self.query.len() + 1
+    }
+
+    /// Types `symbol` at the end of the query
+    pub fn push(&mut self, symbol: K) {
+        if self.has_completions() {
+            let node = self.nodes[self.nodes.len() - 1];
+            if let Ok(ix) = node.children.binary_search_by(|(c, _)| c.cmp(&symbol)) {
+                self.nodes.push(&node.children[ix].1);
+            }
+        }
+        self.query.push(symbol);
+    }
+
+    /// Erases the last symbol of the query, `None` when it is empty
+    pub fn pop(&mut self) -> Option<K> {
+        let symbol = self.query.pop()?;
+        if self.nodes.len() > self.query.len() + 1 {
+            self.nodes.pop();
+        }
+        Some(symbol)
+    }
+
+    /// Up to `k` keys starting with the query, with their values, in lexicographic order
+    pub fn current_completions(&self, k: usize) -> Vec<(Vec<K>, &'a V)> {
+        let mut entries = Vec::new();
+        if self.has_completions() && k > 0 {
+            let mut path = self.query.clone();
+            collect_page(
+                self.nodes[self.nodes.len() - 1],
+                &mut path,
+                None,
+                k,
+                &mut entries,
+            );
+        }
+        entries
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Starts a `CompletionSession` with an empty query, to complete a query typed one symbol
+    /// at a time
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("hello".chars(), 1);
+    ///
+    /// let mut session = trie.completion_session();
+    /// session.push('h');
+    /// assert_eq!(session.current_completions(1), vec![("hello".chars().collect(), &1)]);
+    /// ```
+    pub fn completion_session(&self) -> CompletionSession<'_, K, V> {
+        CompletionSession::new(self)
+    }
+
+    /// Returns up to `limit` completions of `prefix` in lexicographic order, starting after
+    /// `after_key`
+    ///
+    /// The continuation token of a page is the last key it contains, so pages stay stable
+    /// across stateless requests and subtrees before the token are skipped without being
+    /// visited.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// for (i, word) in ["car", "card", "care", "cart", "cat"].iter().enumerate() {
+    ///     trie.insert(word.bytes(), i);
+    /// }
+    ///
+    /// let page = trie.complete_page("car".bytes(), None, 2);
+    /// let keys: Vec<&[u8]> = page.entries.iter().map(|(k, _)| k.as_slice()).collect();
+    /// assert_eq!(keys, vec![&b"car"[..], b"card"]);
+    ///
+    /// let page = trie.complete_page("car".bytes(), page.next.as_deref(), 2);
+    /// let keys: Vec<&[u8]> = page.entries.iter().map(|(k, _)| k.as_slice()).collect();
+    /// assert_eq!(keys, vec![&b"care"[..], b"cart"]);
+    /// assert_eq!(page.next, None);
+    /// ```
+    pub fn complete_page<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+        after_key: Option<&[K]>,
+        limit: usize,
+    ) -> Page<'_, K, V> {
+        let mut path: Vec<K> = prefix.collect();
+        let mut entries = Vec::new();
+        if let Some(node) = self.root.find_node(path.iter().cloned()) {
+            // Collect one more entry to know if there is a next page
+            collect_page(
+                node,
+                &mut path,
+                after_key,
+                limit.saturating_add(1),
+                &mut entries,
+            );
+        }
+        let next = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|(k, _)| k.clone())
+        } else {
+            None
+        };
+        Page { entries, next }
+    }
+}
+
+/// Collects the entries under `node` with a key greater than `after`, in lexicographic order
+fn collect_page<'a, K: Eq + Ord + Clone, V>(
+    node: &'a TrieNode<K, V>,
+    path: &mut Vec<K>,
+    after: Option<&[K]>,
+    limit: usize,
+    entries: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    // Index of the first child which may hold keys after the token
+    let first_child = match after {
+        // The node itself is not after the token, only some of its children may be
+        Some(after) if after.starts_with(path) => match after.get(path.len()) {
+            Some(k) => match node.children.binary_search_by(|(c, _)| c.cmp(k)) {
+                Ok(ix) | Err(ix) => ix,
+            },
+            None => 0,
+        },
+        // The whole subtree is before the token
+        Some(after) if path.as_slice() < after => return,
+        _ => {
+            if let Some(value) = &node.value {
+                entries.push((path.clone(), value));
+            }
+            0
+        }
+    };
+    for (k, child) in &node.children[first_child..] {
+        if entries.len() >= limit {
+            return;
+        }
+        path.push(k.clone());
+        collect_page(child, path, after, limit, entries);
+        path.pop();
+    }
+}
@@ -0,0 +1,69 @@
+//! Hierarchical configuration keyed by dot-separated segments
+//!
+//! Lookups fall back from the most specific key to its parents: `service.db.timeout` resolves
+//! to the value of `service.db.timeout`, else `service.db`, else `service`.
+
+use crate::trie::Trie;
+
+/// Configuration values keyed by dotted paths, with fallback to the parent keys
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::config::HierarchicalConfig;
+///
+/// let mut config = HierarchicalConfig::new();
+/// config.insert("service", 30);
+/// config.insert("service.db", 5);
+/// config.insert("service.db.timeout", 1);
+///
+/// assert_eq!(config.resolve("service.db.timeout"), Some(&1));
+/// assert_eq!(config.resolve("service.db.retries"), Some(&5));
+/// assert_eq!(config.resolve("service.cache.timeout"), Some(&30));
+/// assert_eq!(config.resolve("services"), None);
+///
+/// assert_eq!(config.resolve_with_source("service.cache"), Some(("service".to_string(), &30)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HierarchicalConfig<V> {
+    trie: Trie<String, V>,
+}
+
+impl<V> HierarchicalConfig<V> {
+    /// Creates an empty configuration
+    pub fn new() -> Self {
+        HierarchicalConfig { trie: Trie::new() }
+    }
+
+    /// Sets the value of a dotted key, returning the previous one
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        self.trie.insert(segments(key), value)
+    }
+
+    /// Gets the value set for exactly this key, without fallback
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.trie.get(segments(key))
+    }
+
+    /// Gets the value of the most specific key along the path of `key`
+    pub fn resolve(&self, key: &str) -> Option<&V> {
+        self.trie.find_longest_prefix(segments(key))
+    }
+
+    /// Same as `resolve`, also returning the dotted key which supplied the value
+    pub fn resolve_with_source(&self, key: &str) -> Option<(String, &V)> {
+        let (ix, value) = self.trie.find_prefixes(segments(key)).pop()?;
+        let source: Vec<&str> = key.split('.').take(ix + 1).collect();
+        Some((source.join("."), value))
+    }
+}
+
+impl<V> Default for HierarchicalConfig<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn segments(key: &str) -> impl Iterator<Item = String> + '_ {
+    key.split('.').map(str::to_string)
+}
@@ -0,0 +1,183 @@
+//! Counting trie for n-gram and query-log statistics
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+
+/// Trie whose values are occurrence counters
+///
+/// Counters saturate at `u64::MAX` instead of wrapping around, use `checked_add` and
+/// `checked_count` to be told about it.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::count::CountTrie;
+///
+/// let mut counts = CountTrie::new();
+/// for query in ["rust", "rust trie", "rust trie", "rust book", "ruby"] {
+///     counts.bump(query.bytes());
+/// }
+///
+/// assert_eq!(counts.get("rust trie".bytes()), 2);
+/// assert_eq!(counts.count("rust".bytes()), 4);
+/// assert_eq!(counts.count("ru".bytes()), 5);
+///
+/// let top = counts.top_continuations("rust ".bytes(), 1);
+/// assert_eq!(top, vec![(b"rust trie".to_vec(), 2)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountTrie<K: Eq + Ord + Clone> {
+    trie: Trie<K, u64>,
+}
+
+impl<K: Eq + Ord + Clone> CountTrie<K> {
+    /// Creates an empty counting trie
+    pub fn new() -> Self {
+        CountTrie { trie: Trie::new() }
+    }
+
+    /// Increments the counter of a key, returning the new count
+    pub fn bump<I: Iterator<Item = K>>(&mut self, key: I) -> u64 {
+        self.add(key, 1)
+    }
+
+    /// Adds `n` to the counter of a key, returning the new count
+    pub fn add<I: Iterator<Item = K>>(&mut self, key: I, n: u64) -> u64 {
+        if n == 0 {
+            // Keys are only stored with a positive counter
+            return self.get(key);
+        }
+        let counter = self.trie.get_or_insert_with(key, || 0);
+        *counter = counter.saturating_add(n);
+        *counter
+    }
+
+    /// Adds `n` to the counter of a key, returning the new count
+    ///
+    /// Fails with `TrieError::Overflow`, leaving the counter unchanged, when it would exceed
+    /// `u64::MAX`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::count::CountTrie;
+    ///
+    /// let mut counts = CountTrie::new();
+    /// counts.add("a".bytes(), u64::MAX - 1);
+    /// assert_eq!(counts.checked_add("a".bytes(), 1).unwrap(), u64::MAX);
+    /// assert!(counts.checked_add("a".bytes(), 1).is_err());
+    /// assert_eq!(counts.get("a".bytes()), u64::MAX);
+    ///
+    /// assert_eq!(counts.checked_add("b".bytes(), 0).unwrap(), 0);
+    /// assert_eq!(counts.iter().count(), 1);
+    /// ```
+    pub fn checked_add<I: Iterator<Item = K>>(&mut self, key: I, n: u64) -> Result<u64, TrieError> {
+        if n == 0 {
+            // Keys are only stored with a positive counter
+            return Ok(self.get(key));
+        }
+        let counter = self.trie.get_or_insert_with(key, || 0);
+        *counter = counter.checked_add(n).ok_or_else(|| {
+            TrieError::Overflow(format!("Adding {} to the counter {} overflows", n, counter))
+        })?;
+        Ok(*counter)
+    }
+
+    /// Decrements the counter of a key, returning the new count
+    pub fn decrement<I: Iterator<Item = K>>(&mut self, key: I) -> u64 {
+        self.subtract(key, 1)
+    }
+
+    /// Subtracts `n` from the counter of a key, saturating at 0, returning the new count
+    ///
+    /// Keys whose counter reaches 0 are removed, with the nodes left empty, so iteration and
+    /// prefix counts only see keys still counted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::count::CountTrie;
+    ///
+    /// let mut counts = CountTrie::new();
+    /// counts.add("rust".bytes(), 2);
+    /// counts.bump("ruby".bytes());
+    ///
+    /// assert_eq!(counts.decrement("ruby".bytes()), 0);
+    /// assert_eq!(counts.subtract("rust".bytes(), 1), 1);
+    /// assert_eq!(counts.count("ru".bytes()), 1);
+    /// assert_eq!(counts.iter().count(), 1);
+    /// assert_eq!(counts.decrement("go".bytes()), 0);
+    /// ```
+    pub fn subtract<I: Iterator<Item = K>>(&mut self, key: I, n: u64) -> u64 {
+        let key: Vec<K> = key.collect();
+        let Some(counter) = self.trie.get_mut(key.iter().cloned()) else {
+            return 0;
+        };
+        *counter = counter.saturating_sub(n);
+        if *counter > 0 {
+            return *counter;
+        }
+        self.trie.remove(key.into_iter());
+        0
+    }
+
+    /// Removes a key, returning its counter
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> u64 {
+        self.trie.remove(key).unwrap_or(0)
+    }
+
+    /// Counter of exactly this key, 0 if it was never counted
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> u64 {
+        self.trie.get(key).copied().unwrap_or(0)
+    }
+
+    /// Sum of the counters of all the keys starting with `prefix`, saturating at `u64::MAX`
+    pub fn count<I: Iterator<Item = K>>(&self, prefix: I) -> u64 {
+        self.trie
+            .find_postfixes(prefix)
+            .into_iter()
+            .fold(0, |sum, c| sum.saturating_add(*c))
+    }
+
+    /// Sum of the counters of all the keys starting with `prefix`
+    ///
+    /// Fails with `TrieError::Overflow` when the sum exceeds `u64::MAX`.
+    pub fn checked_count<I: Iterator<Item = K>>(&self, prefix: I) -> Result<u64, TrieError> {
+        self.trie
+            .find_postfixes(prefix)
+            .into_iter()
+            .try_fold(0u64, |sum, c| sum.checked_add(*c))
+            .ok_or_else(|| TrieError::Overflow("Sum of the counters overflows".to_string()))
+    }
+
+    /// The `k` most frequent keys strictly extending `prefix`, most frequent first
+    ///
+    /// Ties are broken by key order.
+    pub fn top_continuations<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+        k: usize,
+    ) -> Vec<(Vec<K>, u64)> {
+        let prefix: Vec<K> = prefix.collect();
+        let mut continuations: Vec<(Vec<K>, u64)> = self
+            .trie
+            .iter_prefix(prefix.iter().cloned())
+            .filter(|(key, _)| key.len() > prefix.len())
+            .map(|(key, count)| (key, *count))
+            .collect();
+        continuations.sort_by(|(ka, a), (kb, b)| b.cmp(a).then_with(|| ka.cmp(kb)));
+        continuations.truncate(k);
+        continuations
+    }
+
+    /// Iterate the keys with their counter
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<K>, u64)> + '_ {
+        self.trie.iter().map(|(k, c)| (k, *c))
+    }
+}
+
+impl<K: Eq + Ord + Clone> Default for CountTrie<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
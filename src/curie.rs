@@ -0,0 +1,106 @@
+//! Compression of URIs to CURIEs (`prefix:reference`) and back
+//!
+//! This packages the longest prefix match use case: namespaces are stored in a `Trie` to find
+//! the most specific one for a URI, and a paired reverse `Trie` maps prefix names back to
+//! their namespace.
+
+use crate::trie::Trie;
+use std::fmt;
+
+/// Compact URI, made of a prefix name and a reference local to its namespace
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Curie {
+    pub prefix: String,
+    pub reference: String,
+}
+
+impl fmt::Display for Curie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.prefix, self.reference)
+    }
+}
+
+/// Converter between URIs and CURIEs, built from `prefix -> namespace` entries
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::curie::UriShortener;
+///
+/// let shortener: UriShortener = [
+///     ("obo", "http://purl.obolibrary.org/obo/"),
+///     ("doid", "http://purl.obolibrary.org/obo/DOID_"),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let curie = shortener.compress("http://purl.obolibrary.org/obo/DOID_1234").unwrap();
+/// assert_eq!(curie.to_string(), "doid:1234");
+/// assert_eq!(shortener.compress("http://purl.obolibrary.org/obo/GO_1").unwrap().to_string(), "obo:GO_1");
+/// assert!(shortener.compress("https://example.org/").is_none());
+///
+/// assert_eq!(
+///     shortener.expand("doid:1234"),
+///     Some("http://purl.obolibrary.org/obo/DOID_1234".to_string())
+/// );
+/// assert_eq!(shortener.expand("unknown:1234"), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UriShortener {
+    /// Namespace -> prefix name
+    namespaces: Trie<u8, String>,
+    /// Prefix name -> namespace
+    prefixes: Trie<u8, String>,
+}
+
+impl UriShortener {
+    /// Creates an empty converter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds a prefix name to a namespace, returning the namespace previously bound to it
+    ///
+    /// Rebinding a prefix makes its previous namespace unavailable for compression.
+    pub fn insert(&mut self, prefix: &str, namespace: &str) -> Option<String> {
+        self.namespaces.insert(namespace.bytes(), prefix.to_string());
+        self.prefixes.insert(prefix.bytes(), namespace.to_string())
+    }
+
+    /// Compresses a URI using the longest namespace it starts with
+    pub fn compress(&self, uri: &str) -> Option<Curie> {
+        self.namespaces
+            .find_prefixes(uri.bytes())
+            .into_iter()
+            .rev()
+            // Skip namespaces whose prefix name has been rebound since
+            .find(|(ix, prefix)| self.namespace(prefix) == Some(&uri[..ix + 1]))
+            .map(|(ix, prefix)| Curie {
+                prefix: prefix.clone(),
+                reference: uri[ix + 1..].to_string(),
+            })
+    }
+
+    /// Expands a CURIE written as `prefix:reference` to the full URI
+    pub fn expand(&self, curie: &str) -> Option<String> {
+        let (prefix, reference) = curie.split_once(':')?;
+        self.prefixes
+            .get(prefix.bytes())
+            .map(|namespace| format!("{}{}", namespace, reference))
+    }
+
+    /// Namespace bound to a prefix name
+    pub fn namespace(&self, prefix: &str) -> Option<&str> {
+        self.prefixes.get(prefix.bytes()).map(String::as_str)
+    }
+}
+
+impl<P: AsRef<str>, N: AsRef<str>> FromIterator<(P, N)> for UriShortener {
+    fn from_iter<T: IntoIterator<Item = (P, N)>>(iter: T) -> Self {
+        let mut shortener = UriShortener::new();
+        for (prefix, namespace) in iter {
+            shortener.insert(prefix.as_ref(), namespace.as_ref());
+        }
+        shortener
+    }
+}
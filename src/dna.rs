@@ -0,0 +1,244 @@
+//! Key adapter for DNA sequences over the A/C/G/T bases
+//!
+//! A `Trie<Base, V>` stores a byte per base in its nodes, like ASCII keys would, the gain
+//! being the validation of the bases. `PackedKmer` packs k-mers in 2 bits per base for
+//! storage outside of the `Trie`.
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+
+/// DNA base, with a 2-bit value for packing
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Base {
+    A = 0,
+    C = 1,
+    G = 2,
+    T = 3,
+}
+
+impl Base {
+    /// Parses an ASCII base, case-insensitive
+    ///
+    /// Ambiguous bases (`N`, IUPAC codes) and other characters fail with `TrieError::InvalidKey`.
+    pub fn from_ascii(b: u8) -> Result<Self, TrieError> {
+        match b.to_ascii_uppercase() {
+            b'A' => Ok(Base::A),
+            b'C' => Ok(Base::C),
+            b'G' => Ok(Base::G),
+            b'T' => Ok(Base::T),
+            _ => Err(TrieError::InvalidKey(format!(
+                "Invalid base {:?}",
+                b as char
+            ))),
+        }
+    }
+
+    /// Upper-case ASCII letter of the base
+    pub fn to_ascii(self) -> u8 {
+        b"ACGT"[self as usize]
+    }
+
+    /// Base paired with this one on the other strand
+    pub fn complement(self) -> Self {
+        Self::from_bits(3 - self as u8)
+    }
+
+    /// 2-bit value of the base
+    pub fn bits(self) -> u8 {
+        self as u8
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0 => Base::A,
+            1 => Base::C,
+            2 => Base::G,
+            _ => Base::T,
+        }
+    }
+}
+
+/// Converts an ASCII sequence in bases, to be used as a `Trie` key
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::dna::{self, Base};
+/// use ptrie::Trie;
+///
+/// let mut trie = Trie::new();
+/// trie.insert(dna::encode(b"ACGT").unwrap().into_iter(), 1);
+/// assert_eq!(trie.get([Base::A, Base::C, Base::G, Base::T].into_iter()), Some(&1));
+/// let err = dna::encode(b"ACNT").unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid base 'N' at position 2");
+/// ```
+pub fn encode(seq: &[u8]) -> Result<Vec<Base>, TrieError> {
+    seq.iter().enumerate().map(|(pos, b)| base_at(*b, pos)).collect()
+}
+
+/// Converts a base of a sequence, naming its position on failure
+fn base_at(b: u8, pos: usize) -> Result<Base, TrieError> {
+    Base::from_ascii(b).map_err(|err| TrieError::InvalidKey(format!("{} at position {}", err, pos)))
+}
+
+/// Converts bases back to an upper-case ASCII sequence
+pub fn decode(bases: &[Base]) -> String {
+    bases.iter().map(|b| b.to_ascii() as char).collect()
+}
+
+/// Reverse complement of an ASCII sequence, as bases
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::dna;
+///
+/// let rc = dna::reverse_complement(b"AACG").unwrap();
+/// assert_eq!(dna::decode(&rc), "CGTT");
+/// ```
+pub fn reverse_complement(seq: &[u8]) -> Result<Vec<Base>, TrieError> {
+    seq.iter()
+        .enumerate()
+        .rev()
+        .map(|(pos, b)| base_at(*b, pos).map(Base::complement))
+        .collect()
+}
+
+/// Gets the value of a sequence from either strand, trying the sequence itself first
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::{dna, Trie};
+///
+/// let mut trie = Trie::new();
+/// trie.insert(dna::encode(b"AACG").unwrap().into_iter(), "kmer");
+/// assert_eq!(dna::get_either_strand(&trie, b"CGTT").unwrap(), Some(&"kmer"));
+/// assert_eq!(dna::get_either_strand(&trie, b"GGGG").unwrap(), None);
+/// ```
+pub fn get_either_strand<'a, V>(
+    trie: &'a Trie<Base, V>,
+    seq: &[u8],
+) -> Result<Option<&'a V>, TrieError> {
+    let forward = encode(seq)?;
+    if let Some(value) = trie.get(forward.into_iter()) {
+        return Ok(Some(value));
+    }
+    Ok(trie.get(reverse_complement(seq)?.into_iter()))
+}
+
+/// Canonical form of a k-mer: the smallest of the sequence and its reverse complement
+pub fn canonical(seq: &[u8]) -> Result<Vec<Base>, TrieError> {
+    let forward = encode(seq)?;
+    let reverse = reverse_complement(seq)?;
+    Ok(forward.min(reverse))
+}
+
+/// K-mer of up to 32 bases packed in a `u64`, 4 times smaller than its ASCII form when kept
+/// outside of a `Trie`, whose keys are the unpacked `bases()`
+///
+/// K-mers are ordered lexicographically, like their ASCII form.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::dna::PackedKmer;
+/// use ptrie::Trie;
+///
+/// let kmer = PackedKmer::from_ascii(b"GATTACA").unwrap();
+/// assert_eq!(kmer.len(), 7);
+/// assert_eq!(kmer.to_string(), "GATTACA");
+/// assert_eq!(kmer.reverse_complement().to_string(), "TGTAATC");
+///
+/// let mut trie = Trie::new();
+/// trie.insert(kmer.bases(), 1);
+/// assert!(trie.contains_key(PackedKmer::from_ascii(b"GATTACA").unwrap().bases()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedKmer {
+    bits: u64,
+    len: u8,
+}
+
+impl PackedKmer {
+    /// Maximum number of bases in a packed k-mer
+    pub const MAX_LEN: usize = 32;
+
+    /// Packs an ASCII sequence of at most 32 bases
+    pub fn from_ascii(seq: &[u8]) -> Result<Self, TrieError> {
+        if seq.len() > Self::MAX_LEN {
+            return Err(TrieError::InvalidKey(format!(
+                "K-mer of {} bases does not fit in {} bases",
+                seq.len(),
+                Self::MAX_LEN
+            )));
+        }
+        let mut bits = 0u64;
+        for (pos, b) in seq.iter().enumerate() {
+            bits = (bits << 2) | base_at(*b, pos)?.bits() as u64;
+        }
+        Ok(PackedKmer {
+            bits,
+            len: seq.len() as u8,
+        })
+    }
+
+    /// Number of bases
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Checks if the k-mer has no base
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate the bases, usable as a `Trie` key
+    pub fn bases(&self) -> impl Iterator<Item = Base> + Clone {
+        let (bits, len) = (self.bits, self.len as u32);
+        (0..len).map(move |i| Base::from_bits((bits >> (2 * (len - 1 - i))) as u8))
+    }
+
+    /// Reverse complement of the k-mer
+    pub fn reverse_complement(&self) -> Self {
+        // The first base ends up in the least significant bits
+        let mut packed = 0u64;
+        for (i, b) in self.bases().enumerate() {
+            packed |= (b.complement().bits() as u64) << (2 * i);
+        }
+        PackedKmer {
+            bits: packed,
+            len: self.len,
+        }
+    }
+}
+
+impl PackedKmer {
+    /// Bases aligned on the most significant bits, so a prefix compares below its extensions
+    fn left_aligned(&self) -> u64 {
+        self.bits.checked_shl(64 - 2 * self.len as u32).unwrap_or(0)
+    }
+}
+
+impl Ord for PackedKmer {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.left_aligned(), self.len).cmp(&(other.left_aligned(), other.len))
+    }
+}
+
+impl PartialOrd for PackedKmer {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for PackedKmer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for b in self.bases() {
+            write!(f, "{}", b.to_ascii() as char)?;
+        }
+        Ok(())
+    }
+}
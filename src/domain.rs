@@ -0,0 +1,128 @@
+//! Public-suffix style matching of domain names, label by label
+
+use crate::trie::Trie;
+use crate::trie_node::NodeRef;
+
+/// Label of a registered suffix, stored from the top-level domain down
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Label {
+    Exact(String),
+    /// `*` label, matching any single label
+    Wildcard,
+}
+
+/// Longest registered suffix of a domain, found by `DomainSuffixMatcher::match_domain`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuffixMatch<'a, V> {
+    /// Labels of the domain matched by the suffix, such as `co.uk`
+    pub suffix: String,
+    /// Suffix with one more label of the domain, such as `example.co.uk`, if the domain has one
+    pub registrable: Option<String>,
+    pub value: &'a V,
+}
+
+/// Matcher of domain names against registered suffixes
+///
+/// Domains are compared label by label, case-insensitively, so `co.uk` never matches
+/// `xco.uk`. A `*` label in a suffix matches any label.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::domain::DomainSuffixMatcher;
+///
+/// let mut matcher = DomainSuffixMatcher::new();
+/// matcher.insert_suffix("uk", 1);
+/// matcher.insert_suffix("co.uk", 2);
+/// matcher.insert_suffix("*.ck", 3);
+///
+/// let m = matcher.match_domain("shop.Example.co.uk").unwrap();
+/// assert_eq!(m.suffix, "co.uk");
+/// assert_eq!(m.registrable.as_deref(), Some("example.co.uk"));
+/// assert_eq!(*m.value, 2);
+///
+/// let m = matcher.match_domain("www.site.gov.ck").unwrap();
+/// assert_eq!(m.suffix, "gov.ck");
+/// assert_eq!(m.registrable.as_deref(), Some("site.gov.ck"));
+///
+/// assert_eq!(matcher.match_domain("co.uk").unwrap().registrable, None);
+/// assert!(matcher.match_domain("example.com").is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DomainSuffixMatcher<V> {
+    trie: Trie<Label, V>,
+}
+
+impl<V> DomainSuffixMatcher<V> {
+    /// Creates an empty matcher
+    pub fn new() -> Self {
+        DomainSuffixMatcher { trie: Trie::new() }
+    }
+
+    /// Registers a suffix such as `co.uk` or `*.ck`, returning the value previously registered
+    pub fn insert_suffix(&mut self, suffix: &str, value: V) -> Option<V> {
+        let key = labels(suffix).map(|l| match l.as_str() {
+            "*" => Label::Wildcard,
+            _ => Label::Exact(l),
+        });
+        self.trie.insert(key, value)
+    }
+
+    /// Finds the longest registered suffix of `domain`
+    pub fn match_domain(&self, domain: &str) -> Option<SuffixMatch<'_, V>> {
+        let labels: Vec<String> = labels(domain).collect();
+        let (len, value) = self.longest_suffix(&labels)?;
+        let join = |n: usize| {
+            let mut parts: Vec<&str> = labels[..n].iter().map(String::as_str).collect();
+            parts.reverse();
+            parts.join(".")
+        };
+        Some(SuffixMatch {
+            suffix: join(len),
+            registrable: (len < labels.len()).then(|| join(len + 1)),
+            value,
+        })
+    }
+
+    /// Number of labels and value of the longest registered suffix of reversed `labels`
+    pub(crate) fn longest_suffix(&self, labels: &[String]) -> Option<(usize, &V)> {
+        longest_match(self.trie.root(), labels)
+    }
+}
+
+impl<V> Default for DomainSuffixMatcher<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lower-cased labels of a domain, from the top-level domain down
+pub(crate) fn labels(domain: &str) -> impl Iterator<Item = String> + '_ {
+    domain
+        .trim_end_matches('.')
+        .rsplit('.')
+        .filter(|l| !l.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Deepest node holding a value along `labels`, trying both exact and wildcard edges
+fn longest_match<'a, V>(node: NodeRef<'a, Label, V>, labels: &[String]) -> Option<(usize, &'a V)> {
+    let (label, rest) = labels.split_first()?;
+    let mut best: Option<(usize, &'a V)> = None;
+    for child in [
+        node.child(&Label::Exact(label.clone())),
+        node.child(&Label::Wildcard),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        let here = child.value().map(|v| (1, v));
+        let deeper = longest_match(child, rest).map(|(n, v)| (n + 1, v));
+        for candidate in [deeper, here].into_iter().flatten() {
+            if best.is_none_or(|(n, _)| candidate.0 > n) {
+                best = Some(candidate);
+            }
+        }
+    }
+    best
+}
@@ -0,0 +1,253 @@
+//! Human-readable renderings of a `Trie`: ASCII-art sketches of subtrees for debugging large
+//! tries, and a canonical listing of the entries for golden tests
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+use std::fmt::{Debug, Write as _};
+use std::io::Write;
+
+/// Key elements with a canonical textual form, used by `Trie::to_debug_string`
+pub trait KeyFormat: Sized {
+    /// Appends the textual form of a whole key to `out`
+    fn format_key(key: &[Self], out: &mut String);
+}
+
+/// Byte string literal, escaping the non-printable bytes: `"a\x00b"`
+impl KeyFormat for u8 {
+    fn format_key(key: &[Self], out: &mut String) {
+        let _ = write!(out, "\"{}\"", key.escape_ascii());
+    }
+}
+
+/// String literal, escaping the non-printable characters: `"a\nb"`
+impl KeyFormat for char {
+    fn format_key(key: &[Self], out: &mut String) {
+        out.push('"');
+        for c in key {
+            out.extend(c.escape_debug());
+        }
+        out.push('"');
+    }
+}
+
+/// Escaped segments separated by slashes: `["usr" / "bin"]`
+impl KeyFormat for String {
+    fn format_key(key: &[Self], out: &mut String) {
+        out.push('[');
+        for (i, segment) in key.iter().enumerate() {
+            if i > 0 {
+                out.push_str(" / ");
+            }
+            let _ = write!(out, "{:?}", segment);
+        }
+        out.push(']');
+    }
+}
+
+/// Bits as a string of `0` and `1`: `0b0110`
+impl KeyFormat for bool {
+    fn format_key(key: &[Self], out: &mut String) {
+        out.push_str("0b");
+        out.extend(key.iter().map(|b| if *b { '1' } else { '0' }));
+    }
+}
+
+macro_rules! integer_key_format {
+    ($($t:ty),*) => {$(
+        /// Numbers separated by dots: `1.2.3`
+        impl KeyFormat for $t {
+            fn format_key(key: &[Self], out: &mut String) {
+                for (i, n) in key.iter().enumerate() {
+                    if i > 0 {
+                        out.push('.');
+                    }
+                    let _ = write!(out, "{}", n);
+                }
+            }
+        }
+    )*};
+}
+
+integer_key_format!(u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Filters applied by `Trie::dump`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpOptions {
+    /// Depth below the prefix after which children are only counted
+    pub max_depth: Option<usize>,
+    /// Number of children written per node, the others are only counted
+    pub max_children: Option<usize>,
+    /// Writes the values next to their edge
+    pub show_values: bool,
+}
+
+impl Default for DumpOptions {
+    /// Writes the whole subtree with its values
+    fn default() -> Self {
+        DumpOptions {
+            max_depth: None,
+            max_children: None,
+            show_values: true,
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone + KeyFormat, V: Debug> Trie<K, V> {
+    /// Lists the entries in key order, one `key => value` line each, for snapshot tests
+    ///
+    /// Keys are written with their `KeyFormat`, so the output only depends on the entries:
+    /// not on the insertion order, the internal structure or the `Debug` format of the `Trie`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("tab\t".bytes(), 2);
+    /// t.insert("caf\u{e9}".bytes(), 1);
+    /// assert_eq!(
+    ///     t.to_debug_string(),
+    ///     "\"caf\\xc3\\xa9\" => 1\n\"tab\\t\" => 2\n"
+    /// );
+    /// ```
+    pub fn to_debug_string(&self) -> String {
+        let mut out = String::new();
+        for (key, value) in self.iter() {
+            K::format_key(&key, &mut out);
+            let _ = writeln!(out, " => {:?}", value);
+        }
+        out
+    }
+}
+
+/// Line left to write, with the indentation of its parent
+enum Line<'a, K: Eq + Ord + Clone, V> {
+    Node {
+        edge: &'a K,
+        node: &'a TrieNode<K, V>,
+        indent: String,
+        last: bool,
+        depth: usize,
+    },
+    Elided {
+        count: usize,
+        indent: String,
+    },
+}
+
+impl<K: Eq + Ord + Clone + Debug, V: Debug> Trie<K, V> {
+    /// Writes a sketch of the subtree under `prefix` to `out`, one edge per line
+    ///
+    /// Children beyond `max_children`, and nodes deeper than `max_depth` below the prefix, are
+    /// summarized by their count. Nothing is written when the prefix is not in the `Trie`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::dump::DumpOptions;
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert(vec!['a', 'b'].into_iter(), 1);
+    /// t.insert(vec!['a', 'c'].into_iter(), 2);
+    /// t.insert(vec!['a', 'c', 'd'].into_iter(), 3);
+    /// t.insert(vec!['a', 'e'].into_iter(), 4);
+    ///
+    /// let mut out = Vec::new();
+    /// let options = DumpOptions { max_depth: Some(1), max_children: Some(2), show_values: true };
+    /// t.dump("a".chars(), &options, &mut out).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "['a']\n\
+    ///      |-- 'b' = 1\n\
+    ///      |-- 'c' = 2 (1 children)\n\
+    ///      `-- ... 1 more\n"
+    /// );
+    /// ```
+    pub fn dump<I: Iterator<Item = K>, W: Write>(
+        &self,
+        prefix: I,
+        options: &DumpOptions,
+        out: &mut W,
+    ) -> Result<(), TrieError> {
+        let prefix: Vec<K> = prefix.collect();
+        let Some(root) = self.root.find_node(prefix.iter().cloned()) else {
+            return Ok(());
+        };
+        write!(out, "{:?}", prefix)?;
+        write_value(root, options, out)?;
+        writeln!(out)?;
+        let mut stack = Vec::new();
+        push_children(&mut stack, root, String::new(), 1, options);
+        while let Some(line) = stack.pop() {
+            match line {
+                Line::Node {
+                    edge,
+                    node,
+                    indent,
+                    last,
+                    depth,
+                } => {
+                    let branch = if last { "`-- " } else { "|-- " };
+                    write!(out, "{}{}{:?}", indent, branch, edge)?;
+                    write_value(node, options, out)?;
+                    let child_indent = format!("{}{}", indent, if last { "    " } else { "|   " });
+                    if options.max_depth.is_some_and(|max| depth >= max) {
+                        if !node.children.is_empty() {
+                            write!(out, " ({} children)", node.children.len())?;
+                        }
+                    } else {
+                        push_children(&mut stack, node, child_indent, depth + 1, options);
+                    }
+                    writeln!(out)?;
+                }
+                Line::Elided { count, indent } => {
+                    writeln!(out, "{}`-- ... {} more", indent, count)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_value<K: Eq + Ord + Clone, V: Debug, W: Write>(
+    node: &TrieNode<K, V>,
+    options: &DumpOptions,
+    out: &mut W,
+) -> Result<(), TrieError> {
+    if let (true, Some(value)) = (options.show_values, &node.value) {
+        write!(out, " = {:?}", value)?;
+    }
+    Ok(())
+}
+
+/// Pushes the lines of the children of `node` so they pop in key order
+fn push_children<'a, K: Eq + Ord + Clone, V>(
+    stack: &mut Vec<Line<'a, K, V>>,
+    node: &'a TrieNode<K, V>,
+    indent: String,
+    depth: usize,
+    options: &DumpOptions,
+) {
+    let shown = options
+        .max_children
+        .map_or(node.children.len(), |max| max.min(node.children.len()));
+    let elided = node.children.len() - shown;
+    if elided > 0 {
+        stack.push(Line::Elided {
+            count: elided,
+            indent: indent.clone(),
+        });
+    }
+    for (i, (edge, child)) in node.children[..shown].iter().enumerate().rev() {
+        stack.push(Line::Node {
+            edge,
+            node: child,
+            indent: indent.clone(),
+            last: elided == 0 && i + 1 == shown,
+            depth,
+        });
+    }
+}
@@ -1,20 +1,107 @@
 //! Errors thrown by the library
 
 use std::error::Error;
-use std::fmt;
+use std::{fmt, io};
 
 /// Enum of errors returned by this library
+///
+/// Messages name the part of the key involved when it is cheap to do so, like the number of
+/// elements matched before a lookup failed.
 #[derive(Debug)]
 pub enum TrieError {
     NotFound(String),
+    InvalidPattern(String),
+    InvalidKey(String),
+    QuotaExceeded(String),
+    DepthExceeded(String),
+    /// An insert would go beyond the memory budget of the builder
+    BudgetExceeded(String),
+    /// Input which does not describe a `Trie`, like a column of the wrong type
+    InvalidData(String),
+    /// A counter would exceed the range of its type
+    Overflow(String),
+    /// Failure of the underlying reader or writer
+    Io(io::Error),
 }
 
-impl Error for TrieError {}
+impl Error for TrieError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TrieError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TrieError {
+    fn from(err: io::Error) -> Self {
+        TrieError::Io(err)
+    }
+}
 
 impl fmt::Display for TrieError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             TrieError::NotFound(ref msg) => write!(f, "{}", msg),
+            TrieError::InvalidPattern(ref msg) => write!(f, "{}", msg),
+            TrieError::InvalidKey(ref msg) => write!(f, "{}", msg),
+            TrieError::QuotaExceeded(ref msg) => write!(f, "{}", msg),
+            TrieError::DepthExceeded(ref msg) => write!(f, "{}", msg),
+            TrieError::BudgetExceeded(ref msg) => write!(f, "{}", msg),
+            TrieError::InvalidData(ref msg) => write!(f, "{}", msg),
+            TrieError::Overflow(ref msg) => write!(f, "{}", msg),
+            TrieError::Io(ref err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+/// Structural inconsistency reported by `Trie::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantError {
+    /// The children of a node at `depth` are out of order at `index`
+    UnsortedChildren { depth: usize, index: usize },
+    /// A node at `depth` has two children with the same edge key at `index`
+    DuplicateEdge { depth: usize, index: usize },
+}
+
+impl Error for InvariantError {}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvariantError::UnsortedChildren { depth, index } => {
+                write!(
+                    f,
+                    "Children out of order at depth {}, index {}",
+                    depth, index
+                )
+            }
+            InvariantError::DuplicateEdge { depth, index } => {
+                write!(f, "Duplicate edge key at depth {}, index {}", depth, index)
+            }
         }
     }
 }
+
+/// Error returned by `Trie::try_insert` when the key already has a value
+///
+/// Gives access to the value already stored and hands back the value which was not inserted.
+#[derive(Debug)]
+pub struct OccupiedError<'a, V> {
+    /// Value already stored for the key
+    pub existing: &'a mut V,
+    /// Value which was not inserted
+    pub value: V,
+}
+
+impl<V: fmt::Debug> Error for OccupiedError<'_, V> {}
+
+impl<V: fmt::Debug> fmt::Display for OccupiedError<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Key already has the value {:?}, {:?} was not inserted",
+            self.existing, self.value
+        )
+    }
+}
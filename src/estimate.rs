@@ -0,0 +1,112 @@
+//! Approximate key counts below a prefix, for instant result counts in interactive tools
+
+use crate::rng::SplitMix;
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+
+/// Number of random paths walked for each node counted exactly, the work of a sample being
+/// about the depth of the subtree
+const NODES_PER_SAMPLE: usize = 16;
+
+/// Bounds on the number of random paths walked by `Trie::estimate_descendants`
+const MIN_SAMPLES: usize = 16;
+const MAX_SAMPLES: usize = 100_000;
+
+/// Approximate number of keys below a prefix, returned by `Trie::estimate_descendants`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    /// Estimated number of keys strictly longer than the prefix and starting with it
+    pub count: usize,
+    /// Whether `count` is exact, the subtree being small enough to be counted
+    pub exact: bool,
+    /// Number of random paths the estimate is averaged from, 0 when exact
+    pub samples: usize,
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Approximate number of keys strictly longer than `prefix` and starting with it, `None`
+    /// when no key starts with it
+    ///
+    /// The `Trie` does not maintain key counts, so small subtrees are counted exactly and larger
+    /// ones are estimated from random paths from the node of the prefix, each weighted by the
+    /// product of the fan-outs along it (Knuth's estimator). About `1 / error_bound²` paths
+    /// are walked, bounding the relative standard error by `error_bound` on balanced subtrees;
+    /// lopsided ones can be off by more. Paths are drawn from a fixed seed, so the estimate of
+    /// an unchanged `Trie` is stable.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for i in 0..20_000 {
+    ///     t.insert(format!("log/{i:05}").bytes(), ());
+    /// }
+    /// t.insert("logo".bytes(), ());
+    ///
+    /// let small = t.estimate_descendants("log/0000".bytes(), 0.1).unwrap();
+    /// assert!(small.exact);
+    /// assert_eq!(small.count, 10);
+    ///
+    /// let large = t.estimate_descendants("log/".bytes(), 0.1).unwrap();
+    /// assert!(!large.exact);
+    /// assert!((18_000..22_000).contains(&large.count));
+    /// assert!(t.estimate_descendants("blog".bytes(), 0.1).is_none());
+    /// ```
+    pub fn estimate_descendants<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+        error_bound: f64,
+    ) -> Option<Estimate> {
+        let node = self.root.find_node(prefix)?;
+        let samples = if error_bound > 0.0 {
+            (error_bound * error_bound)
+                .recip()
+                .ceil()
+                .clamp(MIN_SAMPLES as f64, MAX_SAMPLES as f64) as usize
+        } else {
+            MAX_SAMPLES
+        };
+        if let Some(count) = count_within(node, samples.saturating_mul(NODES_PER_SAMPLE)) {
+            return Some(Estimate {
+                count: count - usize::from(node.value.is_some()),
+                exact: true,
+                samples: 0,
+            });
+        }
+        let mut rng = SplitMix::new(0x7072_6566_6978);
+        let mut total = 0.0;
+        for _ in 0..samples {
+            let (mut path, mut weight) = (node, 1.0);
+            while !path.children.is_empty() {
+                weight *= path.children.len() as f64;
+                path = &path.children[rng.below(path.children.len())].1;
+                if path.value.is_some() {
+                    total += weight;
+                }
+            }
+        }
+        Some(Estimate {
+            count: (total / samples as f64).round() as usize,
+            exact: false,
+            samples,
+        })
+    }
+}
+
+/// Number of values in `node` and its descendants, `None` when there are more than
+/// `max_nodes` nodes to visit
+fn count_within<K: Eq + Ord + Clone, V>(node: &TrieNode<K, V>, max_nodes: usize) -> Option<usize> {
+    let (mut count, mut visited) = (0, 0);
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        visited += 1;
+        if visited > max_nodes {
+            return None;
+        }
+        count += usize::from(node.value.is_some());
+        stack.extend(node.children.iter().map(|(_, child)| child));
+    }
+    Some(count)
+}
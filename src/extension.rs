@@ -0,0 +1,67 @@
+//! File extension lookup table preferring the longest multi-part extension
+
+use crate::domain::{self, DomainSuffixMatcher};
+
+/// Table mapping file extensions such as `gz` or `tar.gz` to values like MIME types
+///
+/// Extensions are matched as whole dot-separated parts from the end of the file name,
+/// case-insensitively, and never consume the whole name: `archive.tar.gz` matches `tar.gz`
+/// while `tar.gz` alone matches `gz`.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::extension::ExtensionTable;
+///
+/// let mut types = ExtensionTable::new();
+/// types.insert("gz", "application/gzip");
+/// types.insert("tar.gz", "application/x-compressed-tar");
+/// types.insert(".txt", "text/plain");
+///
+/// assert_eq!(
+///     types.match_filename("backups/archive.TAR.gz"),
+///     Some(("tar.gz".to_string(), &"application/x-compressed-tar"))
+/// );
+/// assert_eq!(types.match_filename("notes.gz").map(|(_, v)| *v), Some("application/gzip"));
+/// assert_eq!(types.match_filename("readme.txt").map(|(e, _)| e), Some("txt".to_string()));
+/// assert_eq!(types.match_filename("txt"), None);
+/// assert_eq!(types.match_filename("archive.tgz"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExtensionTable<V> {
+    suffixes: DomainSuffixMatcher<V>,
+}
+
+impl<V> ExtensionTable<V> {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        ExtensionTable {
+            suffixes: DomainSuffixMatcher::new(),
+        }
+    }
+
+    /// Registers an extension, with or without its leading dot, returning the previous value
+    pub fn insert(&mut self, extension: &str, value: V) -> Option<V> {
+        self.suffixes.insert_suffix(extension.trim_start_matches('.'), value)
+    }
+
+    /// Finds the longest registered extension of a file name, with its value
+    ///
+    /// Directories in `filename` are ignored, both `/` and `\` are accepted as separators.
+    pub fn match_filename(&self, filename: &str) -> Option<(String, &V)> {
+        let name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+        let mut labels: Vec<String> = domain::labels(name).collect();
+        // Keep the stem out of the extension
+        labels.pop()?;
+        let (len, value) = self.suffixes.longest_suffix(&labels)?;
+        let mut parts: Vec<&str> = labels[..len].iter().map(String::as_str).collect();
+        parts.reverse();
+        Some((parts.join("."), value))
+    }
+}
+
+impl<V> Default for ExtensionTable<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
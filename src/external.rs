@@ -0,0 +1,319 @@
+//! Building a `Trie` from more entries than fit in memory before insertion
+//!
+//! Entries are buffered, sorted and spilled to temporary files in runs, which are merged back
+//! in key order, so the `Trie` is built by sorted insertions without holding the raw input.
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default number of entries sorted in memory before a spill
+pub const DEFAULT_RUN_ENTRIES: usize = 1 << 20;
+
+/// Binary encoding of the keys and values spilled by `ExternalBuilder`
+pub trait Spill: Sized {
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()>;
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! spill_int {
+    ($($t:ty),*) => {$(
+        /// Little-endian bytes
+        impl Spill for $t {
+            fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+                out.write_all(&self.to_le_bytes())
+            }
+
+            fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                input.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+spill_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// Written as a `u64`
+impl Spill for usize {
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        (*self as u64).write_to(out)
+    }
+
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        usize::try_from(u64::read_from(input)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Spill for bool {
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        u8::from(*self).write_to(out)
+    }
+
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        Ok(u8::read_from(input)? != 0)
+    }
+}
+
+/// Code point as a `u32`
+impl Spill for char {
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        u32::from(*self).write_to(out)
+    }
+
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        let code = u32::read_from(input)?;
+        char::from_u32(code).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid char {:#x}", code),
+            )
+        })
+    }
+}
+
+impl Spill for () {
+    fn write_to<W: Write>(&self, _: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_from<R: Read>(_: &mut R) -> io::Result<Self> {
+        Ok(())
+    }
+}
+
+/// Length followed by the elements
+impl<T: Spill> Spill for Vec<T> {
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        self.len().write_to(out)?;
+        self.iter().try_for_each(|item| item.write_to(out))
+    }
+
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        let len = usize::read_from(input)?;
+        (0..len).map(|_| T::read_from(input)).collect()
+    }
+}
+
+/// Length in bytes followed by the UTF-8 bytes
+impl Spill for String {
+    fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        self.len().write_to(out)?;
+        out.write_all(self.as_bytes())
+    }
+
+    fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        String::from_utf8(Vec::<u8>::read_from(input)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Sorted run spilled to a temporary file, deleted on drop
+struct Run {
+    path: PathBuf,
+    entries: usize,
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Reader of the entries of a `Run`, in order
+struct RunReader<'a> {
+    input: BufReader<File>,
+    run: &'a Run,
+    read: usize,
+}
+
+impl RunReader<'_> {
+    fn next_entry<K: Spill, V: Spill>(&mut self) -> io::Result<Option<(Vec<K>, V)>> {
+        if self.read == self.run.entries {
+            return Ok(None);
+        }
+        self.read += 1;
+        let key = Vec::<K>::read_from(&mut self.input)?;
+        Ok(Some((key, V::read_from(&mut self.input)?)))
+    }
+}
+
+static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+/// Builder of a `Trie` from unsorted entries, spilling sorted runs to temporary files
+///
+/// At most `run_entries` entries are held in memory before being written to a run in
+/// `temp_dir`. `build` merges the runs and inserts the entries in key order. When a key is
+/// pushed several times, the last value wins like with `Trie::insert`.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::external::ExternalBuilder;
+///
+/// let mut builder = ExternalBuilder::new().run_entries(2);
+/// for word in ["pear", "apple", "fig", "apple"] {
+///     builder.push(word.bytes(), word.len()).unwrap();
+/// }
+/// let t = builder.build().unwrap();
+/// let keys: Vec<_> = t.iter().map(|(k, _)| String::from_utf8(k).unwrap()).collect();
+/// assert_eq!(keys, vec!["apple", "fig", "pear"]);
+/// ```
+pub struct ExternalBuilder<K, V> {
+    run_entries: usize,
+    temp_dir: PathBuf,
+    buffer: Vec<(Vec<K>, V)>,
+    runs: Vec<Run>,
+}
+
+impl<K: Eq + Ord + Clone + Spill, V: Spill> ExternalBuilder<K, V> {
+    /// Creates a builder spilling `DEFAULT_RUN_ENTRIES` entries at a time to the system
+    /// temporary directory
+    pub fn new() -> Self {
+        ExternalBuilder {
+            run_entries: DEFAULT_RUN_ENTRIES,
+            temp_dir: std::env::temp_dir(),
+            buffer: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Number of entries sorted in memory before a spill, at least 1
+    pub fn run_entries(mut self, entries: usize) -> Self {
+        self.run_entries = entries.max(1);
+        self
+    }
+
+    /// Directory of the temporary run files
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.temp_dir = dir.into();
+        self
+    }
+
+    /// Adds an entry, spilling the buffered ones when the buffer is full
+    ///
+    /// Fails with `TrieError::Io` when the run cannot be written.
+    pub fn push<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Result<(), TrieError> {
+        self.buffer.push((key.collect(), value));
+        if self.buffer.len() >= self.run_entries {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Adds every entry like `push`
+    pub fn extend<I, T>(&mut self, entries: T) -> Result<(), TrieError>
+    where
+        I: IntoIterator<Item = K>,
+        T: IntoIterator<Item = (I, V)>,
+    {
+        entries
+            .into_iter()
+            .try_for_each(|(key, value)| self.push(key.into_iter(), value))
+    }
+
+    /// Number of runs spilled so far
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Merges the runs into a new `Trie`, deleting their files
+    pub fn build(mut self) -> Result<Trie<K, V>, TrieError> {
+        bulk_span!("ptrie::external_build");
+        let mut trie = Trie::new();
+        if self.runs.is_empty() {
+            // A stable sort keeps the last pushed value of a key last
+            self.buffer.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, value) in self.buffer.drain(..) {
+                trie.insert(key.into_iter(), value);
+            }
+            return Ok(trie);
+        }
+        self.spill()?;
+        let mut readers = self
+            .runs
+            .iter()
+            .map(|run| {
+                Ok(RunReader {
+                    input: BufReader::new(File::open(&run.path)?),
+                    run,
+                    read: 0,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        // Equal keys pop in run order, so the value of the latest run is inserted last
+        let mut heads = BinaryHeap::new();
+        let mut values = Vec::with_capacity(readers.len());
+        for (i, reader) in readers.iter_mut().enumerate() {
+            let head = reader.next_entry::<K, V>()?;
+            values.push(head.map(|(key, value)| {
+                heads.push(Reverse((key, i)));
+                value
+            }));
+        }
+        while let Some(Reverse((key, i))) = heads.pop() {
+            if let Some(value) = values[i].take() {
+                trie.insert(key.into_iter(), value);
+            }
+            if let Some((key, value)) = readers[i].next_entry()? {
+                heads.push(Reverse((key, i)));
+                values[i] = Some(value);
+            }
+        }
+        debug_event!(runs = self.runs.len(), "merged runs");
+        Ok(trie)
+    }
+
+    /// Writes the buffered entries, sorted, to a new run
+    fn spill(&mut self) -> Result<(), TrieError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.buffer.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut options = OpenOptions::new();
+        // Never reuse an existing file nor follow a link planted under the name of a run
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let (path, file) = loop {
+            let name = format!(
+                "ptrie-run-{}-{}",
+                std::process::id(),
+                NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = self.temp_dir.join(name);
+            match options.open(&path) {
+                Ok(file) => break (path, file),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        };
+        // Only the files created here are deleted with the run
+        let run = Run {
+            path,
+            entries: self.buffer.len(),
+        };
+        let mut out = BufWriter::new(file);
+        for (key, value) in self.buffer.drain(..) {
+            key.write_to(&mut out)?;
+            value.write_to(&mut out)?;
+        }
+        out.flush()?;
+        debug_event!(entries = run.entries, "spilled run");
+        self.runs.push(run);
+        Ok(())
+    }
+}
+
+impl<K: Eq + Ord + Clone + Spill, V: Spill> Default for ExternalBuilder<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
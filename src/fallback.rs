@@ -0,0 +1,68 @@
+//! Lookups through an ordered chain of tries, for layered tables
+
+use crate::trie::Trie;
+
+/// Ordered chain of tries answering lookups from the first layer which has an answer
+///
+/// Lookups report the index of the answering layer, so layered tables such as user overrides,
+/// then organization defaults, then global defaults can tell where a value comes from.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::fallback::Fallback;
+/// use ptrie::Trie;
+///
+/// let user: Trie<u8, &str> = [("/home".bytes(), "local")].into_iter().collect();
+/// let org: Trie<u8, &str> = [("/home".bytes(), "nfs"), ("/srv".bytes(), "san")].into_iter().collect();
+/// let global: Trie<u8, &str> = [("/".bytes(), "root")].into_iter().collect();
+///
+/// let routes = Fallback::new(vec![&user, &org, &global]);
+/// assert_eq!(routes.get("/home".bytes()), Some((0, &"local")));
+/// assert_eq!(routes.get("/srv".bytes()), Some((1, &"san")));
+/// assert_eq!(routes.find_longest_prefix("/srv/data".bytes()), Some((1, &"san")));
+/// assert_eq!(routes.find_longest_prefix("/opt".bytes()), Some((2, &"root")));
+/// assert_eq!(routes.get("/opt".bytes()), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fallback<'a, K: Eq + Ord + Clone, V> {
+    layers: Vec<&'a Trie<K, V>>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> Fallback<'a, K, V> {
+    /// Chains `layers`, consulted in order
+    pub fn new(layers: Vec<&'a Trie<K, V>>) -> Self {
+        Fallback { layers }
+    }
+
+    /// Layers of the chain, in the order they are consulted
+    pub fn layers(&self) -> &[&'a Trie<K, V>] {
+        &self.layers
+    }
+
+    /// Value of `key` in the first layer holding it, with the index of that layer
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> Option<(usize, &'a V)> {
+        let key: Vec<K> = key.collect();
+        self.layers
+            .iter()
+            .enumerate()
+            .find_map(|(ix, layer)| Some((ix, layer.get(key.iter().cloned())?)))
+    }
+
+    /// Whether a layer holds `key`
+    pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Value of the longest prefix of `key` in the first layer holding a prefix of it, with
+    /// the index of that layer
+    ///
+    /// A layer with any prefix of the key answers, even if a later layer holds a longer one.
+    pub fn find_longest_prefix<I: Iterator<Item = K>>(&self, key: I) -> Option<(usize, &'a V)> {
+        let key: Vec<K> = key.collect();
+        self.layers
+            .iter()
+            .enumerate()
+            .find_map(|(ix, layer)| Some((ix, layer.find_longest_prefix(key.iter().cloned())?)))
+    }
+}
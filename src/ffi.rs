@@ -0,0 +1,164 @@
+//! C interface over byte keys and byte values, for embedding in C and C++ programs
+//!
+//! Tries are passed around as opaque `PtrieTrie` pointers created by `ptrie_new` and released
+//! by `ptrie_free`. Values returned by lookups are borrowed from the trie: they stay valid until
+//! the next mutation or the release of the trie. The header is `include/ptrie.h`, generated with
+//! `cbindgen`.
+
+use crate::trie::Trie;
+use std::slice;
+
+/// Opaque trie handle
+pub struct PtrieTrie {
+    trie: Trie<u8, Vec<u8>>,
+}
+
+/// Borrows `len` bytes at `data`, a null pointer being accepted for an empty slice
+///
+/// # Safety
+///
+/// `data` must be null with `len` 0, or valid for reads of `len` bytes.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+/// Writes a value through the output pointers, when not null
+///
+/// # Safety
+///
+/// The pointers must be null or valid for writes.
+unsafe fn write_value(value: &[u8], out_value: *mut *const u8, out_len: *mut usize) {
+    if !out_value.is_null() {
+        *out_value = value.as_ptr();
+    }
+    if !out_len.is_null() {
+        *out_len = value.len();
+    }
+}
+
+/// Creates an empty trie, to release with `ptrie_free`
+#[no_mangle]
+pub extern "C" fn ptrie_new() -> *mut PtrieTrie {
+    Box::into_raw(Box::new(PtrieTrie { trie: Trie::new() }))
+}
+
+/// Releases a trie created by `ptrie_new`, doing nothing on null
+///
+/// # Safety
+///
+/// `trie` must be null or a pointer returned by `ptrie_new` not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn ptrie_free(trie: *mut PtrieTrie) {
+    if !trie.is_null() {
+        drop(Box::from_raw(trie));
+    }
+}
+
+/// Stores a copy of the value of a key
+///
+/// Returns 1 when a previous value was replaced, 0 when the key was new, and -1 when `trie` is
+/// null.
+///
+/// # Safety
+///
+/// `trie` must be null or come from `ptrie_new`, `key` and `value` must be valid for reads of
+/// `key_len` and `value_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ptrie_insert(
+    trie: *mut PtrieTrie,
+    key: *const u8,
+    key_len: usize,
+    value: *const u8,
+    value_len: usize,
+) -> i32 {
+    let Some(trie) = trie.as_mut() else {
+        return -1;
+    };
+    let key = bytes(key, key_len);
+    let value = bytes(value, value_len).to_vec();
+    i32::from(trie.trie.insert(key.iter().copied(), value).is_some())
+}
+
+/// Looks up the value of exactly this key
+///
+/// Returns whether the key was found, writing the address and length of its value to
+/// `out_value` and `out_len`.
+///
+/// # Safety
+///
+/// `trie` must be null or come from `ptrie_new`, `key` must be valid for reads of `key_len`
+/// bytes, and the output pointers must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ptrie_get(
+    trie: *const PtrieTrie,
+    key: *const u8,
+    key_len: usize,
+    out_value: *mut *const u8,
+    out_len: *mut usize,
+) -> bool {
+    let Some(trie) = trie.as_ref() else {
+        return false;
+    };
+    match trie.trie.get(bytes(key, key_len).iter().copied()) {
+        Some(value) => {
+            write_value(value, out_value, out_len);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Looks up the value of the longest key `key` starts with
+///
+/// Returns whether a prefix was found, writing its length to `out_prefix_len` and the address
+/// and length of its value to `out_value` and `out_len`.
+///
+/// # Safety
+///
+/// `trie` must be null or come from `ptrie_new`, `key` must be valid for reads of `key_len`
+/// bytes, and the output pointers must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ptrie_longest_prefix(
+    trie: *const PtrieTrie,
+    key: *const u8,
+    key_len: usize,
+    out_prefix_len: *mut usize,
+    out_value: *mut *const u8,
+    out_len: *mut usize,
+) -> bool {
+    let Some(trie) = trie.as_ref() else {
+        return false;
+    };
+    match trie.trie.find_prefixes(bytes(key, key_len).iter().copied()).pop() {
+        Some((ix, value)) => {
+            if !out_prefix_len.is_null() {
+                *out_prefix_len = ix + 1;
+            }
+            write_value(value, out_value, out_len);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Removes a key, returning whether it was present
+///
+/// # Safety
+///
+/// `trie` must be null or come from `ptrie_new`, `key` must be valid for reads of `key_len`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ptrie_remove(
+    trie: *mut PtrieTrie,
+    key: *const u8,
+    key_len: usize,
+) -> bool {
+    match trie.as_mut() {
+        Some(trie) => trie.trie.remove(bytes(key, key_len).iter().copied()).is_some(),
+        None => false,
+    }
+}
@@ -0,0 +1,122 @@
+//! Compact probabilistic filters of the keys of a `Trie`, to ship to other processes
+//!
+//! A `KeyFilter` answers whether a key may be in the `Trie` it was built from, with false
+//! positives but no false negatives. Keys are hashed with FNV-1a, which does not depend on
+//! the process, so a filter serialized with `to_bytes` is valid on any machine with the same
+//! pointer width and endianness.
+
+use crate::bloom;
+use crate::trie::Trie;
+use std::hash::{Hash, Hasher};
+
+/// Bloom filter of keys, built with `Trie::to_filter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl KeyFilter {
+    /// Whether `key` may have been a key of the `Trie`, never `false` for one of its keys
+    pub fn may_contain<K: Hash, I: Iterator<Item = K>>(&self, key: I) -> bool {
+        let key: Vec<K> = key.collect();
+        self.bit_positions(&key)
+            .all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Size of the filter in bytes
+    pub fn byte_len(&self) -> usize {
+        4 + self.bits.len() * 8
+    }
+
+    /// Little-endian encoding: the number of hashes on 4 bytes, then the words of the bits
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.byte_len());
+        bytes.extend_from_slice(&self.hashes.to_le_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Filter encoded by `to_bytes`, `None` when `bytes` is not such an encoding
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (hashes, words) = bytes.split_first_chunk::<4>()?;
+        let hashes = u32::from_le_bytes(*hashes);
+        if hashes == 0 || words.is_empty() || words.len() % 8 != 0 {
+            return None;
+        }
+        let bits = words
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().expect("chunks of 8 bytes")))
+            .collect();
+        Some(KeyFilter { bits, hashes })
+    }
+
+    /// Positions of the bits of a key, placed like in a `BloomTrie`
+    fn bit_positions<K: Hash>(&self, key: &[K]) -> impl Iterator<Item = usize> {
+        let mut hasher = Fnv1a::default();
+        key.hash(&mut hasher);
+        bloom::bit_positions(hasher.finish(), self.hashes, self.bits.len())
+    }
+}
+
+/// 64-bit FNV-1a hash
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        // FNV mixes the high bits poorly, fold them into the low ones
+        self.0 ^ (self.0 >> 32)
+    }
+}
+
+impl<K: Eq + Ord + Clone + Hash, V> Trie<K, V> {
+    /// Bloom filter of the keys with `bits_per_key` bits for each key
+    ///
+    /// Ten bits per key give about 1% of false positives.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::filter::KeyFilter;
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for word in ["apple", "banana", "cherry"] {
+    ///     t.insert(word.bytes(), ());
+    /// }
+    /// let filter = KeyFilter::from_bytes(&t.to_filter(10).to_bytes()).unwrap();
+    /// assert!(filter.may_contain("banana".bytes()));
+    /// assert!(!filter.may_contain("durian".bytes()));
+    /// ```
+    pub fn to_filter(&self, bits_per_key: usize) -> KeyFilter {
+        bulk_span!("ptrie::to_filter");
+        let keys = self.root.count_values();
+        let words = keys.max(1).saturating_mul(bits_per_key.max(1)).div_ceil(64);
+        let hashes = ((bits_per_key as f64) * std::f64::consts::LN_2).round().max(1.0) as u32;
+        let mut filter = KeyFilter {
+            bits: vec![0; words],
+            hashes,
+        };
+        for (key, _) in self.iter() {
+            let positions: Vec<usize> = filter.bit_positions(&key).collect();
+            for bit in positions {
+                filter.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        filter
+    }
+}
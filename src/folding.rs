@@ -0,0 +1,115 @@
+//! Lookups folding the elements of the query, like its case, without rebuilding the `Trie`
+//!
+//! Each element of the query matches the edge equal to it and the edge equal to its folding,
+//! both branches being tried when they differ. A `Trie` of lowercase keys thus serves
+//! case-insensitive queries with `to_ascii_lowercase` as folding, while keys stored with their
+//! case still match exactly. Unlike `ordering::Ordered`, the keys keep their own order and
+//! only the queries using a folding pay for it.
+
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Value of `key` with its elements folded by `fold` where the exact ones have no match,
+    /// the exact elements winning when both have a value
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("select".bytes(), 1);
+    /// t.insert("SELECT".bytes(), 2);
+    ///
+    /// assert_eq!(t.get_with("SeLeCt".bytes(), u8::to_ascii_lowercase), Some(&1));
+    /// assert_eq!(t.get_with("SELECT".bytes(), u8::to_ascii_lowercase), Some(&2));
+    /// assert_eq!(t.get_with("insert".bytes(), u8::to_ascii_lowercase), None);
+    /// ```
+    pub fn get_with<I, F>(&self, key: I, fold: F) -> Option<&V>
+    where
+        I: Iterator<Item = K>,
+        F: Fn(&K) -> K,
+    {
+        let key: Vec<K> = key.collect();
+        let mut found = None;
+        self.fold_walk(&key, fold, |depth, node| {
+            found = node.value.as_ref().filter(|_| depth == key.len());
+            found.is_some()
+        });
+        found
+    }
+
+    /// Whether `key` has a value with its elements folded by `fold` where needed
+    pub fn contains_key_with<I, F>(&self, key: I, fold: F) -> bool
+    where
+        I: Iterator<Item = K>,
+        F: Fn(&K) -> K,
+    {
+        self.get_with(key, fold).is_some()
+    }
+
+    /// Value of the longest non-empty prefix of `key` with its elements folded by `fold` where
+    /// needed, like `find_longest_prefix`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("http://".bytes(), "http");
+    /// t.insert("https://".bytes(), "https");
+    ///
+    /// let scheme = t.find_longest_prefix_with("HTTPS://Example.org".bytes(), u8::to_ascii_lowercase);
+    /// assert_eq!(scheme, Some(&"https"));
+    /// ```
+    pub fn find_longest_prefix_with<I, F>(&self, key: I, fold: F) -> Option<&V>
+    where
+        I: Iterator<Item = K>,
+        F: Fn(&K) -> K,
+    {
+        let key: Vec<K> = key.collect();
+        let mut longest: Option<(usize, &V)> = None;
+        self.fold_walk(&key, fold, |depth, node| {
+            if let Some(value) = node.value.as_ref() {
+                if depth > 0 && longest.is_none_or(|(best, _)| depth > best) {
+                    longest = Some((depth, value));
+                }
+            }
+            false
+        });
+        longest.map(|(_, value)| value)
+    }
+
+    /// Visits the nodes matching a prefix of `key` with its elements folded or not, with the
+    /// length of the prefix, exact branches first, until `visit` returns `true`
+    fn fold_walk<'a, F>(
+        &'a self,
+        key: &[K],
+        fold: F,
+        mut visit: impl FnMut(usize, &'a TrieNode<K, V>) -> bool,
+    ) where
+        F: Fn(&K) -> K,
+    {
+        let child = |node: &'a TrieNode<K, V>, k: &K| {
+            let ix = node.children.binary_search_by(|(edge, _)| edge.cmp(k)).ok()?;
+            Some(&node.children[ix].1)
+        };
+        let mut stack = vec![(&self.root, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if visit(depth, node) {
+                return;
+            }
+            let Some(k) = key.get(depth) else {
+                continue;
+            };
+            // Pushed last, the exact branch is visited first
+            let folded = fold(k);
+            if folded != *k {
+                stack.extend(child(node, &folded).map(|next| (next, depth + 1)));
+            }
+            stack.extend(child(node, k).map(|next| (next, depth + 1)));
+        }
+    }
+}
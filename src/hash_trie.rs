@@ -0,0 +1,191 @@
+//! Trie storing the children of its nodes in hash maps, for keys hashed but not ordered
+//!
+//! Enum tokens or interned ids often have no meaningful `Ord`, and nodes with thousands of
+//! children are found faster by hash than by binary search. The price is the order of
+//! iteration, which is arbitrary.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+
+#[derive(Debug)]
+struct HashNode<K, V> {
+    value: Option<V>,
+    children: HashMap<K, HashNode<K, V>>,
+}
+
+impl<K, V> HashNode<K, V> {
+    fn new() -> Self {
+        HashNode {
+            value: None,
+            children: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V> Drop for HashNode<K, V> {
+    /// Drops the descendants with an explicit stack, deep keys would overflow the call stack
+    fn drop(&mut self) {
+        let mut stack: Vec<HashNode<K, V>> = mem::take(&mut self.children).into_values().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(mem::take(&mut node.children).into_values());
+        }
+    }
+}
+
+/// Trie over `K: Hash + Eq` keys, without ordering the children
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::hash_trie::HashTrie;
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// enum Token {
+///     Select,
+///     Star,
+///     From,
+/// }
+///
+/// let mut grammar = HashTrie::new();
+/// grammar.insert([Token::Select, Token::Star].into_iter(), "select all");
+/// grammar.insert([Token::Select].into_iter(), "select");
+///
+/// assert_eq!(grammar.get([Token::Select, Token::Star].into_iter()), Some(&"select all"));
+/// let query = [Token::Select, Token::From];
+/// assert_eq!(grammar.find_longest_prefix(query.into_iter()), Some((1, &"select")));
+/// ```
+#[derive(Debug)]
+pub struct HashTrie<K: Hash + Eq + Clone, V> {
+    root: HashNode<K, V>,
+    len: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> HashTrie<K, V> {
+    /// Creates an empty trie
+    pub fn new() -> Self {
+        HashTrie {
+            root: HashNode::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of keys
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the trie has no keys
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a value, returning the previous one
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for k in key {
+            node = node.children.entry(k).or_insert_with(HashNode::new);
+        }
+        let previous = node.value.replace(value);
+        self.len += usize::from(previous.is_none());
+        previous
+    }
+
+    /// Value of a key
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        self.find_node(key)?.value.as_ref()
+    }
+
+    /// Mutable value of a key
+    pub fn get_mut<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&mut V> {
+        let key: Vec<K> = key.collect();
+        self.get_node_mut(&key)?.value.as_mut()
+    }
+
+    /// Whether the key has a value
+    pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Length and value of the longest non-empty key `key` starts with
+    pub fn find_longest_prefix<I: Iterator<Item = K>>(&self, key: I) -> Option<(usize, &V)> {
+        let mut node = &self.root;
+        let mut longest = None;
+        for (i, k) in key.enumerate() {
+            let Some(next) = node.children.get(&k) else {
+                break;
+            };
+            node = next;
+            if let Some(value) = &node.value {
+                longest = Some((i + 1, value));
+            }
+        }
+        longest
+    }
+
+    /// Removes a key, returning its value and dropping the nodes left empty
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        let key: Vec<K> = key.collect();
+        // Index of the edge heading the chain of nodes left empty by the removal, below the
+        // deepest node on the path with a value or another child
+        let mut at = 0;
+        let mut node = &self.root;
+        for (i, k) in key.iter().enumerate() {
+            if i > 0 && (node.value.is_some() || node.children.len() > 1) {
+                at = i;
+            }
+            node = node.children.get(k)?;
+        }
+        node.value.as_ref()?;
+        self.len -= 1;
+        if key.is_empty() || !node.children.is_empty() {
+            return self.get_node_mut(&key)?.value.take();
+        }
+        let parent = self.get_node_mut(&key[..at])?;
+        let mut chain = parent.children.remove(&key[at])?;
+        for k in &key[at + 1..] {
+            chain = chain.children.remove(k)?;
+        }
+        chain.value.take()
+    }
+
+    /// Iterate the entries, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<K>, &V)> {
+        let mut stack = vec![(Vec::new(), &self.root)];
+        std::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                for (k, child) in &node.children {
+                    let mut child_path = path.clone();
+                    child_path.push(k.clone());
+                    stack.push((child_path, child));
+                }
+                if let Some(value) = &node.value {
+                    return Some((path, value));
+                }
+            }
+            None
+        })
+    }
+
+    fn get_node_mut(&mut self, key: &[K]) -> Option<&mut HashNode<K, V>> {
+        let mut node = &mut self.root;
+        for k in key {
+            node = node.children.get_mut(k)?;
+        }
+        Some(node)
+    }
+
+    fn find_node<I: Iterator<Item = K>>(&self, key: I) -> Option<&HashNode<K, V>> {
+        let mut node = &self.root;
+        for k in key {
+            node = node.children.get(&k)?;
+        }
+        Some(node)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Default for HashTrie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,43 @@
+//! Heap usage reporting with the `get-size` crate
+
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+use get_size::GetSize;
+use std::mem;
+
+/// Counts the children vectors at their capacity, with the heap owned by the keys and values,
+/// walking the nodes with an explicit stack
+impl<K: Eq + Ord + Clone + GetSize, V: GetSize> GetSize for TrieNode<K, V> {
+    fn get_heap_size(&self) -> usize {
+        let mut size = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            size += node.value.get_heap_size();
+            size += node.children.capacity() * mem::size_of::<(K, TrieNode<K, V>)>();
+            for (k, child) in &node.children {
+                size += k.get_heap_size();
+                stack.push(child);
+            }
+        }
+        size
+    }
+}
+
+/// Heap used by the nodes and by the insertion order log
+///
+/// # Example
+///
+/// ```rust
+/// use get_size::GetSize;
+/// use ptrie::Trie;
+///
+/// let mut t = Trie::new();
+/// assert_eq!(t.get_heap_size(), 0);
+/// t.insert("ab".bytes(), String::from("value"));
+/// assert!(t.get_heap_size() >= "value".len());
+/// ```
+impl<K: Eq + Ord + Clone + GetSize, V: GetSize> GetSize for Trie<K, V> {
+    fn get_heap_size(&self) -> usize {
+        self.root.get_heap_size() + self.insertion_order.get_heap_size()
+    }
+}
@@ -0,0 +1,142 @@
+//! Values inherited by the keys below an entry, for hierarchical configuration trees
+
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Value of `key`, or else the value of its nearest ancestor entry
+    ///
+    /// Unlike `find_longest_prefix`, the key itself and the empty key are candidates, the empty
+    /// key holding the default of every key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut limits = Trie::new();
+    /// limits.insert("".bytes(), 10);
+    /// limits.insert("/api".bytes(), 100);
+    ///
+    /// assert_eq!(limits.get_effective("/api/users".bytes()), Some(&100));
+    /// assert_eq!(limits.get_effective("/api".bytes()), Some(&100));
+    /// assert_eq!(limits.get_effective("/static".bytes()), Some(&10));
+    /// ```
+    pub fn get_effective<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        self.get_effective_with_depth(key).map(|(_, value)| value)
+    }
+
+    /// Same as `get_effective`, also returning the key of the entry which supplied the value
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut limits = Trie::new();
+    /// limits.insert("/api".bytes(), 100);
+    ///
+    /// let (source, limit) = limits.get_effective_with_source("/api/users".bytes()).unwrap();
+    /// assert_eq!((source.as_slice(), limit), (b"/api".as_slice(), &100));
+    /// assert!(limits.get_effective_with_source("/static".bytes()).is_none());
+    /// ```
+    pub fn get_effective_with_source<I: Iterator<Item = K>>(&self, key: I) -> Option<(Vec<K>, &V)> {
+        let mut source = Vec::new();
+        let (depth, value) =
+            self.get_effective_with_depth(key.inspect(|k| source.push(k.clone())))?;
+        source.truncate(depth);
+        Some((source, value))
+    }
+
+    /// Length of the key of the nearest entry along `key`, with its value
+    fn get_effective_with_depth<I: Iterator<Item = K>>(&self, key: I) -> Option<(usize, &V)> {
+        let mut node = &self.root;
+        let mut found = node.value.as_ref().map(|value| (0, value));
+        for (depth, k) in key.enumerate() {
+            let Ok(ix) = node.children.binary_search_by(|(edge, _)| edge.cmp(&k)) else {
+                break;
+            };
+            node = &node.children[ix].1;
+            if let Some(value) = node.value.as_ref() {
+                found = Some((depth + 1, value));
+            }
+        }
+        found
+    }
+
+    /// Removes the entries whose value is equal, according to `eq`, to the value of their
+    /// nearest ancestor entry, returning how many were removed
+    ///
+    /// The empty key is the ancestor of every key. Descendants of a removed entry are compared
+    /// with the ancestor it was equal to.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut timeouts = Trie::new();
+    /// timeouts.insert("svc".bytes(), 30);
+    /// timeouts.insert("svc/db".bytes(), 30);
+    /// timeouts.insert("svc/db/replica".bytes(), 5);
+    /// timeouts.insert("svc/db/replica/eu".bytes(), 5);
+    ///
+    /// assert_eq!(timeouts.dedup_descendant_values(|a, b| a == b), 2);
+    /// let keys: Vec<Vec<u8>> = timeouts.iter().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec![b"svc".to_vec(), b"svc/db/replica".to_vec()]);
+    /// ```
+    pub fn dedup_descendant_values<F: FnMut(&V, &V) -> bool>(&mut self, mut eq: F) -> usize {
+        bulk_span!("ptrie::dedup_descendant_values");
+        let mut removed = 0;
+        let mut keys = self.insertion_order.as_ref().map(|_| Vec::new());
+        dedup_node(
+            &mut self.root,
+            None,
+            &mut eq,
+            &mut Vec::new(),
+            &mut removed,
+            &mut keys,
+        );
+        if let (Some(order), Some(mut keys)) = (self.insertion_order.as_mut(), keys) {
+            keys.sort_unstable();
+            order.retain(|key| keys.binary_search(key).is_err());
+        }
+        self.root.prune();
+        debug_event!(removed, "deduplicated values");
+        removed
+    }
+}
+
+/// Clears the values of `node` and its descendants equal to their nearest ancestor value,
+/// counting them and collecting their keys in `keys` when given
+fn dedup_node<K: Eq + Ord + Clone, V>(
+    node: &mut TrieNode<K, V>,
+    inherited: Option<&V>,
+    eq: &mut impl FnMut(&V, &V) -> bool,
+    path: &mut Vec<K>,
+    removed: &mut usize,
+    keys: &mut Option<Vec<Vec<K>>>,
+) {
+    // Nodes to visit with the value they inherit and the length of the path to their parent
+    let mut stack = vec![(None, node, inherited, path.len())];
+    while let Some((edge, node, inherited, len)) = stack.pop() {
+        path.truncate(len);
+        path.extend(edge);
+        let TrieNode { value, children } = node;
+        if let (Some(own), Some(parent)) = (value.as_ref(), inherited) {
+            if eq(own, parent) {
+                *value = None;
+                *removed += 1;
+                if let Some(keys) = keys.as_mut() {
+                    keys.push(path.clone());
+                }
+            }
+        }
+        let value: &Option<V> = value;
+        let inherited = value.as_ref().or(inherited);
+        let len = path.len();
+        for (key_part, child) in children.iter_mut().rev() {
+            stack.push((Some(key_part.clone()), child, inherited, len));
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Internal macros emitting `tracing` spans and events, compiled out without the `tracing` feature
+
+/// Enters an info span named after a bulk operation until the end of the enclosing block
+macro_rules! bulk_span {
+    ($name:expr $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+/// Emits a debug event, for progress of bulk operations and structural changes
+macro_rules! debug_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
@@ -0,0 +1,116 @@
+//! Order-preserving byte encodings of integers and tuples of integers
+//!
+//! Integers are written big-endian on their full width, with the sign bit of signed integers
+//! flipped, so comparing the encodings byte by byte gives the numeric order. A `Trie<u8, V>`
+//! keyed by them iterates in numeric order, and the encoding of the first fields of a tuple is
+//! a prefix of the encoding of the tuple.
+
+/// Values with a fixed-width byte encoding in the same order as the values
+pub trait OrderedKey: Sized {
+    /// Length of the encoding
+    const LEN: usize;
+
+    /// Appends the encoding to `out`
+    fn write_key(&self, out: &mut Vec<u8>);
+
+    /// Decodes the first `LEN` bytes of `bytes`, `None` when there are fewer
+    fn read_key(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! unsigned_key {
+    ($($t:ty),*) => {$(
+        impl OrderedKey for $t {
+            const LEN: usize = std::mem::size_of::<$t>();
+
+            fn write_key(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_be_bytes());
+            }
+
+            fn read_key(bytes: &[u8]) -> Option<Self> {
+                Some(<$t>::from_be_bytes(bytes.get(..Self::LEN)?.try_into().ok()?))
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_key {
+    ($($t:ty => $u:ty),*) => {$(
+        /// Big-endian with the sign bit flipped, negative values first
+        impl OrderedKey for $t {
+            const LEN: usize = std::mem::size_of::<$t>();
+
+            fn write_key(&self, out: &mut Vec<u8>) {
+                ((*self as $u) ^ (1 << (<$u>::BITS - 1))).write_key(out);
+            }
+
+            fn read_key(bytes: &[u8]) -> Option<Self> {
+                Some((<$u>::read_key(bytes)? ^ (1 << (<$u>::BITS - 1))) as $t)
+            }
+        }
+    )*};
+}
+
+unsigned_key!(u8, u16, u32, u64, u128);
+signed_key!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128);
+
+macro_rules! tuple_key {
+    ($($name:ident),*) => {
+        /// Encodings of the fields one after the other
+        impl<$($name: OrderedKey),*> OrderedKey for ($($name,)*) {
+            const LEN: usize = 0 $(+ $name::LEN)*;
+
+            #[allow(non_snake_case)]
+            fn write_key(&self, out: &mut Vec<u8>) {
+                let ($($name,)*) = self;
+                $($name.write_key(out);)*
+            }
+
+            // The offset after the last field is not read
+            #[allow(unused_assignments)]
+            fn read_key(bytes: &[u8]) -> Option<Self> {
+                let mut offset = 0;
+                Some(($({
+                    let field = $name::read_key(bytes.get(offset..)?)?;
+                    offset += $name::LEN;
+                    field
+                },)*))
+            }
+        }
+    };
+}
+
+tuple_key!(A, B);
+tuple_key!(A, B, C);
+tuple_key!(A, B, C, D);
+
+/// Bytes of the order-preserving encoding of `value`, to use as a key
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::int_key::{decode_key, encode_key};
+/// use ptrie::Trie;
+///
+/// let mut events = Trie::new();
+/// for (user, time) in [(2u32, -5i64), (1, 30), (2, 7), (1, -1)] {
+///     events.insert(encode_key(&(user, time)), time);
+/// }
+/// let user2: Vec<i64> = events.iter_prefix(encode_key(&2u32)).map(|(_, t)| *t).collect();
+/// assert_eq!(user2, vec![-5, 7]);
+///
+/// let (first, _) = events.iter().next().unwrap();
+/// assert_eq!(decode_key::<(u32, i64)>(&first), Some((1, -1)));
+/// ```
+pub fn encode_key<T: OrderedKey>(value: &T) -> std::vec::IntoIter<u8> {
+    let mut out = Vec::with_capacity(T::LEN);
+    value.write_key(&mut out);
+    out.into_iter()
+}
+
+/// Decodes a key written by `encode_key`, `None` unless its length is exactly `T::LEN`
+pub fn decode_key<T: OrderedKey>(bytes: &[u8]) -> Option<T> {
+    if bytes.len() != T::LEN {
+        return None;
+    }
+    T::read_key(bytes)
+}
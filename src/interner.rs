@@ -0,0 +1,86 @@
+//! Interning of hierarchical names as dense integer ids
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+
+/// Two-way mapping between keys and dense `u32` ids, handed out in insertion order
+///
+/// Ids are never reused: interning a key again returns its first id.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::interner::Interner;
+///
+/// let mut names = Interner::new();
+/// let std_io = names.intern("std::io".bytes()).unwrap();
+/// let std_fs = names.intern("std::fs".bytes()).unwrap();
+/// assert_eq!((std_io, std_fs), (0, 1));
+/// assert_eq!(names.intern("std::io".bytes()).unwrap(), std_io);
+///
+/// assert_eq!(names.lookup("std::fs".bytes()), Some(std_fs));
+/// assert_eq!(names.resolve(std_io), Some(&b"std::io"[..]));
+/// let in_std: Vec<u32> = names.trie().iter_prefix("std::".bytes()).map(|(_, id)| *id).collect();
+/// assert_eq!(in_std, vec![std_fs, std_io]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Interner<K: Eq + Ord + Clone> {
+    ids: Trie<K, u32>,
+    keys: Vec<Vec<K>>,
+}
+
+impl<K: Eq + Ord + Clone> Interner<K> {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Interner {
+            ids: Trie::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Number of interned keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no key was interned
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The ids by key, for prefix queries
+    pub fn trie(&self) -> &Trie<K, u32> {
+        &self.ids
+    }
+
+    /// Id of a key, given the next id when it is new
+    ///
+    /// Fails with `TrieError::Overflow` when every `u32` id is taken.
+    pub fn intern<I: Iterator<Item = K>>(&mut self, key: I) -> Result<u32, TrieError> {
+        let key: Vec<K> = key.collect();
+        if let Some(id) = self.ids.get(key.iter().cloned()) {
+            return Ok(*id);
+        }
+        let id = u32::try_from(self.keys.len())
+            .map_err(|_| TrieError::Overflow("Every u32 id is taken".to_string()))?;
+        self.ids.insert(key.iter().cloned(), id);
+        self.keys.push(key);
+        Ok(id)
+    }
+
+    /// Id of an interned key
+    pub fn lookup<I: Iterator<Item = K>>(&self, key: I) -> Option<u32> {
+        self.ids.get(key).copied()
+    }
+
+    /// Key of an id
+    pub fn resolve(&self, id: u32) -> Option<&[K]> {
+        self.keys.get(id as usize).map(Vec::as_slice)
+    }
+}
+
+impl<K: Eq + Ord + Clone> Default for Interner<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
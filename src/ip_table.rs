@@ -0,0 +1,185 @@
+//! CIDR routing table built on top of a bitwise `Trie`
+//!
+//! Available with the `ipnet` feature.
+
+use crate::trie::Trie;
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Routing table mapping IPv4 and IPv6 prefixes to values
+///
+/// Each address family is stored in its own trie keyed by the bits of the network address,
+/// so lookups cost at most 32 or 128 steps.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::ip_table::IpPrefixTable;
+/// use std::net::IpAddr;
+///
+/// let mut table = IpPrefixTable::new();
+/// table.insert("10.0.0.0/8".parse().unwrap(), "private");
+/// table.insert("10.1.0.0/16".parse().unwrap(), "office");
+/// table.insert("::/0".parse().unwrap(), "default v6");
+///
+/// let addr: IpAddr = "10.1.2.3".parse().unwrap();
+/// let (net, value) = table.longest_match(addr).unwrap();
+/// assert_eq!(net, "10.1.0.0/16".parse().unwrap());
+/// assert_eq!(*value, "office");
+///
+/// assert!(table.longest_match("192.168.0.1".parse().unwrap()).is_none());
+/// assert_eq!(table.longest_match("2001:db8::1".parse().unwrap()).map(|(_, v)| *v), Some("default v6"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct IpPrefixTable<V> {
+    v4: Trie<bool, V>,
+    v6: Trie<bool, V>,
+}
+
+impl<V> IpPrefixTable<V> {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        IpPrefixTable {
+            v4: Trie::new(),
+            v6: Trie::new(),
+        }
+    }
+
+    /// Adds a prefix to the table, returning the value previously stored for it
+    ///
+    /// Host bits of the network are ignored, `10.1.2.3/8` is stored as `10.0.0.0/8`.
+    pub fn insert(&mut self, net: IpNet, value: V) -> Option<V> {
+        let (trie, bits) = self.trie_mut(net.addr(), net.prefix_len());
+        trie.insert(bits, value)
+    }
+
+    /// Gets the value stored for exactly this prefix
+    pub fn get(&self, net: IpNet) -> Option<&V> {
+        let (trie, bits) = self.trie(net.addr(), net.prefix_len());
+        trie.get(bits)
+    }
+
+    /// Finds the most specific prefix containing `addr`
+    pub fn longest_match(&self, addr: IpAddr) -> Option<(IpNet, &V)> {
+        self.covering(addr).pop()
+    }
+
+    /// Returns all the prefixes containing `addr`, from the least to the most specific
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::ip_table::IpPrefixTable;
+    ///
+    /// let mut table = IpPrefixTable::new();
+    /// table.insert("0.0.0.0/0".parse().unwrap(), 0);
+    /// table.insert("10.0.0.0/8".parse().unwrap(), 8);
+    /// table.insert("10.1.0.0/16".parse().unwrap(), 16);
+    /// table.insert("10.2.0.0/16".parse().unwrap(), 99);
+    ///
+    /// let covering: Vec<u8> = table
+    ///     .covering("10.1.0.1".parse().unwrap())
+    ///     .iter()
+    ///     .map(|(net, _)| net.prefix_len())
+    ///     .collect();
+    /// assert_eq!(covering, vec![0, 8, 16]);
+    /// ```
+    pub fn covering(&self, addr: IpAddr) -> Vec<(IpNet, &V)> {
+        let width = max_prefix_len(addr);
+        let (trie, bits) = self.trie(addr, width);
+        let mut covering = Vec::new();
+        if let Some(value) = trie.root().value() {
+            covering.push((network(addr, 0), value));
+        }
+        for (ix, value) in trie.find_prefixes(bits) {
+            covering.push((network(addr, ix as u8 + 1), value));
+        }
+        covering
+    }
+
+    /// Iterate the prefixes of the table with their value, IPv4 first
+    pub fn iter(&self) -> impl Iterator<Item = (IpNet, &V)> + '_ {
+        let v4 = self
+            .v4
+            .iter()
+            .map(|(bits, v)| (from_bits(IpAddr::V4(Ipv4Addr::UNSPECIFIED), &bits), v));
+        let v6 = self
+            .v6
+            .iter()
+            .map(|(bits, v)| (from_bits(IpAddr::V6(Ipv6Addr::UNSPECIFIED), &bits), v));
+        v4.chain(v6)
+    }
+
+    /// Checks if the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.v4.is_empty() && self.v6.is_empty()
+    }
+
+    fn trie(&self, addr: IpAddr, len: u8) -> (&Trie<bool, V>, impl Iterator<Item = bool>) {
+        let trie = match addr {
+            IpAddr::V4(_) => &self.v4,
+            IpAddr::V6(_) => &self.v6,
+        };
+        (trie, bits(addr, len))
+    }
+
+    fn trie_mut(
+        &mut self,
+        addr: IpAddr,
+        len: u8,
+    ) -> (&mut Trie<bool, V>, impl Iterator<Item = bool>) {
+        let trie = match addr {
+            IpAddr::V4(_) => &mut self.v4,
+            IpAddr::V6(_) => &mut self.v6,
+        };
+        (trie, bits(addr, len))
+    }
+}
+
+impl<V> Default for IpPrefixTable<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn max_prefix_len(addr: IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn to_bits(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(a) => u32::from(a) as u128,
+        IpAddr::V6(a) => u128::from(a),
+    }
+}
+
+/// The `len` most significant bits of `addr`
+fn bits(addr: IpAddr, len: u8) -> impl Iterator<Item = bool> {
+    let width = max_prefix_len(addr);
+    let n = to_bits(addr);
+    (0..len.min(width)).map(move |i| (n >> (width - 1 - i)) & 1 == 1)
+}
+
+fn network(addr: IpAddr, len: u8) -> IpNet {
+    match addr {
+        IpAddr::V4(a) => IpNet::V4(Ipv4Net::new(a, len).unwrap().trunc()),
+        IpAddr::V6(a) => IpNet::V6(Ipv6Net::new(a, len).unwrap().trunc()),
+    }
+}
+
+fn from_bits(family: IpAddr, bits: &[bool]) -> IpNet {
+    let width = max_prefix_len(family);
+    let n = bits
+        .iter()
+        .fold(0u128, |n, &b| (n << 1) | b as u128)
+        .checked_shl((width as usize - bits.len()) as u32)
+        .unwrap_or(0);
+    let addr = match family {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(n as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(n)),
+    };
+    network(addr, bits.len() as u8)
+}
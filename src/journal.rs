@@ -0,0 +1,152 @@
+//! Undo journal reverting speculative updates without cloning the `Trie`
+
+use crate::trie::Trie;
+
+/// Trie recording the previous value of each key it changes while a journal is open
+///
+/// `begin` opens a journal, and can be nested: `commit` keeps the changes since the matching
+/// `begin`, and `rollback` reverts them, newest first. Only the touched keys are recorded, so
+/// reverting a batch costs in proportion to the batch and not to the `Trie`. Rolled back keys
+/// are inserted again, after the others in the insertion order of a `Trie` tracking it.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::journal::JournaledTrie;
+///
+/// let mut config = JournaledTrie::new();
+/// config.insert("net.port".bytes(), 80);
+///
+/// config.begin();
+/// config.insert("net.port".bytes(), 8080);
+/// config.insert("net.host".bytes(), 1);
+/// assert!(config.rollback());
+///
+/// assert_eq!(config.trie().get("net.port".bytes()), Some(&80));
+/// assert!(!config.trie().contains_key("net.host".bytes()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct JournaledTrie<K: Eq + Ord + Clone, V> {
+    trie: Trie<K, V>,
+    /// Previous value of each changed key, oldest change first
+    undo: Vec<(Vec<K>, Option<V>)>,
+    /// Length of `undo` at each open `begin`
+    savepoints: Vec<usize>,
+}
+
+impl<K: Eq + Ord + Clone, V> JournaledTrie<K, V> {
+    /// Creates an empty trie without an open journal
+    pub fn new() -> Self {
+        Self::from(Trie::new())
+    }
+
+    /// The entries, for lookups
+    pub fn trie(&self) -> &Trie<K, V> {
+        &self.trie
+    }
+
+    /// Returns the `Trie`, keeping the changes of the journals still open
+    pub fn into_inner(self) -> Trie<K, V> {
+        self.trie
+    }
+
+    /// Number of open journals
+    pub fn depth(&self) -> usize {
+        self.savepoints.len()
+    }
+
+    /// Opens a journal, nested in the current one if any
+    pub fn begin(&mut self) {
+        self.savepoints.push(self.undo.len());
+    }
+
+    /// Keeps the changes since the last `begin`, returning false when no journal is open
+    ///
+    /// In a nested journal the changes stay recorded, so the outer journal can still revert them.
+    pub fn commit(&mut self) -> bool {
+        if self.savepoints.pop().is_none() {
+            return false;
+        }
+        if self.savepoints.is_empty() {
+            self.undo.clear();
+        }
+        true
+    }
+
+    /// Reverts the changes since the last `begin`, returning false when no journal is open
+    pub fn rollback(&mut self) -> bool {
+        let Some(savepoint) = self.savepoints.pop() else {
+            return false;
+        };
+        debug_event!(changes = self.undo.len() - savepoint, "rolling back");
+        for (key, previous) in self.undo.drain(savepoint..).rev() {
+            match previous {
+                Some(value) => self.trie.insert(key.into_iter(), value),
+                None => self.trie.remove(key.into_iter()),
+            };
+        }
+        true
+    }
+
+    /// Inserts a value, returning the previous one
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V>
+    where
+        V: Clone,
+    {
+        if self.savepoints.is_empty() {
+            return self.trie.insert(key, value);
+        }
+        let key: Vec<K> = key.collect();
+        let previous = self.trie.insert(key.iter().cloned(), value);
+        self.undo.push((key, previous.clone()));
+        previous
+    }
+
+    /// Removes a key, returning its value
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V>
+    where
+        V: Clone,
+    {
+        if self.savepoints.is_empty() {
+            return self.trie.remove(key);
+        }
+        let key: Vec<K> = key.collect();
+        let value = self.trie.remove(key.iter().cloned())?;
+        self.undo.push((key, Some(value.clone())));
+        Some(value)
+    }
+
+    /// Removes every key starting with `prefix`
+    pub fn remove_subtree<I: Iterator<Item = K>>(&mut self, prefix: I) {
+        if self.savepoints.is_empty() {
+            self.trie.remove_subtree(prefix);
+            return;
+        }
+        let prefix: Vec<K> = prefix.collect();
+        let keys: Vec<Vec<K>> = self
+            .trie
+            .iter_prefix(prefix.iter().cloned())
+            .map(|(key, _)| key)
+            .collect();
+        for key in keys {
+            let value = self.trie.remove(key.iter().cloned());
+            self.undo.push((key, value));
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> From<Trie<K, V>> for JournaledTrie<K, V> {
+    fn from(trie: Trie<K, V>) -> Self {
+        JournaledTrie {
+            trie,
+            undo: Vec::new(),
+            savepoints: Vec::new(),
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Default for JournaledTrie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,125 @@
+//! Detection and redaction of keywords in text, on word boundaries
+
+use crate::trie::Trie;
+use std::ops::Range;
+
+/// Set of terms to find in text
+///
+/// Terms only match whole words: they must start and end next to a character which is not
+/// alphanumeric (or the text boundaries). Terms may contain several words.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::keyword::KeywordFilter;
+///
+/// let filter: KeywordFilter = ["darn", "heck", "oh heck"].into_iter().collect();
+///
+/// assert_eq!(filter.scan("Oh heck, darned darn!"), vec![3..7, 16..20]);
+/// assert_eq!(filter.censor("Oh heck, darned darn!", '*'), "Oh ****, darned ****!");
+///
+/// let mut filter = KeywordFilter::new().ascii_case_insensitive(true);
+/// filter.insert("Darn");
+/// assert_eq!(filter.censor("darn, DARN", '#'), "####, ####");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeywordFilter {
+    terms: Trie<char, ()>,
+    ascii_case_insensitive: bool,
+}
+
+impl KeywordFilter {
+    /// Creates an empty filter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches terms regardless of ASCII case, must be set before adding terms
+    pub fn ascii_case_insensitive(mut self, enabled: bool) -> Self {
+        self.ascii_case_insensitive = enabled;
+        self
+    }
+
+    /// Adds a term to the filter
+    pub fn insert(&mut self, term: &str) {
+        let fold = self.ascii_case_insensitive;
+        self.terms.insert(term.chars().map(|c| fold_case(c, fold)), ());
+    }
+
+    /// Byte ranges of the terms found in `text`, longest match first and without overlap
+    pub fn scan(&self, text: &str) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        let mut chars = text.char_indices().peekable();
+        let mut previous: Option<char> = None;
+        while let Some((start, c)) = chars.next() {
+            if previous.is_some_and(is_word_char) {
+                previous = Some(c);
+                continue;
+            }
+            match self.longest_term_at(&text[start..]) {
+                Some(len) => {
+                    matches.push(start..start + len);
+                    // Resume after the match
+                    while chars.next_if(|(i, _)| *i < start + len).is_some() {}
+                    previous = text[..start + len].chars().next_back();
+                }
+                None => previous = Some(c),
+            }
+        }
+        matches
+    }
+
+    /// Copy of `text` where every character of the terms found is replaced by `mask`
+    pub fn censor(&self, text: &str, mask: char) -> String {
+        let mut censored = String::with_capacity(text.len());
+        let mut last = 0;
+        for range in self.scan(text) {
+            censored.push_str(&text[last..range.start]);
+            censored.extend(text[range.clone()].chars().map(|_| mask));
+            last = range.end;
+        }
+        censored.push_str(&text[last..]);
+        censored
+    }
+
+    /// Length in bytes of the longest term starting `text` and ending on a word boundary
+    fn longest_term_at(&self, text: &str) -> Option<usize> {
+        let mut node = self.terms.root();
+        let mut longest = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match node.child(&fold_case(c, self.ascii_case_insensitive)) {
+                Some(next) => node = next,
+                None => break,
+            }
+            let end = i + c.len_utf8();
+            let at_boundary = chars.peek().is_none_or(|(_, next)| !is_word_char(*next));
+            if node.has_value() && at_boundary {
+                longest = Some(end);
+            }
+        }
+        longest
+    }
+}
+
+impl<T: AsRef<str>> FromIterator<T> for KeywordFilter {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut filter = KeywordFilter::new();
+        for term in iter {
+            filter.insert(term.as_ref());
+        }
+        filter
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+fn fold_case(c: char, fold: bool) -> char {
+    if fold {
+        c.to_ascii_lowercase()
+    } else {
+        c
+    }
+}
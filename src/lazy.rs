@@ -0,0 +1,108 @@
+//! Tries materialized on demand from external storage, one subtree at a time
+
+use crate::error::TrieError;
+use crate::lru::LruTrie;
+use crate::trie::Trie;
+
+/// Source of the subtrees of a `LazyTrie`, such as a directory of files or a key-value store
+///
+/// `load` receives the first `shard_depth` elements of a key, or the whole key when it is
+/// shorter, and returns the entries whose key starts with this prefix cut the same way: the
+/// whole subtree for a full-length prefix, only the exact key for a shorter one.
+pub trait SubtreeLoader<K: Eq + Ord + Clone, V> {
+    fn load(&mut self, prefix: &[K]) -> Result<Trie<K, V>, TrieError>;
+}
+
+impl<K, V, F> SubtreeLoader<K, V> for F
+where
+    K: Eq + Ord + Clone,
+    F: FnMut(&[K]) -> Result<Trie<K, V>, TrieError>,
+{
+    fn load(&mut self, prefix: &[K]) -> Result<Trie<K, V>, TrieError> {
+        self(prefix)
+    }
+}
+
+/// Read-only `Trie` split in subtrees under the prefixes of `shard_depth` elements, loaded on
+/// first access and kept in memory up to `max_resident` of them
+///
+/// The least recently used subtree is dropped when another one is loaded past the limit, which
+/// suits access patterns skewed towards a few prefixes.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::lazy::LazyTrie;
+/// use ptrie::Trie;
+///
+/// let mut loads = Vec::new();
+/// let loader = |prefix: &[u8]| {
+///     loads.push(prefix.to_vec());
+///     let mut shard = Trie::new();
+///     if prefix == b"ab" {
+///         shard.insert("abc".bytes(), 1);
+///     }
+///     Ok(shard)
+/// };
+///
+/// let mut t = LazyTrie::new(loader, 2, 1);
+/// assert_eq!(t.get("abc".bytes()).unwrap(), Some(&1));
+/// assert_eq!(t.get("abd".bytes()).unwrap(), None);
+/// assert_eq!(t.get("xy".bytes()).unwrap(), None);
+/// assert_eq!(t.resident(), 1);
+/// drop(t);
+/// assert_eq!(loads, vec![b"ab".to_vec(), b"xy".to_vec()]);
+/// ```
+pub struct LazyTrie<K: Eq + Ord + Clone, V, L> {
+    loader: L,
+    shard_depth: usize,
+    shards: LruTrie<K, Trie<K, V>>,
+}
+
+impl<K: Eq + Ord + Clone, V, L: SubtreeLoader<K, V>> LazyTrie<K, V, L> {
+    /// Creates a `Trie` loading its subtrees from `loader`, keeping at most `max_resident` of
+    /// them in memory, and at least the one being read
+    pub fn new(loader: L, shard_depth: usize, max_resident: usize) -> Self {
+        LazyTrie {
+            loader,
+            shard_depth,
+            shards: LruTrie::with_capacity(max_resident.max(1)),
+        }
+    }
+
+    /// Number of subtrees in memory
+    pub fn resident(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Drops the subtree of `prefix` from memory, returning whether it was resident
+    pub fn evict<I: Iterator<Item = K>>(&mut self, prefix: I) -> bool {
+        let prefix: Vec<K> = prefix.take(self.shard_depth).collect();
+        self.shards.remove(prefix.into_iter()).is_some()
+    }
+
+    /// Value of a key, loading its subtree when not resident
+    ///
+    /// Fails with the error of the loader.
+    pub fn get<I: Iterator<Item = K>>(&mut self, key: I) -> Result<Option<&V>, TrieError> {
+        let key: Vec<K> = key.collect();
+        let shard = self.shard(&key)?;
+        Ok(shard.and_then(|shard| shard.get(key.into_iter())))
+    }
+
+    /// Whether the key has a value, loading its subtree when not resident
+    pub fn contains_key<I: Iterator<Item = K>>(&mut self, key: I) -> Result<bool, TrieError> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Subtree holding `key`, loaded when not resident
+    fn shard(&mut self, key: &[K]) -> Result<Option<&Trie<K, V>>, TrieError> {
+        let prefix = &key[..key.len().min(self.shard_depth)];
+        if self.shards.peek(prefix.iter().cloned()).is_none() {
+            debug_event!(depth = prefix.len(), "loading subtree");
+            let shard = self.loader.load(prefix)?;
+            self.shards.insert(prefix.iter().cloned(), shard);
+        }
+        Ok(self.shards.get(prefix.iter().cloned()))
+    }
+}
@@ -1,7 +1,84 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(any(feature = "unsafe-opt", feature = "ffi")), forbid(unsafe_code))]
+#![cfg_attr(all(feature = "ffi", not(feature = "unsafe-opt")), deny(unsafe_code))]
 
+#[macro_use]
+mod instrument;
+
+pub mod analysis;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "bench_support")]
+pub mod bench_support;
+pub mod bloom;
+pub mod borrowed;
+pub mod builder;
+pub mod chunked;
+pub mod codec;
+pub mod command;
+pub mod completion;
+pub mod config;
+pub mod count;
+pub mod curie;
+pub mod dna;
+pub mod domain;
+pub mod dump;
 pub mod error;
+pub mod estimate;
+pub mod extension;
+pub mod external;
+pub mod fallback;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+pub mod ffi;
+#[cfg(feature = "filter")]
+pub mod filter;
+pub mod folding;
+pub mod hash_trie;
+#[cfg(feature = "get-size")]
+mod heap_size;
+pub mod inherit;
+pub mod int_key;
+pub mod interner;
+#[cfg(feature = "ipnet")]
+pub mod ip_table;
+pub mod journal;
+pub mod keyword;
+pub mod lazy;
+pub mod lru;
+pub mod merged;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "model")]
+pub mod model;
+pub mod multimap;
+pub mod nested;
+pub mod noisy;
+pub mod ordering;
+pub mod os_key;
+pub mod path_map;
+pub mod path_trie;
+pub mod phone;
+pub mod progress;
+pub mod quota;
+pub mod rank;
+pub mod rewrite;
+mod rng;
+pub mod set;
+pub mod set_ops;
+pub mod shared;
+pub mod slab;
+pub mod spelling;
+pub mod transaction;
+pub mod transduce;
 pub mod trie;
 pub mod trie_node;
+#[cfg(feature = "unicode")]
+pub mod unicode;
+pub mod utf16;
+pub mod vocab;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod watch;
 
 pub use trie::Trie;
@@ -0,0 +1,119 @@
+//! Prefix-aware cache holding a bounded number of entries, evicting the least recently used
+
+use crate::trie::Trie;
+use std::collections::BTreeMap;
+
+/// Trie holding at most `capacity` entries, evicting the least recently used one on insert
+///
+/// Inserting or reading an entry through `get` or `longest_prefix` marks it as the most
+/// recently used, `peek` does not. Evicted keys are removed with the nodes left empty.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::lru::LruTrie;
+///
+/// let mut cache = LruTrie::with_capacity(2);
+/// cache.insert("/a".bytes(), 1);
+/// cache.insert("/b".bytes(), 2);
+/// assert_eq!(cache.get("/a".bytes()), Some(&1));
+///
+/// let evicted = cache.insert("/c".bytes(), 3);
+/// assert_eq!(evicted, Some((b"/b".to_vec(), 2)));
+/// assert_eq!(cache.longest_prefix("/a/index.html".bytes()), Some(&1));
+/// assert_eq!(cache.len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LruTrie<K: Eq + Ord + Clone, V> {
+    trie: Trie<K, (V, u64)>,
+    /// Keys by the tick of their last use, the oldest first
+    recency: BTreeMap<u64, Vec<K>>,
+    tick: u64,
+    capacity: usize,
+}
+
+impl<K: Eq + Ord + Clone, V> LruTrie<K, V> {
+    /// Creates an empty cache holding at most `capacity` entries
+    pub fn with_capacity(capacity: usize) -> Self {
+        LruTrie {
+            trie: Trie::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            capacity,
+        }
+    }
+
+    /// Maximum number of entries
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.recency.len()
+    }
+
+    /// Whether the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.recency.is_empty()
+    }
+
+    /// Inserts a value as the most recently used, returning the entry evicted to make room
+    ///
+    /// Replacing the value of a key already cached evicts nothing. With a capacity of 0 the
+    /// new entry is returned right away.
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<(Vec<K>, V)> {
+        let key: Vec<K> = key.collect();
+        if self.capacity == 0 {
+            return Some((key, value));
+        }
+        self.tick += 1;
+        if let Some((_, tick)) = self.trie.insert(key.iter().cloned(), (value, self.tick)) {
+            self.recency.remove(&tick);
+            self.recency.insert(self.tick, key);
+            return None;
+        }
+        self.recency.insert(self.tick, key);
+        if self.recency.len() <= self.capacity {
+            return None;
+        }
+        let (_, oldest) = self.recency.pop_first()?;
+        let (value, _) = self.trie.remove(oldest.iter().cloned())?;
+        Some((oldest, value))
+    }
+
+    /// Value of a key, marking it as the most recently used
+    pub fn get<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&V> {
+        let key: Vec<K> = key.collect();
+        self.touch(key)
+    }
+
+    /// Value of the longest cached key `key` starts with, marking it as the most recently used
+    pub fn longest_prefix<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&V> {
+        let mut key: Vec<K> = key.collect();
+        let (ix, _) = self.trie.find_prefixes(key.iter().cloned()).pop()?;
+        key.truncate(ix + 1);
+        self.touch(key)
+    }
+
+    /// Value of a key, without changing its recency
+    pub fn peek<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        self.trie.get(key).map(|(value, _)| value)
+    }
+
+    /// Removes a key, returning its value
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        let (value, tick) = self.trie.remove(key)?;
+        self.recency.remove(&tick);
+        Some(value)
+    }
+
+    fn touch(&mut self, key: Vec<K>) -> Option<&V> {
+        let (value, tick) = self.trie.get_mut(key.iter().cloned())?;
+        self.recency.remove(tick);
+        self.tick += 1;
+        *tick = self.tick;
+        self.recency.insert(self.tick, key);
+        Some(value)
+    }
+}
@@ -0,0 +1,75 @@
+//! Iteration over several tries as one sorted stream, without merging them
+
+use crate::trie::{Trie, TrieIterator};
+use std::iter::Peekable;
+
+/// Iterator over the keys of several tries in ascending order, created with
+/// `Trie::merged_iter()`
+///
+/// Each key is yielded once, with the value chosen by the conflict policy among the values of
+/// the tries holding it.
+pub struct MergedIter<'a, K: Eq + Ord + Clone, V, F> {
+    layers: Vec<Peekable<TrieIterator<'a, K, V>>>,
+    resolve: F,
+    values: Vec<&'a V>,
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Iterates the keys of all the `tries` in ascending order, merged on the fly
+    ///
+    /// `resolve` picks the value of a key among the values of the tries holding it, given in
+    /// the order of `tries`, so layered configurations can let the last layer win.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let defaults: Trie<u8, &str> = [("color".bytes(), "auto"), ("pager".bytes(), "less")].into_iter().collect();
+    /// let site: Trie<u8, &str> = [("pager".bytes(), "more")].into_iter().collect();
+    /// let user: Trie<u8, &str> = [("color".bytes(), "never"), ("editor".bytes(), "vi")].into_iter().collect();
+    ///
+    /// let merged: Vec<(Vec<u8>, &str)> = Trie::merged_iter(&[&defaults, &site, &user], |values| values[values.len() - 1])
+    ///     .map(|(key, value)| (key, *value))
+    ///     .collect();
+    /// assert_eq!(merged, vec![
+    ///     (b"color".to_vec(), "never"),
+    ///     (b"editor".to_vec(), "vi"),
+    ///     (b"pager".to_vec(), "more"),
+    /// ]);
+    /// ```
+    pub fn merged_iter<'a, F>(tries: &[&'a Trie<K, V>], resolve: F) -> MergedIter<'a, K, V, F>
+    where
+        F: FnMut(&[&'a V]) -> &'a V,
+    {
+        MergedIter {
+            layers: tries.iter().map(|trie| trie.iter().peekable()).collect(),
+            resolve,
+            values: Vec::with_capacity(tries.len()),
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord + Clone, V, F> Iterator for MergedIter<'a, K, V, F>
+where
+    F: FnMut(&[&'a V]) -> &'a V,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The layers are few, a linear scan finds the smallest head
+        let key = self
+            .layers
+            .iter_mut()
+            .filter_map(|layer| layer.peek().map(|(key, _)| key))
+            .min()?
+            .clone();
+        self.values.clear();
+        for layer in &mut self.layers {
+            if let Some((_, value)) = layer.next_if(|(head, _)| *head == key) {
+                self.values.push(value);
+            }
+        }
+        Some((key, (self.resolve)(&self.values)))
+    }
+}
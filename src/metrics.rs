@@ -0,0 +1,99 @@
+//! Metrics of a `Trie` in the Prometheus text exposition format
+
+use crate::builder::node_footprint;
+use crate::trie::Trie;
+use std::fmt::Debug;
+use std::io::{self, Write};
+
+impl<K: Eq + Ord + Clone + Debug, V> Trie<K, V> {
+    /// Writes the gauges of the `Trie` in the Prometheus text format, each sample carrying
+    /// `labels`
+    ///
+    /// The gauges are the number of entries, the number of nodes, the approximate memory of
+    /// the nodes in bytes, the length of the longest key, and the number of entries under each
+    /// first element of the keys, labelled `prefix` with its `Debug` representation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("ab".chars(), 1);
+    /// t.insert("ac".chars(), 2);
+    /// t.insert("b".chars(), 3);
+    ///
+    /// let mut out = Vec::new();
+    /// t.write_metrics(&mut out, &[("trie", "routes")]).unwrap();
+    /// let text = String::from_utf8(out).unwrap();
+    /// assert!(text.contains("ptrie_entries{trie=\"routes\"} 3\n"));
+    /// assert!(text.contains("ptrie_max_depth{trie=\"routes\"} 2\n"));
+    /// assert!(text.contains("ptrie_prefix_entries{trie=\"routes\",prefix=\"'a'\"} 2\n"));
+    /// ```
+    pub fn write_metrics<W: Write>(&self, out: &mut W, labels: &[(&str, &str)]) -> io::Result<()> {
+        let mut entries = 0;
+        let mut nodes = 0;
+        let mut max_depth = 0;
+        let mut stack = vec![(&self.root, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            nodes += 1;
+            if node.value.is_some() {
+                entries += 1;
+                max_depth = max_depth.max(depth);
+            }
+            stack.extend(node.children.iter().map(|(_, child)| (child, depth + 1)));
+        }
+        let labels = format_labels(labels);
+        let gauges = [
+            ("ptrie_entries", "Number of keys with a value", entries),
+            ("ptrie_nodes", "Number of nodes, including the root", nodes),
+            (
+                "ptrie_bytes",
+                "Approximate memory of the nodes, without the heap owned by keys and values",
+                nodes * node_footprint::<K, V>(),
+            ),
+            ("ptrie_max_depth", "Length of the longest key", max_depth),
+        ];
+        for (name, help, sample) in gauges {
+            writeln!(out, "# HELP {} {}", name, help)?;
+            writeln!(out, "# TYPE {} gauge", name)?;
+            writeln!(out, "{}{} {}", name, braced(&labels), sample)?;
+        }
+        writeln!(
+            out,
+            "# HELP ptrie_prefix_entries Number of keys starting with each first element"
+        )?;
+        writeln!(out, "# TYPE ptrie_prefix_entries gauge")?;
+        for (first, child) in &self.root.children {
+            let mut prefix_labels = labels.clone();
+            prefix_labels.push(format!("prefix=\"{}\"", escape(&format!("{:?}", first))));
+            let sample = child.count_values();
+            writeln!(
+                out,
+                "ptrie_prefix_entries{} {}",
+                braced(&prefix_labels),
+                sample
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_labels(labels: &[(&str, &str)]) -> Vec<String> {
+    labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect()
+}
+
+fn braced(labels: &[String]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    format!("{{{}}}", labels.join(","))
+}
+
+/// Escapes a label value as the exposition format requires
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,211 @@
+//! Differential testing of `Trie` against a `BTreeMap` model
+//!
+//! Operations are applied to both a `Trie` and a `BTreeMap<Vec<K>, V>`, asserting after each of
+//! them that lookups, iteration order and prefix queries agree. Downstream crates can reuse it to
+//! check their own operation sequences, or generate random ones with `random_ops`.
+
+use crate::rng::SplitMix;
+use crate::trie::Trie;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// Operation applied to both the `Trie` and the model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<K, V> {
+    Insert(Vec<K>, V),
+    Remove(Vec<K>),
+    RemoveSubtree(Vec<K>),
+    Get(Vec<K>),
+    FindPrefixes(Vec<K>),
+    FindLongestPrefix(Vec<K>),
+    FindPostfixes(Vec<K>),
+    IterPrefix(Vec<K>),
+}
+
+/// A `Trie` checked against a `BTreeMap` holding the same entries
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::model::{ModelChecker, Op};
+///
+/// let mut checker = ModelChecker::new();
+/// checker.apply(&Op::Insert(b"ab".to_vec(), 1));
+/// checker.apply(&Op::Insert(b"a".to_vec(), 2));
+/// checker.apply(&Op::FindPrefixes(b"abc".to_vec()));
+/// checker.apply(&Op::Remove(b"a".to_vec()));
+/// assert_eq!(checker.trie().get("ab".bytes()), Some(&1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModelChecker<K: Eq + Ord + Clone, V> {
+    trie: Trie<K, V>,
+    model: BTreeMap<Vec<K>, V>,
+}
+
+impl<K: Eq + Ord + Clone + Debug, V: Clone + PartialEq + Debug> ModelChecker<K, V> {
+    /// Creates a checker with an empty `Trie` and model
+    pub fn new() -> Self {
+        ModelChecker {
+            trie: Trie::new(),
+            model: BTreeMap::new(),
+        }
+    }
+
+    /// Trie under test
+    pub fn trie(&self) -> &Trie<K, V> {
+        &self.trie
+    }
+
+    /// Model the trie is checked against
+    pub fn model(&self) -> &BTreeMap<Vec<K>, V> {
+        &self.model
+    }
+
+    /// Applies `op` to the trie and the model, panicking when their results or entries differ
+    pub fn apply(&mut self, op: &Op<K, V>) {
+        match op {
+            Op::Insert(key, value) => assert_eq!(
+                self.trie.insert(key.iter().cloned(), value.clone()),
+                self.model.insert(key.clone(), value.clone()),
+                "insert {:?}",
+                key
+            ),
+            Op::Remove(key) => assert_eq!(
+                self.trie.remove(key.iter().cloned()),
+                self.model.remove(key),
+                "remove {:?}",
+                key
+            ),
+            Op::RemoveSubtree(prefix) => {
+                self.trie.remove_subtree(prefix.iter().cloned());
+                self.model.retain(|key, _| !key.starts_with(prefix));
+            }
+            Op::Get(key) => assert_eq!(
+                self.trie.get(key.iter().cloned()),
+                self.model.get(key),
+                "get {:?}",
+                key
+            ),
+            Op::FindPrefixes(key) => {
+                let expected: Vec<(usize, &V)> = (0..key.len())
+                    .filter_map(|i| self.model.get(&key[..=i]).map(|value| (i, value)))
+                    .collect();
+                assert_eq!(
+                    self.trie.find_prefixes(key.iter().cloned()),
+                    expected,
+                    "find_prefixes {:?}",
+                    key
+                );
+            }
+            Op::FindLongestPrefix(key) => {
+                let expected = (1..=key.len()).rev().find_map(|len| self.model.get(&key[..len]));
+                assert_eq!(
+                    self.trie.find_longest_prefix(key.iter().cloned()),
+                    expected,
+                    "find_longest_prefix {:?}",
+                    key
+                );
+            }
+            Op::FindPostfixes(prefix) => {
+                let expected: Vec<&V> = self.descendants(prefix).map(|(_, v)| v).collect();
+                assert_eq!(
+                    self.trie.find_postfixes(prefix.iter().cloned()),
+                    expected,
+                    "find_postfixes {:?}",
+                    prefix
+                );
+            }
+            Op::IterPrefix(prefix) => {
+                let expected: Vec<(Vec<K>, &V)> =
+                    self.descendants(prefix).map(|(k, v)| (k.clone(), v)).collect();
+                let actual: Vec<(Vec<K>, &V)> =
+                    self.trie.iter_prefix(prefix.iter().cloned()).collect();
+                assert_eq!(actual, expected, "iter_prefix {:?}", prefix);
+            }
+        }
+        self.check_entries();
+    }
+
+    /// Applies every operation of `ops` in order
+    pub fn run<'a>(&mut self, ops: impl IntoIterator<Item = &'a Op<K, V>>)
+    where
+        K: 'a,
+        V: 'a,
+    {
+        for op in ops {
+            self.apply(op);
+        }
+    }
+
+    /// Asserts that the trie iterates the same entries as the model, in the same order
+    pub fn check_entries(&self) {
+        let actual: Vec<(Vec<K>, &V)> = self.trie.iter().collect();
+        let expected: Vec<(Vec<K>, &V)> = self.model.iter().map(|(k, v)| (k.clone(), v)).collect();
+        assert_eq!(actual, expected, "entries differ");
+        assert_eq!(
+            self.trie.is_empty(),
+            self.model.is_empty(),
+            "is_empty differs"
+        );
+        assert_eq!(self.trie.validate(), Ok(()));
+    }
+
+    fn descendants<'a>(&'a self, prefix: &'a [K]) -> impl Iterator<Item = (&'a Vec<K>, &'a V)> {
+        self.model
+            .range(prefix.to_vec()..)
+            .take_while(move |(key, _)| key.starts_with(prefix))
+    }
+}
+
+impl<K: Eq + Ord + Clone + Debug, V: Clone + PartialEq + Debug> Default for ModelChecker<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generates `count` pseudo-random operations from `seed`
+///
+/// Keys are made of up to `max_len` elements of `alphabet`, so a small alphabet produces many
+/// shared prefixes. The same seed always produces the same operations.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::model::{random_ops, ModelChecker};
+///
+/// let ops = random_ops(7, 500, b"abc", 4);
+/// assert_eq!(ops, random_ops(7, 500, b"abc", 4));
+/// ModelChecker::new().run(&ops);
+/// ```
+pub fn random_ops<K: Clone>(
+    seed: u64,
+    count: usize,
+    alphabet: &[K],
+    max_len: usize,
+) -> Vec<Op<K, u64>> {
+    let mut rng = SplitMix::new(seed);
+    let key = |rng: &mut SplitMix| -> Vec<K> {
+        if alphabet.is_empty() {
+            return Vec::new();
+        }
+        let len = rng.below(max_len + 1);
+        (0..len)
+            .map(|_| alphabet[rng.below(alphabet.len())].clone())
+            .collect()
+    };
+    (0..count)
+        .map(|_| match rng.below(16) {
+            0..=5 => {
+                let k = key(&mut rng);
+                Op::Insert(k, rng.next())
+            }
+            6..=7 => Op::Remove(key(&mut rng)),
+            8 => Op::RemoveSubtree(key(&mut rng)),
+            9..=10 => Op::Get(key(&mut rng)),
+            11 => Op::FindPrefixes(key(&mut rng)),
+            12 => Op::FindLongestPrefix(key(&mut rng)),
+            13 => Op::FindPostfixes(key(&mut rng)),
+            _ => Op::IterPrefix(key(&mut rng)),
+        })
+        .collect()
+}
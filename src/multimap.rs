@@ -0,0 +1,117 @@
+//! Trie storing several values per key
+
+use crate::trie::Trie;
+use std::{mem, slice};
+
+/// Values of a key, stored inline while there is only one of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Bucket<V> {
+    One(V),
+    Many(Vec<V>),
+}
+
+impl<V> Bucket<V> {
+    fn as_slice(&self) -> &[V] {
+        match self {
+            Bucket::One(value) => slice::from_ref(value),
+            Bucket::Many(values) => values,
+        }
+    }
+
+    fn push(&mut self, value: V) {
+        match self {
+            Bucket::Many(values) => values.push(value),
+            Bucket::One(_) => {
+                if let Bucket::One(first) = mem::replace(self, Bucket::Many(Vec::new())) {
+                    *self = Bucket::Many(vec![first, value]);
+                }
+            }
+        }
+    }
+
+    fn into_vec(self) -> Vec<V> {
+        match self {
+            Bucket::One(value) => vec![value],
+            Bucket::Many(values) => values,
+        }
+    }
+}
+
+/// Trie mapping each key to a list of values, in insertion order
+///
+/// A key with a single value stores it without allocating a list, which is the common case of
+/// entity dictionaries where few surface forms are ambiguous.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::multimap::TrieMultiMap;
+///
+/// let mut entities = TrieMultiMap::new();
+/// entities.insert("paris".bytes(), "Q90");
+/// entities.insert("paris".bytes(), "Q167646");
+/// entities.insert("london".bytes(), "Q84");
+///
+/// assert_eq!(entities.get_all("paris".bytes()), &["Q90", "Q167646"]);
+/// assert_eq!(entities.get_all("london".bytes()), &["Q84"]);
+/// assert!(entities.get_all("rome".bytes()).is_empty());
+///
+/// assert_eq!(entities.remove_all("paris".bytes()), vec!["Q90", "Q167646"]);
+/// assert!(!entities.contains_key("paris".bytes()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrieMultiMap<K: Eq + Ord + Clone, V> {
+    trie: Trie<K, Bucket<V>>,
+}
+
+impl<K: Eq + Ord + Clone, V> TrieMultiMap<K, V> {
+    /// Creates an empty multi-map
+    pub fn new() -> Self {
+        TrieMultiMap { trie: Trie::new() }
+    }
+
+    /// Appends a value to the values of a key
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) {
+        if let Err(occupied) = self.trie.try_insert(key, Bucket::One(value)) {
+            if let Bucket::One(value) = occupied.value {
+                occupied.existing.push(value);
+            }
+        }
+    }
+
+    /// Values of a key in insertion order, empty when the key is absent
+    pub fn get_all<I: Iterator<Item = K>>(&self, key: I) -> &[V] {
+        self.trie.get(key).map_or(&[], Bucket::as_slice)
+    }
+
+    /// Whether the key has at least one value
+    pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.trie.contains_key(key)
+    }
+
+    /// Removes a key, returning its values
+    pub fn remove_all<I: Iterator<Item = K>>(&mut self, key: I) -> Vec<V> {
+        self.trie.remove(key).map_or_else(Vec::new, Bucket::into_vec)
+    }
+
+    /// Iterate the keys starting with `prefix` with their values, in key order
+    pub fn iter_prefix<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+    ) -> impl Iterator<Item = (Vec<K>, &[V])> + '_ {
+        self.trie
+            .iter_prefix(prefix)
+            .map(|(key, bucket)| (key, bucket.as_slice()))
+    }
+
+    /// Whether the multi-map has no values
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Default for TrieMultiMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,96 @@
+//! Two-level tries, such as a `Trie` of tenants holding a `Trie` of paths for each of them
+
+use crate::trie::Trie;
+
+impl<K1: Eq + Ord + Clone, K2: Eq + Ord + Clone, V> Trie<K1, Trie<K2, V>> {
+    /// Inserts a value under the `(outer, inner)` pair of keys, returning the previous one
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut routes: Trie<u8, Trie<u8, u32>> = Trie::new();
+    /// routes.insert_nested(("acme".bytes(), "/api".bytes()), 1);
+    /// routes.insert_nested(("acme".bytes(), "/".bytes()), 2);
+    /// routes.insert_nested(("globex".bytes(), "/".bytes()), 3);
+    ///
+    /// assert_eq!(routes.get_nested(("acme".bytes(), "/api".bytes())), Some(&1));
+    /// assert_eq!(routes.get_nested(("globex".bytes(), "/api".bytes())), None);
+    /// ```
+    pub fn insert_nested<I1, I2>(&mut self, (outer, inner): (I1, I2), value: V) -> Option<V>
+    where
+        I1: Iterator<Item = K1>,
+        I2: Iterator<Item = K2>,
+    {
+        self.get_or_insert_with(outer, Trie::new).insert(inner, value)
+    }
+
+    /// Value of the `(outer, inner)` pair of keys
+    pub fn get_nested<I1, I2>(&self, (outer, inner): (I1, I2)) -> Option<&V>
+    where
+        I1: Iterator<Item = K1>,
+        I2: Iterator<Item = K2>,
+    {
+        self.get(outer)?.get(inner)
+    }
+
+    /// Mutable value of the `(outer, inner)` pair of keys
+    pub fn get_nested_mut<I1, I2>(&mut self, (outer, inner): (I1, I2)) -> Option<&mut V>
+    where
+        I1: Iterator<Item = K1>,
+        I2: Iterator<Item = K2>,
+    {
+        self.get_mut(outer)?.get_mut(inner)
+    }
+
+    /// Removes the value of the `(outer, inner)` pair of keys, and the inner `Trie` when it
+    /// becomes empty
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut routes: Trie<u8, Trie<u8, u32>> = Trie::new();
+    /// routes.insert_nested(("acme".bytes(), "/api".bytes()), 1);
+    /// assert_eq!(routes.remove_nested(("acme".bytes(), "/api".bytes())), Some(1));
+    /// assert!(routes.is_empty());
+    /// ```
+    pub fn remove_nested<I1, I2>(&mut self, (outer, inner): (I1, I2)) -> Option<V>
+    where
+        I1: Iterator<Item = K1>,
+        I2: Iterator<Item = K2>,
+    {
+        let outer: Vec<K1> = outer.collect();
+        let nested = self.get_mut(outer.iter().cloned())?;
+        let value = nested.remove(inner)?;
+        if nested.is_empty() {
+            self.remove(outer.into_iter());
+        }
+        Some(value)
+    }
+
+    /// Iterate the entries of every inner `Trie` with their pair of keys, in key order
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut routes: Trie<char, Trie<char, u32>> = Trie::new();
+    /// routes.insert_nested(("b".chars(), "x".chars()), 1);
+    /// routes.insert_nested(("a".chars(), "y".chars()), 2);
+    /// routes.insert_nested(("a".chars(), "x".chars()), 3);
+    ///
+    /// let values: Vec<u32> = routes.flatten_iter().map(|(_, v)| *v).collect();
+    /// assert_eq!(values, vec![3, 2, 1]);
+    /// ```
+    pub fn flatten_iter(&self) -> impl Iterator<Item = ((Vec<K1>, Vec<K2>), &V)> + '_ {
+        self.iter().flat_map(|(outer, nested)| {
+            nested
+                .iter()
+                .map(move |(inner, value)| ((outer.clone(), inner), value))
+        })
+    }
+}
@@ -0,0 +1,65 @@
+//! Prefix matching tolerant to stray symbols in the input, like OCR or log noise
+
+use crate::trie::Trie;
+
+/// Longest prefix found by `Trie::find_longest_prefix_skipping`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipMatch<'a, V> {
+    /// Number of input symbols covered, the skipped ones included
+    pub len: usize,
+    /// Positions in the input of the skipped symbols, in order
+    pub skipped: Vec<usize>,
+    pub value: &'a V,
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Longest non-empty prefix of `key` with a value, skipping up to `max_skips` input symbols
+    /// which match no edge
+    ///
+    /// Symbols are matched whenever they can be, so a skip only happens where the exact walk
+    /// would stop. The match ends on a matched symbol, never on a skipped one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut ids = Trie::new();
+    /// ids.insert("INV-2024".bytes(), "invoice");
+    ///
+    /// let found = ids.find_longest_prefix_skipping("IN.V-20'24 total".bytes(), 2).unwrap();
+    /// assert_eq!((found.len, found.skipped, found.value), (10, vec![2, 7], &"invoice"));
+    /// assert!(ids.find_longest_prefix_skipping("IN.V-20'24".bytes(), 1).is_none());
+    /// ```
+    pub fn find_longest_prefix_skipping<I: Iterator<Item = K>>(
+        &self,
+        key: I,
+        max_skips: usize,
+    ) -> Option<SkipMatch<'_, V>> {
+        let mut node = &self.root;
+        let mut skipped = Vec::new();
+        let mut best = None;
+        for (pos, k) in key.enumerate() {
+            if node.children.is_empty() {
+                break;
+            }
+            match node.children.binary_search_by(|(edge, _)| edge.cmp(&k)) {
+                Ok(ix) => {
+                    node = &node.children[ix].1;
+                    if let Some(value) = node.value.as_ref() {
+                        best = Some((pos + 1, skipped.len(), value));
+                    }
+                }
+                Err(_) if skipped.len() < max_skips => skipped.push(pos),
+                Err(_) => break,
+            }
+        }
+        let (len, skips, value) = best?;
+        skipped.truncate(skips);
+        Some(SkipMatch {
+            len,
+            skipped,
+            value,
+        })
+    }
+}
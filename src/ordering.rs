@@ -0,0 +1,124 @@
+//! Domain-specific orders for the key elements of a `Trie`
+//!
+//! Children are sorted and searched with the `Ord` of the key elements, so wrapping them in
+//! `Ordered` makes every ordering-sensitive query follow another order: sorted iteration, rank,
+//! completions, dumps. Elements the order deems equal share their edge, so a case-insensitive
+//! order also makes lookups case-insensitive.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Order of the key elements `K`, to be consistent like an `Ord` implementation
+pub trait KeyOrd<K> {
+    fn cmp(a: &K, b: &K) -> Ordering;
+}
+
+/// ASCII letters compared without their case, other elements by their value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AsciiCaseInsensitive;
+
+impl KeyOrd<u8> for AsciiCaseInsensitive {
+    fn cmp(a: &u8, b: &u8) -> Ordering {
+        a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase())
+    }
+}
+
+impl KeyOrd<char> for AsciiCaseInsensitive {
+    fn cmp(a: &char, b: &char) -> Ordering {
+        a.to_ascii_lowercase().cmp(&b.to_ascii_lowercase())
+    }
+}
+
+/// Reverse of the `Ord` of the elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Descending;
+
+impl<K: Ord> KeyOrd<K> for Descending {
+    fn cmp(a: &K, b: &K) -> Ordering {
+        b.cmp(a)
+    }
+}
+
+/// Key element compared with the order `O`
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::ordering::{ordered, AsciiCaseInsensitive, Ordered};
+/// use ptrie::Trie;
+///
+/// let mut headers: Trie<Ordered<u8, AsciiCaseInsensitive>, u32> = Trie::new();
+/// headers.insert(ordered("Content-Type".bytes()), 1);
+/// headers.insert(ordered("accept".bytes()), 2);
+///
+/// assert_eq!(headers.get(ordered("CONTENT-TYPE".bytes())), Some(&1));
+/// let names: Vec<String> = headers
+///     .iter()
+///     .map(|(k, _)| k.into_iter().map(|b| char::from(b.into_inner())).collect())
+///     .collect();
+/// assert_eq!(names, vec!["accept", "Content-Type"]);
+/// ```
+pub struct Ordered<K, O> {
+    key: K,
+    order: PhantomData<fn() -> O>,
+}
+
+/// Wraps the elements of a key to compare them with the order `O`
+pub fn ordered<K, O, I: IntoIterator<Item = K>>(key: I) -> impl Iterator<Item = Ordered<K, O>> {
+    key.into_iter().map(Ordered::new)
+}
+
+impl<K, O> Ordered<K, O> {
+    /// Wraps `key`
+    pub fn new(key: K) -> Self {
+        Ordered {
+            key,
+            order: PhantomData,
+        }
+    }
+
+    /// The wrapped element
+    pub fn get(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the wrapped element
+    pub fn into_inner(self) -> K {
+        self.key
+    }
+}
+
+impl<K: Clone, O> Clone for Ordered<K, O> {
+    fn clone(&self) -> Self {
+        Ordered::new(self.key.clone())
+    }
+}
+
+impl<K: Copy, O> Copy for Ordered<K, O> {}
+
+impl<K: fmt::Debug, O> fmt::Debug for Ordered<K, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.key.fmt(f)
+    }
+}
+
+impl<K, O: KeyOrd<K>> PartialEq for Ordered<K, O> {
+    fn eq(&self, other: &Self) -> bool {
+        O::cmp(&self.key, &other.key) == Ordering::Equal
+    }
+}
+
+impl<K, O: KeyOrd<K>> Eq for Ordered<K, O> {}
+
+impl<K, O: KeyOrd<K>> PartialOrd for Ordered<K, O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, O: KeyOrd<K>> Ord for Ordered<K, O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        O::cmp(&self.key, &other.key)
+    }
+}
@@ -0,0 +1,115 @@
+//! Keys from `OsStr` and `Path` values, without lossy conversions to UTF-8
+//!
+//! Byte keys are the platform encoding of `OsStr::as_encoded_bytes`: the raw bytes on Unix,
+//! WTF-8 on Windows, which keeps unpaired surrogates of file names. Both are supersets of
+//! UTF-8, so valid Unicode names have the same keys on every platform.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Bytes of an `OsStr`, to use as a `Trie<u8, V>` key
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::os_key::{os_str_key, os_string_from_key};
+/// use ptrie::Trie;
+/// use std::ffi::OsStr;
+///
+/// let mut t = Trie::new();
+/// t.insert(os_str_key(OsStr::new("caf\u{e9}.txt")), 1);
+/// let (key, _) = t.iter().next().unwrap();
+/// assert_eq!(os_string_from_key(&key).unwrap(), "caf\u{e9}.txt");
+/// ```
+pub fn os_str_key(s: &OsStr) -> impl Iterator<Item = u8> + '_ {
+    s.as_encoded_bytes().iter().copied()
+}
+
+/// Bytes of a `Path`, to use as a `Trie<u8, V>` key
+pub fn path_key(path: &Path) -> impl Iterator<Item = u8> + '_ {
+    os_str_key(path.as_os_str())
+}
+
+/// Components of a `Path`, to use as a `Trie<OsString, V>` key matching whole components
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::os_key::path_components_key;
+/// use ptrie::Trie;
+/// use std::path::Path;
+///
+/// let mut owners = Trie::new();
+/// owners.insert(path_components_key(Path::new("/srv/www")), "web");
+/// let owner = owners.find_longest_prefix(path_components_key(Path::new("/srv/www/index.html")));
+/// assert_eq!(owner, Some(&"web"));
+/// assert_eq!(owners.find_longest_prefix(path_components_key(Path::new("/srv/wwwx"))), None);
+/// ```
+pub fn path_components_key(path: &Path) -> impl Iterator<Item = OsString> + '_ {
+    path.components()
+        .map(|component| component.as_os_str().to_os_string())
+}
+
+/// `OsString` of a key made by `os_str_key`, any bytes being an `OsStr` on Unix
+#[cfg(unix)]
+pub fn os_string_from_key(bytes: &[u8]) -> Option<OsString> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(OsStr::from_bytes(bytes).to_os_string())
+}
+
+/// `OsString` of a key made by `os_str_key`, `None` when the bytes are not a whole encoding,
+/// like a prefix cut in the middle of a character
+#[cfg(windows)]
+pub fn os_string_from_key(bytes: &[u8]) -> Option<OsString> {
+    use std::os::windows::ffi::OsStringExt;
+    Some(OsString::from_wide(&wtf8_to_wide(bytes)?))
+}
+
+/// `OsString` of a key made by `os_str_key`, `None` when the bytes are not a whole encoding,
+/// like a prefix cut in the middle of a character
+#[cfg(not(any(unix, windows)))]
+pub fn os_string_from_key(bytes: &[u8]) -> Option<OsString> {
+    std::str::from_utf8(bytes).ok().map(OsString::from)
+}
+
+/// `PathBuf` of a key made by `path_key`
+pub fn path_from_key(bytes: &[u8]) -> Option<PathBuf> {
+    os_string_from_key(bytes).map(PathBuf::from)
+}
+
+/// Decodes WTF-8, the UTF-8 encoding extended to lone surrogates, into UTF-16 code units
+#[cfg_attr(not(windows), allow(dead_code))]
+fn wtf8_to_wide(bytes: &[u8]) -> Option<Vec<u16>> {
+    let mut wide = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let Some(&lead) = rest.first() {
+        let (len, init) = match lead {
+            0x00..=0x7f => (1, u32::from(lead)),
+            0xc2..=0xdf => (2, u32::from(lead & 0x1f)),
+            0xe0..=0xef => (3, u32::from(lead & 0x0f)),
+            0xf0..=0xf4 => (4, u32::from(lead & 0x07)),
+            _ => return None,
+        };
+        let sequence = rest.get(1..len)?;
+        let mut code = init;
+        for byte in sequence {
+            if byte & 0xc0 != 0x80 {
+                return None;
+            }
+            code = (code << 6) | u32::from(byte & 0x3f);
+        }
+        let min = [0, 0, 0x80, 0x800, 0x10000][len];
+        if code < min || code > 0x10ffff {
+            return None;
+        }
+        if code >= 0x10000 {
+            let code = code - 0x10000;
+            wide.push(0xd800 | (code >> 10) as u16);
+            wide.push(0xdc00 | (code & 0x3ff) as u16);
+        } else {
+            wide.push(code as u16);
+        }
+        rest = &rest[len..];
+    }
+    Some(wide)
+}
@@ -0,0 +1,128 @@
+//! Map keyed by file-system paths, matching whole path components
+//!
+//! Paths are split with `Path::components`, so platform separators and `OsStr` components are
+//! handled by the standard library, and `/a/bc` is never considered under `/a/b`.
+
+use crate::trie::Trie;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+
+/// Owned version of a `std::path::Component`, used as key of the `PathTrieMap`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathComponent {
+    /// Windows prefix such as `C:` or `\\server\share`
+    Prefix(OsString),
+    RootDir,
+    CurDir,
+    ParentDir,
+    Normal(OsString),
+}
+
+impl PathComponent {
+    fn as_os_str(&self) -> &OsStr {
+        match self {
+            PathComponent::Prefix(s) | PathComponent::Normal(s) => s,
+            PathComponent::RootDir => Component::RootDir.as_os_str(),
+            PathComponent::CurDir => OsStr::new("."),
+            PathComponent::ParentDir => OsStr::new(".."),
+        }
+    }
+}
+
+impl From<Component<'_>> for PathComponent {
+    fn from(c: Component<'_>) -> Self {
+        match c {
+            Component::Prefix(p) => PathComponent::Prefix(p.as_os_str().to_os_string()),
+            Component::RootDir => PathComponent::RootDir,
+            Component::CurDir => PathComponent::CurDir,
+            Component::ParentDir => PathComponent::ParentDir,
+            Component::Normal(s) => PathComponent::Normal(s.to_os_string()),
+        }
+    }
+}
+
+/// Map from paths to values, for mount tables, ignore rules or watch registrations
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::path_map::PathTrieMap;
+/// use std::path::{Path, PathBuf};
+///
+/// let mut mounts = PathTrieMap::new();
+/// mounts.insert("/", "rootfs");
+/// mounts.insert("/home", "home");
+/// mounts.insert("/home/user/data", "data");
+///
+/// let (path, fs) = mounts.longest_ancestor("/home/user/file.txt").unwrap();
+/// assert_eq!(path, PathBuf::from("/home"));
+/// assert_eq!(*fs, "home");
+/// assert_eq!(mounts.longest_ancestor("/homework").map(|(_, v)| *v), Some("rootfs"));
+///
+/// let mut under_home: Vec<PathBuf> = mounts.descendants("/home").into_iter().map(|(p, _)| p).collect();
+/// under_home.sort();
+/// assert_eq!(under_home, vec![PathBuf::from("/home"), PathBuf::from("/home/user/data")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathTrieMap<V> {
+    trie: Trie<PathComponent, V>,
+}
+
+impl<V> PathTrieMap<V> {
+    /// Creates an empty map
+    pub fn new() -> Self {
+        PathTrieMap { trie: Trie::new() }
+    }
+
+    /// Adds a path, returning the value previously stored for it
+    pub fn insert<P: AsRef<Path>>(&mut self, path: P, value: V) -> Option<V> {
+        self.trie.insert(components(path.as_ref()), value)
+    }
+
+    /// Gets the value stored for exactly this path
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&V> {
+        self.trie.get(components(path.as_ref()))
+    }
+
+    /// Checks if a value is stored for this path
+    pub fn contains_path<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.trie.contains_key(components(path.as_ref()))
+    }
+
+    /// Finds the deepest stored path which is an ancestor of `path`, or `path` itself
+    pub fn longest_ancestor<P: AsRef<Path>>(&self, path: P) -> Option<(PathBuf, &V)> {
+        let key: Vec<PathComponent> = components(path.as_ref()).collect();
+        match self.trie.find_prefixes(key.iter().cloned()).pop() {
+            Some((ix, value)) => Some((to_path(&key[..=ix]), value)),
+            None => self.trie.root().value().map(|v| (PathBuf::new(), v)),
+        }
+    }
+
+    /// Returns the stored paths under `path`, including `path` itself
+    pub fn descendants<P: AsRef<Path>>(&self, path: P) -> Vec<(PathBuf, &V)> {
+        self.trie
+            .iter_prefix(components(path.as_ref()))
+            .map(|(key, value)| (to_path(&key), value))
+            .collect()
+    }
+
+    /// Checks if the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+}
+
+impl<V> Default for PathTrieMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn components(path: &Path) -> impl Iterator<Item = PathComponent> + '_ {
+    path.components().map(PathComponent::from)
+}
+
+fn to_path(key: &[PathComponent]) -> PathBuf {
+    key.iter().map(PathComponent::as_os_str).collect()
+}
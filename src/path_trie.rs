@@ -0,0 +1,334 @@
+//! HTTP-style path router built on top of the `Trie`
+//!
+//! Patterns are split on `/` into segments. A segment starting with `:` captures one path
+//! segment as a named parameter, and a final segment starting with `*` captures the rest of
+//! the path.
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+use crate::trie_node::NodeRef;
+use std::cmp::{Ordering, Reverse};
+
+/// Segment of a route pattern
+///
+/// The variants are ordered by matching precedence: static segments are tried before
+/// parameters, and parameters before wildcards.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Segment {
+    /// Segment that must match exactly
+    Static(String),
+    /// `:name` segment capturing a single path segment
+    Param(String),
+    /// `*name` segment capturing the rest of the path
+    Wildcard(String),
+}
+
+impl Segment {
+    /// Rank of the kind of segment in matching precedence, static segments first
+    fn kind(&self) -> u8 {
+        match self {
+            Segment::Static(_) => 0,
+            Segment::Param(_) => 1,
+            Segment::Wildcard(_) => 2,
+        }
+    }
+
+    fn parse(segment: &str) -> Self {
+        if let Some(name) = segment.strip_prefix(':') {
+            Segment::Param(name.to_string())
+        } else if let Some(name) = segment.strip_prefix('*') {
+            Segment::Wildcard(name.to_string())
+        } else {
+            Segment::Static(segment.to_string())
+        }
+    }
+}
+
+/// Parameters captured while routing a path, in the order they appear in the pattern
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Params<'a> {
+    captures: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Params<'a> {
+    /// Value captured for the parameter `name`
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.captures.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+
+    /// Iterate the `(name, value)` pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.captures.iter().copied()
+    }
+
+    /// Number of captured parameters
+    pub fn len(&self) -> usize {
+        self.captures.len()
+    }
+
+    /// Whether no parameter was captured
+    pub fn is_empty(&self) -> bool {
+        self.captures.is_empty()
+    }
+}
+
+/// Pattern matched by `PathTrie::route_with_priority`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteMatch<'a, V> {
+    /// Value registered for the pattern
+    pub value: &'a V,
+    /// Segments captured by the parameters and the catch-all of the pattern
+    pub params: Params<'a>,
+    /// Priority the pattern was registered with
+    pub priority: i32,
+}
+
+/// Router mapping path patterns such as `/users/:id/*rest` to values
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::path_trie::PathTrie;
+///
+/// let mut router = PathTrie::new();
+/// router.insert("/users/:id", "user").unwrap();
+/// router.insert("/users/me", "me").unwrap();
+/// router.insert("/static/*file", "static").unwrap();
+///
+/// let (value, params) = router.route("/users/42").unwrap();
+/// assert_eq!(*value, "user");
+/// assert_eq!(params.get("id"), Some("42"));
+///
+/// let (value, params) = router.route("/users/me").unwrap();
+/// assert_eq!(*value, "me");
+/// assert!(params.is_empty());
+///
+/// let (value, params) = router.route("/static/css/site.css").unwrap();
+/// assert_eq!(*value, "static");
+/// assert_eq!(params.get("file"), Some("css/site.css"));
+///
+/// assert!(router.route("/posts").is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathTrie<V> {
+    trie: Trie<Segment, (V, i32)>,
+    /// Highest priority of the patterns starting with each prefix
+    priorities: Trie<Segment, i32>,
+    tie_breaker: fn(&Segment, &Segment) -> Ordering,
+}
+
+impl<V> PathTrie<V> {
+    /// Creates an empty router
+    pub fn new() -> Self {
+        PathTrie {
+            trie: Trie::new(),
+            priorities: Trie::new(),
+            tie_breaker: Segment::cmp,
+        }
+    }
+
+    /// Orders the sibling parameters, or wildcards, of equal priority with `tie_breaker`
+    /// instead of by name
+    pub fn with_tie_breaker(mut self, tie_breaker: fn(&Segment, &Segment) -> Ordering) -> Self {
+        self.tie_breaker = tie_breaker;
+        self
+    }
+
+    /// Registers a pattern, returning the value previously registered for the same pattern
+    ///
+    /// Fails with `TrieError::InvalidPattern` when a wildcard is not the last segment.
+    pub fn insert(&mut self, pattern: &str, value: V) -> Result<Option<V>, TrieError> {
+        self.insert_with_priority(pattern, value, 0)
+    }
+
+    /// Registers a pattern like `insert`, with a priority among the patterns matching the same
+    /// paths
+    ///
+    /// Static segments are still tried before parameters, and parameters before wildcards.
+    /// Among sibling parameters, or sibling wildcards, the branch leading to the pattern of
+    /// highest priority is tried first, ties going to the tie breaker.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::path_trie::PathTrie;
+    ///
+    /// let mut router = PathTrie::new();
+    /// router.insert_with_priority("/files/:name", "by name", 0).unwrap();
+    /// router.insert_with_priority("/files/:id", "by id", 10).unwrap();
+    ///
+    /// let found = router.route_with_priority("/files/42").unwrap();
+    /// assert_eq!((*found.value, found.priority), ("by id", 10));
+    /// assert_eq!(found.params.get("id"), Some("42"));
+    /// ```
+    pub fn insert_with_priority(
+        &mut self,
+        pattern: &str,
+        value: V,
+        priority: i32,
+    ) -> Result<Option<V>, TrieError> {
+        let segments = Self::parse_pattern(pattern)?;
+        let previous = self.trie.insert(segments.iter().cloned(), (value, priority));
+        // Every prefix of the pattern has a node in both tries, deepest first
+        for len in (0..=segments.len()).rev() {
+            let node = self.trie.node(segments[..len].iter().cloned());
+            let own = node.and_then(|node| node.value()).map(|(_, priority)| *priority);
+            let below = self
+                .priorities
+                .node(segments[..len].iter().cloned())
+                .into_iter()
+                .flat_map(|node| node.children().filter_map(|(_, child)| child.value().copied()));
+            let highest = own.into_iter().chain(below).max().unwrap_or(priority);
+            self.priorities.insert(segments[..len].iter().cloned(), highest);
+        }
+        Ok(previous.map(|(value, _)| value))
+    }
+
+    /// Gets the value registered for a pattern, without matching parameters
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::path_trie::PathTrie;
+    ///
+    /// let mut router = PathTrie::new();
+    /// router.insert("/users/:id", 1).unwrap();
+    /// assert_eq!(router.get("/users/:id"), Some(&1));
+    /// assert_eq!(router.get("/users/42"), None);
+    /// ```
+    pub fn get(&self, pattern: &str) -> Option<&V> {
+        let segments = Self::parse_pattern(pattern).ok()?;
+        self.trie.get(segments.into_iter()).map(|(value, _)| value)
+    }
+
+    /// Finds the value of the pattern matching `path`, with the captured parameters
+    ///
+    /// Static segments take precedence over parameters, and parameters over wildcards. When a
+    /// more specific branch does not lead to a match, the next candidate is tried.
+    pub fn route<'a>(&'a self, path: &'a str) -> Option<(&'a V, Params<'a>)> {
+        self.route_with_priority(path)
+            .map(|found| (found.value, found.params))
+    }
+
+    /// Same as `route`, also returning the priority of the matched pattern
+    pub fn route_with_priority<'a>(&'a self, path: &'a str) -> Option<RouteMatch<'a, V>> {
+        let segments = split_path(path);
+        let mut captures = Vec::new();
+        let router = Router {
+            path,
+            tie_breaker: self.tie_breaker,
+        };
+        router
+            .route_node(
+                self.trie.root(),
+                self.priorities.root(),
+                &segments,
+                &mut captures,
+            )
+            .map(|(value, priority)| RouteMatch {
+                value,
+                params: Params { captures },
+                priority: *priority,
+            })
+    }
+
+    /// Checks if the router has no registered pattern
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, TrieError> {
+        let segments: Vec<Segment> = split_path(pattern)
+            .into_iter()
+            .map(|(_, s)| Segment::parse(s))
+            .collect();
+        let wildcard = segments.iter().position(|s| matches!(s, Segment::Wildcard(_)));
+        match wildcard {
+            Some(ix) if ix + 1 != segments.len() => Err(TrieError::InvalidPattern(format!(
+                "Wildcard must be the last segment of {}",
+                pattern
+            ))),
+            _ => Ok(segments),
+        }
+    }
+}
+
+impl<V> Default for PathTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Splits a path in its non-empty segments, with their byte offset
+fn split_path(path: &str) -> Vec<(usize, &str)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    for part in path.split('/') {
+        if !part.is_empty() {
+            segments.push((start, part));
+        }
+        start += part.len() + 1;
+    }
+    segments
+}
+
+/// State of a lookup shared by the nodes it visits
+struct Router<'a> {
+    path: &'a str,
+    tie_breaker: fn(&Segment, &Segment) -> Ordering,
+}
+
+impl<'a> Router<'a> {
+    fn route_node<V>(
+        &self,
+        node: NodeRef<'a, Segment, (V, i32)>,
+        priorities: NodeRef<'a, Segment, i32>,
+        segments: &[(usize, &'a str)],
+        captures: &mut Vec<(&'a str, &'a str)>,
+    ) -> Option<&'a (V, i32)> {
+        let Some(&(start, segment)) = segments.first() else {
+            return node.value();
+        };
+        let edge = Segment::Static(segment.to_string());
+        if let (Some(child), Some(child_priorities)) = (node.child(&edge), priorities.child(&edge))
+        {
+            let found = self.route_node(child, child_priorities, &segments[1..], captures);
+            if found.is_some() {
+                return found;
+            }
+        }
+        let mut candidates: Vec<_> = priorities
+            .children()
+            .filter(|(edge, _)| !matches!(edge, Segment::Static(_)))
+            .collect();
+        candidates.sort_by(|(a, pa), (b, pb)| {
+            let key = |edge: &Segment, p: &NodeRef<'a, Segment, i32>| {
+                (edge.kind(), Reverse(p.value().copied()))
+            };
+            key(a, pa).cmp(&key(b, pb)).then_with(|| (self.tie_breaker)(a, b))
+        });
+        for (edge, child_priorities) in candidates {
+            let Some(child) = node.child(edge) else {
+                continue;
+            };
+            match edge {
+                Segment::Static(_) => continue,
+                Segment::Param(name) => {
+                    captures.push((name, segment));
+                    let found = self.route_node(child, child_priorities, &segments[1..], captures);
+                    if found.is_some() {
+                        return found;
+                    }
+                    captures.pop();
+                }
+                Segment::Wildcard(name) => {
+                    if let Some(found) = child.value() {
+                        captures.push((name, self.path[start..].trim_end_matches('/')));
+                        return Some(found);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,174 @@
+//! Routing of E.164 phone numbers by longest digit prefix
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+
+/// Prefix shadowed by a shorter prefix of the same table, found by `PhonePrefixTable::load`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlap {
+    /// More specific prefix, which takes precedence when routing
+    pub prefix: String,
+    /// Shorter prefix covering it
+    pub covered_by: String,
+}
+
+/// Outcome of a bulk load with `PhonePrefixTable::load`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Number of rows stored
+    pub inserted: usize,
+    /// Prefixes appearing more than once, the last row wins
+    pub duplicates: Vec<String>,
+    /// Rows which are not valid phone prefixes, with the reason
+    pub invalid: Vec<(String, String)>,
+    /// Stored prefixes covered by a shorter stored prefix
+    pub overlaps: Vec<Overlap>,
+}
+
+/// Table routing phone numbers to the value of their longest registered prefix
+///
+/// Numbers and prefixes may start with `+` and contain spaces, dashes, dots and parentheses,
+/// which are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::phone::PhonePrefixTable;
+///
+/// let mut table = PhonePrefixTable::new();
+/// table.insert_prefix("+44", "UK").unwrap();
+/// table.insert_prefix("+4479", "UK mobile").unwrap();
+///
+/// assert_eq!(table.route("+44 7911 123456"), Some(("4479".to_string(), &"UK mobile")));
+/// assert_eq!(table.route("+44 20 7946 0958"), Some(("44".to_string(), &"UK")));
+/// assert_eq!(table.route("+1 555 0100"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PhonePrefixTable<V> {
+    trie: Trie<u8, V>,
+}
+
+impl<V> PhonePrefixTable<V> {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        PhonePrefixTable { trie: Trie::new() }
+    }
+
+    /// Registers a prefix, returning the value previously registered for it
+    ///
+    /// Fails with `TrieError::InvalidKey` when the prefix contains other characters than digits
+    /// and separators, or no digit at all.
+    pub fn insert_prefix(&mut self, prefix: &str, value: V) -> Result<Option<V>, TrieError> {
+        let digits = normalize(prefix)?;
+        Ok(self.trie.insert(digits.bytes(), value))
+    }
+
+    /// Gets the value registered for exactly this prefix
+    pub fn get(&self, prefix: &str) -> Option<&V> {
+        let digits = normalize(prefix).ok()?;
+        self.trie.get(digits.bytes())
+    }
+
+    /// Finds the longest registered prefix of a number, returned as digits with its value
+    pub fn route(&self, number: &str) -> Option<(String, &V)> {
+        let digits = normalize(number).ok()?;
+        self.trie
+            .find_prefixes(digits.bytes())
+            .pop()
+            .map(|(ix, value)| (digits[..=ix].to_string(), value))
+    }
+
+    /// Loads rate-sheet style `(prefix, value)` rows, reporting invalid rows, duplicates and
+    /// prefixes shadowed by shorter ones
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::phone::{Overlap, PhonePrefixTable};
+    ///
+    /// let mut table = PhonePrefixTable::new();
+    /// let report = table.load(vec![
+    ///     ("+1", 0.01),
+    ///     ("+1 212", 0.02),
+    ///     ("+1-212", 0.03),
+    ///     ("+1 x", 0.04),
+    /// ]);
+    /// assert_eq!(report.inserted, 3);
+    /// assert_eq!(report.duplicates, vec!["1212".to_string()]);
+    /// assert_eq!(report.invalid.len(), 1);
+    /// assert_eq!(
+    ///     report.overlaps,
+    ///     vec![Overlap { prefix: "1212".to_string(), covered_by: "1".to_string() }]
+    /// );
+    /// assert_eq!(table.route("+1 212 555 0100").map(|(_, v)| *v), Some(0.03));
+    /// ```
+    pub fn load<S: AsRef<str>, I: IntoIterator<Item = (S, V)>>(&mut self, rows: I) -> LoadReport {
+        bulk_span!("ptrie::phone::load");
+        let mut report = LoadReport::default();
+        let mut loaded = Vec::new();
+        for (prefix, value) in rows {
+            let prefix = prefix.as_ref();
+            match normalize(prefix) {
+                Ok(digits) => {
+                    if self.trie.insert(digits.bytes(), value).is_some() {
+                        report.duplicates.push(digits.clone());
+                    }
+                    report.inserted += 1;
+                    loaded.push(digits);
+                }
+                Err(e) => report.invalid.push((prefix.to_string(), e.to_string())),
+            }
+        }
+        loaded.sort();
+        loaded.dedup();
+        for digits in loaded {
+            let covering = self.trie.find_prefixes(digits.bytes());
+            if let Some((ix, _)) = covering.iter().rev().find(|(ix, _)| ix + 1 < digits.len()) {
+                report.overlaps.push(Overlap {
+                    covered_by: digits[..=*ix].to_string(),
+                    prefix: digits,
+                });
+            }
+        }
+        debug_event!(
+            inserted = report.inserted,
+            duplicates = report.duplicates.len(),
+            invalid = report.invalid.len(),
+            overlaps = report.overlaps.len(),
+            "loaded phone prefixes"
+        );
+        report
+    }
+}
+
+impl<V> Default for PhonePrefixTable<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps the digits of a number, rejecting unexpected characters
+fn normalize(number: &str) -> Result<String, TrieError> {
+    let number = number.trim();
+    let number = number.strip_prefix('+').unwrap_or(number);
+    let mut digits = String::with_capacity(number.len());
+    for c in number.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => {
+                return Err(TrieError::InvalidKey(format!(
+                    "Invalid character {:?} in phone number {:?}",
+                    c, number
+                )))
+            }
+        }
+    }
+    if digits.is_empty() {
+        return Err(TrieError::InvalidKey(format!(
+            "Phone number without digits {:?}",
+            number
+        )));
+    }
+    Ok(digits)
+}
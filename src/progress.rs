@@ -0,0 +1,131 @@
+//! Progress reporting for long-running bulk operations
+
+use crate::trie::Trie;
+use std::io::{self, Write};
+
+/// Number of entries between two reports of `Trie::extend_with_progress`
+pub const ENTRIES_INTERVAL: usize = 64 * 1024;
+
+/// Number of bytes between two reports of `ProgressWriter`
+pub const BYTES_INTERVAL: u64 = 1024 * 1024;
+
+/// Amount of work done so far by a bulk operation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Entries processed
+    pub entries: usize,
+    /// Bytes processed, for operations reading or writing bytes
+    pub bytes: u64,
+    /// Entries to process, when known in advance
+    pub total_entries: Option<usize>,
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Inserts every key and value like `extend`, calling `progress` every `ENTRIES_INTERVAL`
+    /// entries and once at the end
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::progress::Progress;
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// let mut reports = Vec::new();
+    /// let words = vec![("a".bytes(), 1), ("b".bytes(), 2)];
+    /// t.extend_with_progress(words, |p: Progress| reports.push(p.entries));
+    /// assert_eq!(reports, vec![2]);
+    /// ```
+    pub fn extend_with_progress<I, T>(&mut self, entries: T, mut progress: impl FnMut(Progress))
+    where
+        I: IntoIterator<Item = K>,
+        T: IntoIterator<Item = (I, V)>,
+    {
+        bulk_span!("ptrie::extend");
+        let entries = entries.into_iter();
+        let total_entries = match entries.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        };
+        let mut report = Progress {
+            total_entries,
+            ..Progress::default()
+        };
+        for (key, value) in entries {
+            self.insert(key.into_iter(), value);
+            report.entries += 1;
+            if report.entries.is_multiple_of(ENTRIES_INTERVAL) {
+                debug_event!(inserted = report.entries, "inserting entries");
+                progress(report);
+            }
+        }
+        debug_event!(inserted = report.entries, "inserted entries");
+        if report.entries == 0 || !report.entries.is_multiple_of(ENTRIES_INTERVAL) {
+            progress(report);
+        }
+    }
+}
+
+/// Writer reporting the number of bytes written through it, to follow serialization
+///
+/// `progress` is called every `BYTES_INTERVAL` bytes and on `flush`.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::progress::ProgressWriter;
+/// use std::io::Write;
+///
+/// let mut written = 0;
+/// let mut out = ProgressWriter::new(Vec::new(), |p| written = p.bytes);
+/// out.write_all(b"{\"root\":{}}").unwrap();
+/// out.flush().unwrap();
+/// let bytes = out.into_inner();
+/// assert_eq!(written, bytes.len() as u64);
+/// ```
+pub struct ProgressWriter<W, F> {
+    inner: W,
+    progress: F,
+    report: Progress,
+    next_report: u64,
+}
+
+impl<W: Write, F: FnMut(Progress)> ProgressWriter<W, F> {
+    /// Wraps `inner`, reporting to `progress`
+    pub fn new(inner: W, progress: F) -> Self {
+        ProgressWriter {
+            inner,
+            progress,
+            report: Progress::default(),
+            next_report: BYTES_INTERVAL,
+        }
+    }
+
+    /// Bytes written so far
+    pub fn bytes(&self) -> u64 {
+        self.report.bytes
+    }
+
+    /// Returns the wrapped writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, F: FnMut(Progress)> Write for ProgressWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.report.bytes += written as u64;
+        if self.report.bytes >= self.next_report {
+            self.next_report = (self.report.bytes / BYTES_INTERVAL + 1) * BYTES_INTERVAL;
+            (self.progress)(self.report);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        (self.progress)(self.report);
+        Ok(())
+    }
+}
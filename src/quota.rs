@@ -0,0 +1,123 @@
+//! Hierarchical usage accounting, with quotas enforced at every level of a key
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+
+/// Usage and optional limit of a prefix
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Account {
+    /// Total charged to the keys starting with this prefix
+    pub usage: u64,
+    pub limit: Option<u64>,
+}
+
+/// Usage counters aggregated on every prefix of the charged keys
+///
+/// Charging a key adds to the usage of each of its prefixes, including the empty one, so
+/// `usage(prefix)` is a single lookup.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::quota::QuotaTrie;
+///
+/// let mut quotas = QuotaTrie::new();
+/// quotas.set_limit("/org1/".bytes(), 100);
+///
+/// quotas.charge("/org1/teamA/api".bytes(), 60);
+/// quotas.charge("/org1/teamB/api".bytes(), 30);
+/// quotas.charge("/org2/api".bytes(), 500);
+///
+/// assert_eq!(quotas.usage("/org1/".bytes()), 90);
+/// assert_eq!(quotas.usage("/org1/teamA".bytes()), 60);
+/// assert_eq!(quotas.usage("".bytes()), 590);
+///
+/// assert!(quotas.try_charge("/org1/teamB/api".bytes(), 20).is_err());
+/// assert!(quotas.try_charge("/org1/teamB/api".bytes(), 10).is_ok());
+/// assert_eq!(quotas.usage("/org1/".bytes()), 100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuotaTrie<K: Eq + Ord + Clone> {
+    accounts: Trie<K, Account>,
+}
+
+impl<K: Eq + Ord + Clone> QuotaTrie<K> {
+    /// Creates an empty accounting trie
+    pub fn new() -> Self {
+        QuotaTrie {
+            accounts: Trie::new(),
+        }
+    }
+
+    /// Adds `amount` to the usage of every prefix of `key`, ignoring limits
+    ///
+    /// Usage saturates at `u64::MAX`, `try_charge` reports it instead.
+    pub fn charge<I: Iterator<Item = K>>(&mut self, key: I, amount: u64) {
+        self.accounts.insert_with(key, |mut node, _| {
+            if !node.has_value() {
+                node.set_value(Account::default());
+            }
+            if let Some(account) = node.value_mut() {
+                account.usage = account.usage.saturating_add(amount);
+            }
+        });
+    }
+
+    /// Charges `amount` only if no prefix of `key` would exceed its limit
+    ///
+    /// Fails with `TrieError::QuotaExceeded` naming the depth of the first exceeded prefix, or
+    /// `TrieError::Overflow` when a usage would exceed `u64::MAX`.
+    pub fn try_charge<I: Iterator<Item = K> + Clone>(
+        &mut self,
+        key: I,
+        amount: u64,
+    ) -> Result<(), TrieError> {
+        let mut node = Some(self.accounts.root());
+        let mut path = key.clone();
+        let mut depth = 0;
+        while let Some(n) = node {
+            if let Some(Account { usage, limit }) = n.value() {
+                let total = usage.checked_add(amount).ok_or_else(|| {
+                    TrieError::Overflow(format!(
+                        "Charging {} overflows the usage {} of the prefix of length {}",
+                        amount, usage, depth
+                    ))
+                })?;
+                if let Some(limit) = limit.filter(|limit| total > *limit) {
+                    return Err(TrieError::QuotaExceeded(format!(
+                        "Charging {} exceeds the limit of {} of the prefix of length {}",
+                        amount, limit, depth
+                    )));
+                }
+            }
+            node = path.next().and_then(|k| n.child(&k));
+            depth += 1;
+        }
+        self.charge(key, amount);
+        Ok(())
+    }
+
+    /// Sets the limit of a prefix, returning the previous one
+    pub fn set_limit<I: Iterator<Item = K>>(&mut self, prefix: I, limit: u64) -> Option<u64> {
+        self.accounts
+            .get_or_insert_with(prefix, Account::default)
+            .limit
+            .replace(limit)
+    }
+
+    /// Total charged to the keys starting with `prefix`
+    pub fn usage<I: Iterator<Item = K>>(&self, prefix: I) -> u64 {
+        self.accounts.get(prefix).map_or(0, |a| a.usage)
+    }
+
+    /// Usage and limit of a prefix
+    pub fn account<I: Iterator<Item = K>>(&self, prefix: I) -> Option<&Account> {
+        self.accounts.get(prefix)
+    }
+}
+
+impl<K: Eq + Ord + Clone> Default for QuotaTrie<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
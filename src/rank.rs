@@ -0,0 +1,89 @@
+//! Order statistics: position of a key among the sorted keys, and key at a position
+
+use crate::trie::Trie;
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// O(n): number of keys strictly before `key` in lexicographic order, whether `key` is
+    /// stored or not
+    ///
+    /// Subtree sizes are not stored, so the keys of the subtrees before the path of `key` are
+    /// counted on each call, up to every key of the `Trie`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for word in ["b", "ba", "bb", "c"] {
+    ///     t.insert(word.bytes(), ());
+    /// }
+    /// assert_eq!(t.rank("a".bytes()), 0);
+    /// assert_eq!(t.rank("ba".bytes()), 1);
+    /// assert_eq!(t.rank("bab".bytes()), 2);
+    /// assert_eq!(t.rank("z".bytes()), 4);
+    /// ```
+    pub fn rank<I: Iterator<Item = K>>(&self, key: I) -> usize {
+        let mut rank = 0;
+        let mut node = &self.root;
+        for k in key {
+            // The key of this node is a strict prefix of `key`, so it comes before
+            rank += usize::from(node.value.is_some());
+            let ix = match node.children.binary_search_by_key(&&k, |(k, _)| k) {
+                Ok(ix) => ix,
+                Err(ix) => ix,
+            };
+            rank += node.children[..ix]
+                .iter()
+                .map(|(_, child)| child.count_values())
+                .sum::<usize>();
+            match node.children.get(ix) {
+                Some((edge, child)) if *edge == k => node = child,
+                _ => return rank,
+            }
+        }
+        rank
+    }
+
+    /// O(n): key and value at position `n` in lexicographic order, `None` past the last key
+    ///
+    /// Subtree sizes are not stored, so the subtrees skipped on the way are counted on each
+    /// call, up to every key of the `Trie`. Pages are better read with `complete_page`, which
+    /// resumes from the last key of the previous page.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for (i, word) in ["b", "ba", "bb", "c"].iter().enumerate() {
+    ///     t.insert(word.bytes(), i);
+    /// }
+    /// assert_eq!(t.select(0), Some((b"b".to_vec(), &0)));
+    /// assert_eq!(t.select(2), Some((b"bb".to_vec(), &2)));
+    /// assert_eq!(t.select(4), None);
+    /// ```
+    pub fn select(&self, mut n: usize) -> Option<(Vec<K>, &V)> {
+        let mut key = Vec::new();
+        let mut node = &self.root;
+        'descend: loop {
+            if let Some(value) = &node.value {
+                if n == 0 {
+                    return Some((key, value));
+                }
+                n -= 1;
+            }
+            for (edge, child) in &node.children {
+                let count = child.count_values();
+                if n < count {
+                    key.push(edge.clone());
+                    node = child;
+                    continue 'descend;
+                }
+                n -= count;
+            }
+            return None;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Prefix rewriting of headers and URLs
+
+use crate::trie::Trie;
+use std::borrow::Cow;
+
+/// Rewrites inputs by replacing their longest matching prefix
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::rewrite::Rewriter;
+///
+/// let mut rewriter = Rewriter::new();
+/// rewriter.insert(b"/api/", b"/backend/v2/");
+/// rewriter.insert(b"/api/v1/", b"/legacy/");
+///
+/// assert_eq!(&*rewriter.rewrite(b"/api/v1/users"), b"/legacy/users");
+/// assert_eq!(&*rewriter.rewrite(b"/api/users"), b"/backend/v2/users");
+/// // Inputs without a matching prefix are not copied
+/// assert!(matches!(rewriter.rewrite(b"/static/app.js"), std::borrow::Cow::Borrowed(_)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rewriter {
+    rules: Trie<u8, Vec<u8>>,
+    max_passes: usize,
+}
+
+impl Rewriter {
+    /// Creates a rewriter without rules, applying a single substitution per input
+    pub fn new() -> Self {
+        Rewriter {
+            rules: Trie::new(),
+            max_passes: 1,
+        }
+    }
+
+    /// Applies substitutions again on the result, up to `max_passes` in total
+    ///
+    /// Rewriting stops early once no prefix matches or the output does not change.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::rewrite::Rewriter;
+    ///
+    /// let mut rewriter = Rewriter::new().repeat(5);
+    /// rewriter.insert(b"http://", b"https://");
+    /// rewriter.insert(b"https://old.", b"https://new.");
+    ///
+    /// assert_eq!(&*rewriter.rewrite(b"http://old.example.org"), b"https://new.example.org");
+    /// ```
+    pub fn repeat(mut self, max_passes: usize) -> Self {
+        self.max_passes = max_passes.max(1);
+        self
+    }
+
+    /// Adds a rule replacing `prefix` by `replacement`, returning the previous replacement
+    pub fn insert(&mut self, prefix: &[u8], replacement: &[u8]) -> Option<Vec<u8>> {
+        self.rules.insert(prefix.iter().copied(), replacement.to_vec())
+    }
+
+    /// Rewrites `input` with the rule of its longest matching prefix
+    pub fn rewrite<'a>(&self, input: &'a [u8]) -> Cow<'a, [u8]> {
+        let mut output = Cow::Borrowed(input);
+        for _ in 0..self.max_passes {
+            let Some((ix, replacement)) = self.rules.find_prefixes(output.iter().copied()).pop()
+            else {
+                break;
+            };
+            let mut rewritten = Vec::with_capacity(replacement.len() + output.len() - ix - 1);
+            rewritten.extend_from_slice(replacement);
+            rewritten.extend_from_slice(&output[ix + 1..]);
+            if rewritten == *output {
+                break;
+            }
+            output = Cow::Owned(rewritten);
+        }
+        output
+    }
+}
+
+impl Default for Rewriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
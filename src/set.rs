@@ -0,0 +1,139 @@
+//! Set of keys sharing their prefixes
+
+use crate::trie::{Trie, TrieIterator};
+
+/// Set of keys backed by a `Trie<K, ()>`
+///
+/// The unit values take no space beyond the presence flag of their node, so the set costs
+/// the same as the bare structure, with call sites reading `insert(key)` and `contains(key)`.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::set::TrieSet;
+///
+/// let mut stopwords: TrieSet<u8> = ["a", "an", "the"].iter().map(|w| w.bytes()).collect();
+/// assert!(stopwords.contains("an".bytes()));
+/// assert!(!stopwords.insert("the".bytes()));
+/// assert_eq!(stopwords.longest_prefix_len("another".bytes()), Some(2));
+///
+/// let words: Vec<Vec<u8>> = stopwords.iter_prefix("a".bytes()).collect();
+/// assert_eq!(words, vec![b"a".to_vec(), b"an".to_vec()]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrieSet<K: Eq + Ord + Clone> {
+    trie: Trie<K, ()>,
+}
+
+impl<K: Eq + Ord + Clone> TrieSet<K> {
+    /// Creates an empty set
+    pub fn new() -> Self {
+        TrieSet { trie: Trie::new() }
+    }
+
+    /// The underlying `Trie`, for the queries not wrapped here
+    pub fn trie(&self) -> &Trie<K, ()> {
+        &self.trie
+    }
+
+    /// Adds a key, returning whether it was new
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I) -> bool {
+        self.trie.insert(key, ()).is_none()
+    }
+
+    /// Whether the set holds the key
+    pub fn contains<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.trie.contains_key(key)
+    }
+
+    /// Removes a key, returning whether it was present
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> bool {
+        self.trie.remove(key).is_some()
+    }
+
+    /// Whether the set has no keys
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// Removes every key
+    pub fn clear(&mut self) {
+        self.trie.clear()
+    }
+
+    /// Lengths of the non-empty keys of the set `key` starts with, shortest first
+    pub fn prefix_lens<I: Iterator<Item = K>>(&self, key: I) -> Vec<usize> {
+        self.trie
+            .find_prefixes(key)
+            .into_iter()
+            .map(|(ix, _)| ix + 1)
+            .collect()
+    }
+
+    /// Length of the longest non-empty key of the set `key` starts with
+    pub fn longest_prefix_len<I: Iterator<Item = K>>(&self, key: I) -> Option<usize> {
+        self.prefix_lens(key).pop()
+    }
+
+    /// Iterate the keys, in ascending order
+    pub fn iter(&self) -> Keys<'_, K> {
+        Keys(self.trie.iter())
+    }
+
+    /// Iterate the keys starting with `prefix`, in ascending order
+    pub fn iter_prefix<I: Iterator<Item = K>>(&self, prefix: I) -> Keys<'_, K> {
+        Keys(self.trie.iter_prefix(prefix))
+    }
+
+    /// Keys in either set
+    pub fn union(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        TrieSet {
+            trie: self.trie.union_with(&other.trie, |_, _| ()),
+        }
+    }
+
+    /// Keys in both sets
+    pub fn intersection(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        TrieSet {
+            trie: self.trie.intersect_with(&other.trie, |_, _| ()),
+        }
+    }
+
+    /// Keys in this set and not in `other`
+    pub fn difference(&self, other: &TrieSet<K>) -> TrieSet<K> {
+        TrieSet {
+            trie: self.trie.difference(&other.trie),
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone> Default for TrieSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Ord + Clone, I: IntoIterator<Item = K>> Extend<I> for TrieSet<K> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, keys: T) {
+        self.trie.extend(keys.into_iter().map(|key| (key, ())));
+    }
+}
+
+impl<K: Eq + Ord + Clone, I: IntoIterator<Item = K>> FromIterator<I> for TrieSet<K> {
+    fn from_iter<T: IntoIterator<Item = I>>(keys: T) -> Self {
+        let mut set = TrieSet::new();
+        set.extend(keys);
+        set
+    }
+}
+
+/// Iterator over the keys of a `TrieSet`
+pub struct Keys<'a, K: Eq + Ord + Clone>(TrieIterator<'a, K, ()>);
+
+impl<K: Eq + Ord + Clone> Iterator for Keys<'_, K> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+}
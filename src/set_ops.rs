@@ -0,0 +1,204 @@
+//! Set algebra between two tries, walking both structures together
+//!
+//! Subtrees present on one side only are skipped or cloned whole, without comparing their keys
+//! one by one. The results are new tries without insertion order tracking or depth limit.
+
+use crate::trie::Trie;
+use crate::trie_node::TrieNode;
+
+/// Child edge found in one or both of the tries walked together
+enum Pair<'a, K: Eq + Ord + Clone, V, W> {
+    Left(&'a K, &'a TrieNode<K, V>),
+    Right(&'a K, &'a TrieNode<K, W>),
+    Both(&'a K, &'a TrieNode<K, V>, &'a TrieNode<K, W>),
+}
+
+/// Pairs the children of two nodes by edge, merging their sorted lists
+fn pair_children<'a, K: Eq + Ord + Clone, V, W>(
+    left: &'a TrieNode<K, V>,
+    right: &'a TrieNode<K, W>,
+) -> Vec<Pair<'a, K, V, W>> {
+    let mut pairs = Vec::with_capacity(left.children.len().max(right.children.len()));
+    let mut left = left.children.iter().peekable();
+    let mut right = right.children.iter().peekable();
+    loop {
+        let pair = match (left.peek(), right.peek()) {
+            (Some((l, _)), Some((r, _))) if l < r => left.next().map(|(l, a)| Pair::Left(l, a)),
+            (Some((l, _)), Some((r, _))) if l > r => right.next().map(|(r, b)| Pair::Right(r, b)),
+            (Some(_), Some(_)) => match (left.next(), right.next()) {
+                (Some((k, a)), Some((_, b))) => Some(Pair::Both(k, a, b)),
+                _ => None,
+            },
+            (Some(_), None) => left.next().map(|(l, a)| Pair::Left(l, a)),
+            (None, Some(_)) => right.next().map(|(r, b)| Pair::Right(r, b)),
+            (None, None) => None,
+        };
+        match pair {
+            Some(pair) => pairs.push(pair),
+            None => return pairs,
+        }
+    }
+}
+
+fn child_path<K: Clone>(path: &[K], edge: &K) -> Vec<K> {
+    let mut child = path.to_vec();
+    child.push(edge.clone());
+    child
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// New `Trie` with the keys of both tries, `merge` combining the values of the keys in both
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let monday: Trie<u8, u32> = [("/a".bytes(), 1), ("/b".bytes(), 2)].into_iter().collect();
+    /// let tuesday: Trie<u8, u32> = [("/b".bytes(), 3), ("/c".bytes(), 4)].into_iter().collect();
+    ///
+    /// let hits = monday.union_with(&tuesday, |a, b| a + b);
+    /// assert_eq!(hits.get("/a".bytes()), Some(&1));
+    /// assert_eq!(hits.get("/b".bytes()), Some(&5));
+    /// assert_eq!(hits.get("/c".bytes()), Some(&4));
+    /// ```
+    pub fn union_with(&self, other: &Trie<K, V>, mut merge: impl FnMut(&V, &V) -> V) -> Trie<K, V>
+    where
+        V: Clone,
+    {
+        bulk_span!("ptrie::union_with");
+        let mut result = Trie::new();
+        let mut stack = vec![(Vec::new(), &self.root, &other.root)];
+        while let Some((path, left, right)) = stack.pop() {
+            let value = match (&left.value, &right.value) {
+                (Some(a), Some(b)) => Some(merge(a, b)),
+                (Some(v), None) | (None, Some(v)) => Some(v.clone()),
+                (None, None) => None,
+            };
+            if value.is_some() {
+                result.root.find_or_create_node(path.iter().cloned()).value = value;
+            }
+            for pair in pair_children(left, right) {
+                match pair {
+                    Pair::Left(edge, node) | Pair::Right(edge, node) => {
+                        let child = child_path(&path, edge);
+                        *result.root.find_or_create_node(child.into_iter()) = node.clone();
+                    }
+                    Pair::Both(edge, a, b) => stack.push((child_path(&path, edge), a, b)),
+                }
+            }
+        }
+        result
+    }
+
+    /// New `Trie` with the keys in both tries, valued by `merge` of their two values
+    ///
+    /// Subtrees of one `Trie` whose edge is missing from the other are not visited.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let crawled: Trie<u8, u32> = [("/a".bytes(), 200), ("/b".bytes(), 404)].into_iter().collect();
+    /// let linked: Trie<u8, &str> = [("/b".bytes(), "home"), ("/c".bytes(), "faq")].into_iter().collect();
+    ///
+    /// let broken = crawled.intersect_with(&linked, |status, page| (*status, *page));
+    /// assert_eq!(broken.iter().collect::<Vec<_>>(), vec![(b"/b".to_vec(), &(404, "home"))]);
+    /// ```
+    pub fn intersect_with<W, U>(
+        &self,
+        other: &Trie<K, W>,
+        mut merge: impl FnMut(&V, &W) -> U,
+    ) -> Trie<K, U> {
+        bulk_span!("ptrie::intersect_with");
+        let mut result = Trie::new();
+        let mut stack = vec![(Vec::new(), &self.root, &other.root)];
+        while let Some((path, left, right)) = stack.pop() {
+            if let (Some(a), Some(b)) = (&left.value, &right.value) {
+                result.root.find_or_create_node(path.iter().cloned()).value = Some(merge(a, b));
+            }
+            for pair in pair_children(left, right) {
+                if let Pair::Both(edge, a, b) = pair {
+                    stack.push((child_path(&path, edge), a, b));
+                }
+            }
+        }
+        result
+    }
+
+    /// New `Trie` with the entries whose key is not in `other`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let today: Trie<u8, ()> = [("/a".bytes(), ()), ("/b".bytes(), ())].into_iter().collect();
+    /// let yesterday: Trie<u8, ()> = [("/b".bytes(), ()), ("/c".bytes(), ())].into_iter().collect();
+    ///
+    /// let added = today.difference(&yesterday);
+    /// assert_eq!(added.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![b"/a".to_vec()]);
+    /// ```
+    pub fn difference<W>(&self, other: &Trie<K, W>) -> Trie<K, V>
+    where
+        V: Clone,
+    {
+        bulk_span!("ptrie::difference");
+        let mut result = Trie::new();
+        let mut stack = vec![(Vec::new(), &self.root, &other.root)];
+        while let Some((path, left, right)) = stack.pop() {
+            if let (Some(value), None) = (&left.value, &right.value) {
+                result.root.find_or_create_node(path.iter().cloned()).value = Some(value.clone());
+            }
+            for pair in pair_children(left, right) {
+                match pair {
+                    Pair::Left(edge, node) => {
+                        let child = child_path(&path, edge);
+                        *result.root.find_or_create_node(child.into_iter()) = node.clone();
+                    }
+                    Pair::Right(..) => {}
+                    Pair::Both(edge, a, b) => stack.push((child_path(&path, edge), a, b)),
+                }
+            }
+        }
+        result
+    }
+
+    /// Whether both tries have exactly the same keys, whatever their values
+    ///
+    /// Nodes left without value by `remove_no_prune` do not count as keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let words: Trie<u8, String> = [("car".bytes(), "car".to_string())].into_iter().collect();
+    /// let mut ids: Trie<u8, u32> = [("car".bytes(), 1)].into_iter().collect();
+    /// assert!(words.same_keys(&ids));
+    ///
+    /// ids.insert("cart".bytes(), 2);
+    /// assert!(!words.same_keys(&ids));
+    /// ids.remove_no_prune("cart".bytes());
+    /// assert!(words.same_keys(&ids));
+    /// ```
+    pub fn same_keys<W>(&self, other: &Trie<K, W>) -> bool {
+        bulk_span!("ptrie::same_keys");
+        let mut stack = vec![(&self.root, &other.root)];
+        while let Some((left, right)) = stack.pop() {
+            if left.value.is_some() != right.value.is_some() {
+                return false;
+            }
+            for pair in pair_children(left, right) {
+                match pair {
+                    Pair::Left(_, node) if node.count_values() > 0 => return false,
+                    Pair::Right(_, node) if node.count_values() > 0 => return false,
+                    Pair::Both(_, a, b) => stack.push((a, b)),
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+}
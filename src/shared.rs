@@ -0,0 +1,114 @@
+//! Values shared between keys, for tries mapping many keys to a few distinct values
+
+use crate::trie::Trie;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::sync::Arc;
+
+impl<K: Eq + Ord + Clone, V> Trie<K, Arc<V>> {
+    /// Inserts a shared value, returning the previous one
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    /// use std::sync::Arc;
+    ///
+    /// let label = Arc::new(String::from("fruit"));
+    /// let mut t: Trie<u8, Arc<String>> = Trie::new();
+    /// t.insert_shared("apple".bytes(), label.clone());
+    /// t.insert_shared("pear".bytes(), label.clone());
+    /// assert_eq!(Arc::strong_count(&label), 3);
+    /// ```
+    pub fn insert_shared<I: Iterator<Item = K>>(
+        &mut self,
+        key: I,
+        value: impl Into<Arc<V>>,
+    ) -> Option<Arc<V>> {
+        self.insert(key, value.into())
+    }
+
+    /// New handle to the value of a key, which outlives borrows of the `Trie`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    /// use std::sync::Arc;
+    ///
+    /// let mut t: Trie<u8, Arc<String>> = Trie::new();
+    /// t.insert_shared("apple".bytes(), String::from("fruit"));
+    /// let label = t.get_shared("apple".bytes()).unwrap();
+    /// t.clear();
+    /// assert_eq!(*label, "fruit");
+    /// ```
+    pub fn get_shared<I: Iterator<Item = K>>(&self, key: I) -> Option<Arc<V>> {
+        self.get(key).cloned()
+    }
+}
+
+/// Pool of distinct values, handing out one shared copy of each
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::shared::ValuePool;
+/// use ptrie::Trie;
+/// use std::sync::Arc;
+///
+/// let mut pool = ValuePool::new();
+/// let mut categories: Trie<u8, Arc<String>> = Trie::new();
+/// for (product, category) in [("apple", "fruit"), ("pear", "fruit"), ("leek", "vegetable")] {
+///     categories.insert_shared(product.bytes(), pool.intern(category.to_string()));
+/// }
+/// assert_eq!(pool.len(), 2);
+/// let apple = categories.get_shared("apple".bytes()).unwrap();
+/// let pear = categories.get_shared("pear".bytes()).unwrap();
+/// assert!(Arc::ptr_eq(&apple, &pear));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValuePool<V: Eq + Hash> {
+    values: HashSet<Arc<V>>,
+}
+
+impl<V: Eq + Hash> ValuePool<V> {
+    /// Creates an empty pool
+    pub fn new() -> Self {
+        ValuePool {
+            values: HashSet::new(),
+        }
+    }
+
+    /// Shared copy of `value`, added to the pool when it has no equal value yet
+    pub fn intern(&mut self, value: V) -> Arc<V> {
+        if let Some(shared) = self.values.get(&value) {
+            return shared.clone();
+        }
+        let shared = Arc::new(value);
+        self.values.insert(shared.clone());
+        shared
+    }
+
+    /// Number of distinct values
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the pool has no values
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Drops the values held by the pool alone, returning how many were dropped
+    pub fn purge(&mut self) -> usize {
+        let before = self.values.len();
+        self.values.retain(|value| Arc::strong_count(value) > 1);
+        before - self.values.len()
+    }
+}
+
+impl<V: Eq + Hash> Default for ValuePool<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,178 @@
+//! Trie handing out stable integer handles to its values
+
+use crate::trie::Trie;
+use std::mem;
+
+/// Handle to a value of a `SlabTrie`, valid until the value is removed
+///
+/// A slot freed by a removal is reused by later insertions with a new generation, so a stale
+/// handle never reaches the value of another key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValueId {
+    index: usize,
+    generation: u32,
+}
+
+impl ValueId {
+    /// Position of the value in the slab, dense enough to index side tables
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<K, V> {
+    generation: u32,
+    entry: Option<(Vec<K>, V)>,
+}
+
+/// Trie storing its values in a slab, addressed by `ValueId` in constant time
+///
+/// Lookups by key walk the `Trie` like a `Trie`, lookups by id index the slab. Keys keep their
+/// id when their value is replaced.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::slab::SlabTrie;
+///
+/// let mut graph = SlabTrie::new();
+/// let (a, _) = graph.insert("node/a".bytes(), "A");
+/// let (b, _) = graph.insert("node/b".bytes(), "B");
+///
+/// assert_eq!(graph.id_of("node/a".bytes()), Some(a));
+/// assert_eq!(graph.get_by_id(b), Some(&"B"));
+/// assert_eq!(graph.key_of(b), Some(&b"node/b"[..]));
+///
+/// assert_eq!(graph.remove_by_id(a), Some("A"));
+/// assert_eq!(graph.get_by_id(a), None);
+/// let (c, _) = graph.insert("node/c".bytes(), "C");
+/// assert_eq!(c.index(), a.index());
+/// assert_eq!(graph.get_by_id(a), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlabTrie<K: Eq + Ord + Clone, V> {
+    ids: Trie<K, ValueId>,
+    slots: Vec<Slot<K, V>>,
+    free: Vec<usize>,
+}
+
+impl<K: Eq + Ord + Clone, V> SlabTrie<K, V> {
+    /// Creates an empty trie
+    pub fn new() -> Self {
+        SlabTrie {
+            ids: Trie::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// Whether the trie has no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The ids by key, for prefix queries
+    pub fn ids(&self) -> &Trie<K, ValueId> {
+        &self.ids
+    }
+
+    /// Inserts a value, returning its id and the value it replaced
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> (ValueId, Option<V>) {
+        let key: Vec<K> = key.collect();
+        if let Some(id) = self.ids.get(key.iter().cloned()) {
+            let id = *id;
+            let previous = self.slots[id.index]
+                .entry
+                .as_mut()
+                .map(|(_, old)| mem::replace(old, value));
+            return (id, previous);
+        }
+        let id = match self.free.pop() {
+            Some(index) => ValueId {
+                index,
+                generation: self.slots[index].generation,
+            },
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry: None,
+                });
+                ValueId {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.ids.insert(key.iter().cloned(), id);
+        self.slots[id.index].entry = Some((key, value));
+        (id, None)
+    }
+
+    /// Id of the value of a key
+    pub fn id_of<I: Iterator<Item = K>>(&self, key: I) -> Option<ValueId> {
+        self.ids.get(key).copied()
+    }
+
+    /// Value of a key
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        self.id_of(key).and_then(|id| self.get_by_id(id))
+    }
+
+    /// Value of an id, `None` once removed
+    pub fn get_by_id(&self, id: ValueId) -> Option<&V> {
+        self.entry(id).map(|(_, value)| value)
+    }
+
+    /// Mutable value of an id, `None` once removed
+    pub fn get_by_id_mut(&mut self, id: ValueId) -> Option<&mut V> {
+        match self.slots.get_mut(id.index) {
+            Some(slot) if slot.generation == id.generation => {
+                slot.entry.as_mut().map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Key of an id, `None` once removed
+    pub fn key_of(&self, id: ValueId) -> Option<&[K]> {
+        self.entry(id).map(|(key, _)| key.as_slice())
+    }
+
+    /// Removes the value of an id, freeing its slot
+    pub fn remove_by_id(&mut self, id: ValueId) -> Option<V> {
+        let slot = self.slots.get_mut(id.index)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let (key, value) = slot.entry.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.ids.remove(key.into_iter());
+        Some(value)
+    }
+
+    /// Removes a key, returning its value
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        let id = self.id_of(key)?;
+        self.remove_by_id(id)
+    }
+
+    fn entry(&self, id: ValueId) -> Option<&(Vec<K>, V)> {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.entry.as_ref())
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Default for SlabTrie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,86 @@
+//! Spelling suggestions ranked by edit distance and word frequency
+
+use crate::trie::Trie;
+
+/// Candidate correction returned by `SpellChecker::suggest`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub word: String,
+    /// Levenshtein distance to the queried word, counted in `char`s
+    pub distance: usize,
+    pub frequency: u64,
+}
+
+/// Dictionary of words with their frequency, suggesting corrections for misspelled words
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::spelling::SpellChecker;
+///
+/// let mut checker = SpellChecker::new(2);
+/// checker.insert("their", 500);
+/// checker.insert("there", 800);
+/// checker.insert("the", 5000);
+/// checker.insert("thermal", 20);
+///
+/// let words: Vec<String> = checker.suggest("ther", 3).into_iter().map(|s| s.word).collect();
+/// assert_eq!(words, vec!["the", "there", "their"]);
+/// assert!(checker.suggest("xylophone", 3).is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpellChecker {
+    words: Trie<char, u64>,
+    max_distance: usize,
+}
+
+impl SpellChecker {
+    /// Creates an empty dictionary suggesting words at most `max_distance` edits away
+    pub fn new(max_distance: usize) -> Self {
+        SpellChecker {
+            words: Trie::new(),
+            max_distance,
+        }
+    }
+
+    /// Adds a word with its frequency, returning the previous frequency
+    pub fn insert(&mut self, word: &str, frequency: u64) -> Option<u64> {
+        self.words.insert(word.chars(), frequency)
+    }
+
+    /// Adds `count` occurrences to the frequency of a word, saturating at `u64::MAX`
+    pub fn add_occurrences(&mut self, word: &str, count: u64) {
+        match self.words.get_mut(word.chars()) {
+            Some(frequency) => *frequency = frequency.saturating_add(count),
+            None => {
+                self.words.insert(word.chars(), count);
+            }
+        }
+    }
+
+    /// Checks if a word is in the dictionary
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains_key(word.chars())
+    }
+
+    /// Returns up to `limit` corrections, ordered by edit distance then decreasing frequency
+    ///
+    /// A word of the dictionary is returned first with a distance of 0.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<Suggestion> {
+        let mut suggestions: Vec<Suggestion> = self
+            .words
+            .find_fuzzy(word.chars(), self.max_distance)
+            .into_iter()
+            .map(|(chars, distance, frequency)| Suggestion {
+                word: chars.into_iter().collect(),
+                distance,
+                frequency: *frequency,
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            (a.distance, b.frequency, &a.word).cmp(&(b.distance, a.frequency, &b.word))
+        });
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
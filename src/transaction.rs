@@ -0,0 +1,112 @@
+//! Multi-key updates staged in a delta and applied all at once
+
+use crate::trie::Trie;
+use std::collections::BTreeMap;
+
+/// Mutations staged by `Trie::transaction`, read through by its lookups
+///
+/// The `Trie` is only borrowed for reading until the transaction succeeds, so a failed
+/// transaction has nothing to revert.
+#[derive(Debug)]
+pub struct Transaction<'a, K: Eq + Ord + Clone, V> {
+    trie: &'a Trie<K, V>,
+    /// Value staged for each touched key, `None` for a removal
+    delta: BTreeMap<Vec<K>, Option<V>>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> Transaction<'a, K, V> {
+    /// Stages the insertion of a value
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) {
+        self.delta.insert(key.collect(), Some(value));
+    }
+
+    /// Stages the removal of a key, returning whether it had a value in the transaction
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> bool {
+        let key: Vec<K> = key.collect();
+        let existed = self.get(key.iter().cloned()).is_some();
+        self.delta.insert(key, None);
+        existed
+    }
+
+    /// Value of a key with the staged mutations applied
+    pub fn get<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        let key: Vec<K> = key.collect();
+        match self.delta.get(&key) {
+            Some(staged) => staged.as_ref(),
+            None => self.trie.get(key.into_iter()),
+        }
+    }
+
+    /// Whether a key has a value with the staged mutations applied
+    pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Number of keys touched so far
+    pub fn len(&self) -> usize {
+        self.delta.len()
+    }
+
+    /// Whether no mutation was staged
+    pub fn is_empty(&self) -> bool {
+        self.delta.is_empty()
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Trie<K, V> {
+    /// Runs `f` on a transaction staging mutations, applied all together when it returns `Ok`
+    /// and dropped when it returns an error
+    ///
+    /// A key touched several times keeps its last staged mutation. The mutations are applied
+    /// in key order, which is also the order of the new keys in the insertion order log.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut routes = Trie::new();
+    /// routes.insert("10.0.".bytes(), "eth0");
+    ///
+    /// let moved: Result<(), String> = routes.transaction(|txn| {
+    ///     txn.insert("10.1.".bytes(), "eth1");
+    ///     if !txn.remove("10.0.".bytes()) {
+    ///         return Err("no route to move".to_string());
+    ///     }
+    ///     txn.insert("10.0.".bytes(), "eth1");
+    ///     Ok(())
+    /// });
+    /// assert!(moved.is_ok());
+    /// assert_eq!(routes.get("10.0.".bytes()), Some(&"eth1"));
+    ///
+    /// let failed: Result<(), String> = routes.transaction(|txn| {
+    ///     txn.remove("10.1.".bytes());
+    ///     Err("aborted".to_string())
+    /// });
+    /// assert!(failed.is_err());
+    /// assert_eq!(routes.get("10.1.".bytes()), Some(&"eth1"));
+    /// ```
+    pub fn transaction<R, E, F>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Transaction<'_, K, V>) -> Result<R, E>,
+    {
+        let mut txn = Transaction {
+            trie: self,
+            delta: BTreeMap::new(),
+        };
+        let result = f(&mut txn)?;
+        let delta = txn.delta;
+        bulk_span!("ptrie::transaction");
+        for (key, staged) in delta {
+            match staged {
+                Some(value) => {
+                    self.insert(key.into_iter(), value);
+                }
+                None => {
+                    self.remove(key.into_iter());
+                }
+            }
+        }
+        Ok(result)
+    }
+}
@@ -0,0 +1,42 @@
+//! Transducer mode: tries whose values are the output sequences of their keys
+
+use crate::trie::Trie;
+
+impl<K: Eq + Ord + Clone, V: AsRef<[K]>> Trie<K, V> {
+    /// Rewrites `input` from left to right, replacing each longest key found by its value and
+    /// copying the elements starting no key
+    ///
+    /// Replaced spans never overlap, and outputs are not matched again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut translit: Trie<char, Vec<char>> = Trie::new();
+    /// translit.insert("sh".chars(), "ш".chars().collect());
+    /// translit.insert("s".chars(), "с".chars().collect());
+    /// translit.insert("a".chars(), "а".chars().collect());
+    ///
+    /// let input: Vec<char> = "shas!".chars().collect();
+    /// let output: String = translit.transduce(&input).into_iter().collect();
+    /// assert_eq!(output, "шас!");
+    /// ```
+    pub fn transduce(&self, input: &[K]) -> Vec<K> {
+        let mut output = Vec::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(first) = rest.first() {
+            match self.find_prefixes(rest.iter().cloned()).pop() {
+                Some((ix, replacement)) => {
+                    output.extend_from_slice(replacement.as_ref());
+                    rest = &rest[ix + 1..];
+                }
+                None => {
+                    output.push(first.clone());
+                    rest = &rest[1..];
+                }
+            }
+        }
+        output
+    }
+}
@@ -1,19 +1,48 @@
 //! Struct and functions for the `Trie` data structure
 
-use crate::error::TrieError;
-use crate::trie_node::TrieNode;
+use crate::builder::{BudgetPolicy, MemoryBudget, TrieBuilder};
+use crate::error::{InvariantError, OccupiedError, TrieError};
+use crate::trie_node::{NodeHandle, NodeMut, NodeRef, TrieNode};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::clone::Clone;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
-/// Prefix tree object, contains 1 field for the `root` node of the tree
+/// Prefix tree object, contains the `root` node of the tree and the optional bookkeeping
+/// enabled through the [`TrieBuilder`]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
 pub struct Trie<K: Eq + Ord + Clone, V> {
     /// Root of the prefix tree
-    root: TrieNode<K, V>,
+    pub(crate) root: TrieNode<K, V>,
+    /// Keys in the order they were first inserted, when insertion order tracking is enabled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) insertion_order: Option<Vec<Vec<K>>>,
+    /// Maximum key length accepted by `insert_checked`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) max_depth: Option<usize>,
+    /// Bound on the memory of the nodes created by `insert_checked`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) memory_budget: Option<MemoryBudget>,
+    /// Sort key of the children in `iter_presented`, functions are not serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) child_order: Option<ChildOrder<K, V>>,
 }
 
+/// Sort key of a child from its element and value, configured with
+/// [`TrieBuilder::child_order`](crate::builder::TrieBuilder::child_order)
+pub type ChildOrder<K, V> = fn(&K, Option<&V>) -> i64;
+
 impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// Creates a new `Trie` object
     ///
@@ -27,11 +56,100 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     pub fn new() -> Self {
         Trie {
             root: TrieNode::default(),
+            insertion_order: None,
+            max_depth: None,
+            memory_budget: None,
+            child_order: None,
+        }
+    }
+
+    /// Creates a `TrieBuilder` to configure the optional behaviors of a `Trie`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let t = Trie::<u8, String>::builder().insertion_order(true).build();
+    /// assert!(t.is_empty());
+    /// ```
+    pub fn builder() -> TrieBuilder<K, V> {
+        TrieBuilder::new()
+    }
+
+    /// Builds a `Trie` from shards of entries each sorted by key, merging them in one pass
+    ///
+    /// Fails with `TrieError::InvalidKey` when a shard is not strictly sorted. A key found in
+    /// several shards is handled with `policy`, the shards taken in order: the last value wins
+    /// with `DuplicatePolicy::Overwrite`, the first one with `DuplicatePolicy::KeepFirst`, and
+    /// `DuplicatePolicy::Error` fails with `TrieError::InvalidKey`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::trie::DuplicatePolicy;
+    /// use ptrie::Trie;
+    ///
+    /// let shard = |keys: &[&str], value| {
+    ///     keys.iter().map(move |k| (k.as_bytes().to_vec(), value)).collect::<Vec<_>>().into_iter()
+    /// };
+    /// let t = Trie::from_sorted_shards(
+    ///     vec![shard(&["apple", "fig"], 1), shard(&["banana", "fig"], 2)],
+    ///     DuplicatePolicy::KeepFirst,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(t.get("fig".bytes()), Some(&1));
+    /// assert_eq!(t.iter().count(), 3);
+    ///
+    /// let unsorted = Trie::from_sorted_shards(vec![shard(&["fig", "apple"], 1)], DuplicatePolicy::Overwrite);
+    /// assert!(unsorted.is_err());
+    /// ```
+    pub fn from_sorted_shards<S>(
+        mut shards: Vec<S>,
+        policy: DuplicatePolicy,
+    ) -> Result<Self, TrieError>
+    where
+        S: Iterator<Item = (Vec<K>, V)>,
+    {
+        bulk_span!("ptrie::from_sorted_shards");
+        let mut trie = Trie::new();
+        // Equal keys pop in shard order
+        let mut heads = BinaryHeap::new();
+        let mut values: Vec<Option<V>> = Vec::with_capacity(shards.len());
+        for (i, shard) in shards.iter_mut().enumerate() {
+            values.push(shard.next().map(|(key, value)| {
+                heads.push(Reverse((key, i)));
+                value
+            }));
+        }
+        while let Some(Reverse((key, i))) = heads.pop() {
+            let value = values[i].take().expect("every head has a value");
+            if let Some((next, next_value)) = shards[i].next() {
+                if next <= key {
+                    return Err(TrieError::InvalidKey(format!(
+                        "Shard {} is not strictly sorted",
+                        i
+                    )));
+                }
+                heads.push(Reverse((next, i)));
+                values[i] = Some(next_value);
+            }
+            // Keys come in order, one already present comes from an earlier shard
+            if trie.insert_with_policy(key.into_iter(), value, policy).is_err() {
+                return Err(TrieError::InvalidKey(format!(
+                    "Key of shard {} already found in an earlier shard",
+                    i
+                )));
+            }
         }
+        debug_event!(shards = shards.len(), "merged shards");
+        Ok(trie)
     }
 
     /// Looks for the key in trie
     ///
+    /// The empty key is stored at the root, like any other key.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -46,12 +164,12 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// assert!(!t.is_empty());
     /// assert!(t.contains_key(data));
     /// assert!(!t.contains_key(another_data));
+    ///
+    /// assert!(!t.contains_key("".bytes()));
+    /// t.insert("".bytes(), 0);
+    /// assert!(t.contains_key("".bytes()));
     /// ```
     pub fn contains_key<I: Iterator<Item = K>>(&self, key: I) -> bool {
-        if self.is_empty() {
-            return false;
-        }
-        // self.root.find_node(key).is_some()
         match self.find_node(key) {
             Some(node) => node.may_be_leaf(),
             None => false,
@@ -78,9 +196,101 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
         self.find_node(key).and_then(|node| node.get_value())
     }
 
+    /// Gets the value from the tree by key, or `default` when the key is not in the tree
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("timeout".bytes(), 30);
+    ///
+    /// assert_eq!(t.get_or("timeout".bytes(), &10), &30);
+    /// assert_eq!(t.get_or("retries".bytes(), &10), &10);
+    /// ```
+    pub fn get_or<'a, I: Iterator<Item = K>>(&'a self, key: I, default: &'a V) -> &'a V {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Gets a clone of the value from the tree by key
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("name".bytes(), String::from("ptrie"));
+    ///
+    /// assert_eq!(t.get_cloned("name".bytes()), Some(String::from("ptrie")));
+    /// assert_eq!(t.get_cloned("nam".bytes()), None);
+    /// ```
+    pub fn get_cloned<I: Iterator<Item = K>>(&self, key: I) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.get(key).cloned()
+    }
+
+    /// Gets a mutable reference to the value from the tree by key
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("test".bytes(), 42);
+    /// t.insert("testing".bytes(), 43);
+    ///
+    /// *t.get_mut("test".bytes()).unwrap() += 1;
+    /// assert_eq!(t.get("test".bytes()), Some(&43));
+    /// assert_eq!(t.get_mut("testi".bytes()), None);
+    /// ```
     pub fn get_mut<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&mut V> {
-        self.find_node_mut(key)
-            .and_then(|node| Some(node.value.as_mut().unwrap()))
+        self.find_node_mut(key).and_then(|node| node.value.as_mut())
+    }
+
+    /// Gets mutable references to the values of several keys at once
+    ///
+    /// # Panics
+    ///
+    /// Panics if two of the keys are equal, their values being the same.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut balances = Trie::new();
+    /// balances.insert("alice".bytes(), 50);
+    /// balances.insert("bob".bytes(), 10);
+    ///
+    /// if let [Some(from), Some(to), None] = balances.get_disjoint_mut(["alice", "bob", "carol"].map(str::bytes)) {
+    ///     *from -= 20;
+    ///     *to += 20;
+    /// }
+    /// assert_eq!(balances.get("alice".bytes()), Some(&30));
+    /// assert_eq!(balances.get("bob".bytes()), Some(&30));
+    /// ```
+    pub fn get_disjoint_mut<I: Iterator<Item = K>, const N: usize>(
+        &mut self,
+        keys: [I; N],
+    ) -> [Option<&mut V>; N] {
+        let keys: [Vec<K>; N] = keys.map(Iterator::collect);
+        let mut sorted: Vec<(&[K], usize)> = keys
+            .iter()
+            .enumerate()
+            .map(|(slot, key)| (key.as_slice(), slot))
+            .collect();
+        sorted.sort_unstable();
+        if sorted.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            panic!("get_disjoint_mut called with overlapping keys");
+        }
+        let mut values = std::array::from_fn(|_| None);
+        self.root.values_mut(&mut sorted, &mut values);
+        values
     }
 
     /// Sets the value pointed by a key
@@ -99,18 +309,36 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// assert_eq!(t.get(data.clone()), Some(42).as_ref());
     /// assert!(t.set_value(data.clone(), 43).is_ok());
     /// assert_eq!(t.get(data), Some(43).as_ref());
-    /// assert!(t.set_value(another_data, 39)
-    ///     .map_err(|e| assert!(e.to_string().starts_with("Key not found")))
-    ///     .is_err());
+    /// let err = t.set_value(another_data, 39).unwrap_err();
+    /// assert!(err.to_string().starts_with("Key not found"));
+    /// assert_eq!(err.to_string(), "Key not found: 0 of its 9 elements matched");
     /// ```
     pub fn set_value<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Result<(), TrieError> {
-        self.find_node_mut(key)
-            .ok_or_else(|| TrieError::NotFound("Key not found".to_string()))
-            .map(|node| node.set_value(value))
+        let Some(order) = self.insertion_order.as_mut() else {
+            return self
+                .root
+                .find_node_mut_or_matched(key)
+                .map_err(not_found)
+                .map(|node| node.set_value(value));
+        };
+        let key: Vec<K> = key.collect();
+        let node = self
+            .root
+            .find_node_mut_or_matched(key.iter().cloned())
+            .map_err(not_found)?;
+        if node.value.is_none() {
+            order.push(key);
+        }
+        node.set_value(value);
+        Ok(())
     }
 
     /// Returns a list of all prefixes in the trie for a given string, ordered from smaller to longer.
     ///
+    /// Each prefix is returned with the index of its last element in the key. Like every prefix
+    /// query, only non-empty prefixes are considered: the value of the empty key is never
+    /// returned, and an empty key has no prefixes.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -122,20 +350,23 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// trie.insert("abcde".bytes(), "ABCDE");
     ///
     /// let prefixes = trie.find_prefixes("abcd".bytes());
-    /// assert_eq!(prefixes, vec![&"ABC", &"ABCD"]);
-    /// assert_eq!(trie.find_prefixes("efghij".bytes()), Vec::<&&str>::new());
-    /// assert_eq!(trie.find_prefixes("abz".bytes()), Vec::<&&str>::new());
+    /// assert_eq!(prefixes, vec![(2, &"ABC"), (3, &"ABCD")]);
+    /// assert_eq!(trie.find_prefixes("efghij".bytes()), vec![]);
+    /// assert_eq!(trie.find_prefixes("abz".bytes()), vec![]);
+    ///
+    /// trie.insert("".bytes(), "");
+    /// assert_eq!(trie.find_prefixes("ab".bytes()), vec![]);
+    /// assert_eq!(trie.find_prefixes("".bytes()), vec![]);
     /// ```
-
     pub fn find_prefixes<I: Iterator<Item = K>>(&self, key: I) -> Vec<(usize, &V)> {
         let mut node = &self.root;
         let mut prefixes = Vec::new();
         for (i, k) in key.enumerate() {
-            if let Some((nk, next)) = node
+            if let Some((_, next)) = node
                 .children
-                .binary_search_by_key(&&k, |(k, n)| k)
+                .binary_search_by_key(&&k, |(k, _)| k)
                 .ok()
-                .and_then(|ix| Some(&node.children[ix]))
+                .map(|ix| &node.children[ix])
             {
                 if let Some(value) = &next.value {
                     prefixes.push((i, value));
@@ -148,21 +379,87 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
         prefixes
     }
 
+    /// Walks the prefixes of a key, calling `cb` with the index and a view of every node holding a value.
+    ///
+    /// When the key leaves the trie, `cb` is called one last time with the index of the first
+    /// unmatched element and the deepest matched node.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("a".bytes(), 1);
+    /// trie.insert("abc".bytes(), 3);
+    ///
+    /// trie.iter_prefixes("abc".bytes(), |_, mut node| {
+    ///     if let Some(v) = node.value_mut() {
+    ///         *v *= 10;
+    ///     }
+    /// });
+    /// assert_eq!(trie.get("a".bytes()), Some(&10));
+    /// assert_eq!(trie.get("abc".bytes()), Some(&30));
+    /// ```
     pub fn iter_prefixes<I: Iterator<Item = K>>(
         &mut self,
         key: I,
-        mut cb: impl FnMut(usize, &mut TrieNode<K, V>),
+        mut cb: impl FnMut(usize, NodeMut<'_, K, V>),
+    ) {
+        let mut node = &mut self.root;
+        for (i, k) in key.enumerate() {
+            if let Ok(ix) = node.children.binary_search_by_key(&&k, |(k, _)| k) {
+                let (_, next) = &mut node.children[ix];
+                if next.value.is_some() {
+                    cb(i, NodeMut::new(next));
+                }
+                node = next;
+            } else {
+                cb(i, NodeMut::new(node));
+                break;
+            }
+        }
+    }
+
+    /// Same as `iter_prefixes`, also passing the `NodeHandle` of each visited node to `cb`.
+    ///
+    /// Handles can be kept after the walk to retrieve the full key of a node with `key_of`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("a".bytes(), 1);
+    /// trie.insert("abc".bytes(), 3);
+    ///
+    /// let mut handles = Vec::new();
+    /// trie.iter_prefixes_with_handles("abcd".bytes(), |_, handle, node| {
+    ///     if node.has_value() {
+    ///         handles.push(handle.clone());
+    ///     }
+    /// });
+    /// let keys: Vec<Vec<u8>> = handles.iter().filter_map(|h| trie.key_of(h)).collect();
+    /// assert_eq!(keys, vec![b"a".to_vec(), b"abc".to_vec(), b"abc".to_vec()]);
+    /// ```
+    pub fn iter_prefixes_with_handles<I: Iterator<Item = K>>(
+        &mut self,
+        key: I,
+        mut cb: impl FnMut(usize, &NodeHandle, NodeMut<'_, K, V>),
     ) {
         let mut node = &mut self.root;
+        let mut handle = NodeHandle::root();
         for (i, k) in key.enumerate() {
-            if let Ok(ix) = node.children.binary_search_by_key(&&k, |(k, n)| k) {
-                let (nk, next) = &mut node.children[ix];
-                if let Some(_) = &mut next.value {
-                    cb(i, next);
+            if let Ok(ix) = node.children.binary_search_by_key(&&k, |(k, _)| k) {
+                handle.push(ix);
+                let (_, next) = &mut node.children[ix];
+                if next.value.is_some() {
+                    cb(i, &handle, NodeMut::new(next));
                 }
                 node = next;
             } else {
-                cb(i, node);
+                cb(i, &handle, NodeMut::new(node));
                 break;
             }
         }
@@ -170,6 +467,9 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
 
     /// Finds the longest prefix in the `Trie` for a given string.
     ///
+    /// Only non-empty prefixes are considered, like `find_prefixes`: the value of the empty key
+    /// is never returned, even for an empty key.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -184,6 +484,10 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// assert_eq!(trie.find_longest_prefix("http://purl.obolibrary.org/obo/1234".bytes()), Some("obo").as_ref());
     /// assert_eq!(trie.find_longest_prefix("notthere".bytes()), None.as_ref());
     /// assert_eq!(trie.find_longest_prefix("httno".bytes()), None.as_ref());
+    ///
+    /// trie.insert("".bytes(), "root");
+    /// assert_eq!(trie.find_longest_prefix("httno".bytes()), None);
+    /// assert_eq!(trie.find_longest_prefix("".bytes()), None);
     /// ```
     pub fn find_longest_prefix<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
         {
@@ -203,8 +507,40 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
         }
     }
 
+    /// Finds the shortest prefix in the `Trie` for a given key, stopping at the first value on
+    /// its path
+    ///
+    /// Only non-empty prefixes are considered, like `find_longest_prefix`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut grants = Trie::new();
+    /// grants.insert("org/eng/".bytes(), "eng");
+    /// grants.insert("org/eng/infra/".bytes(), "infra");
+    ///
+    /// assert_eq!(grants.find_shortest_prefix("org/eng/infra/db".bytes()), Some(&"eng"));
+    /// assert_eq!(grants.find_shortest_prefix("org/sales/".bytes()), None);
+    /// ```
+    pub fn find_shortest_prefix<I: Iterator<Item = K>>(&self, key: I) -> Option<&V> {
+        let mut current = &self.root;
+        for k in key {
+            let ix = current.children.binary_search_by(|(key, _)| key.cmp(&k)).ok()?;
+            current = &current.children[ix].1;
+            if let Some(value) = current.value.as_ref() {
+                return Some(value);
+            }
+        }
+        None
+    }
+
     /// Returns a list of all strings in the `Trie` that start with the given prefix.
     ///
+    /// Every key starts with the empty prefix, so an empty prefix returns all the values,
+    /// including the one of the empty key.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -220,6 +556,7 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// assert_eq!(strings, vec![&"App", &"Apple", &"Applet"]);
     /// assert_eq!(trie.find_postfixes("bpp".bytes()), Vec::<&&str>::new());
     /// assert_eq!(trie.find_postfixes("apzz".bytes()), Vec::<&&str>::new());
+    /// assert_eq!(trie.find_postfixes("".bytes()).len(), 4);
     /// ```
     pub fn find_postfixes<I: Iterator<Item = K>>(&self, prefix: I) -> Vec<&V> {
         let mut postfixes = Vec::new();
@@ -239,18 +576,158 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
         }
     }
 
+    /// Reduces the values whose key starts with `prefix`, in ascending key order
+    ///
+    /// Neither the keys nor a list of the values are built, unlike `iter_prefix` and
+    /// `find_postfixes`. Returns `init` when no key starts with `prefix`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut sizes = Trie::new();
+    /// sizes.insert("/var/log/a".bytes(), 10);
+    /// sizes.insert("/var/log/b".bytes(), 20);
+    /// sizes.insert("/var/tmp/c".bytes(), 40);
+    /// assert_eq!(sizes.fold_prefix("/var/log/".bytes(), 0, |total, size| total + size), 30);
+    /// assert_eq!(sizes.fold_prefix("/home/".bytes(), 0, |total, size| total + size), 0);
+    /// ```
+    pub fn fold_prefix<I, B, F>(&self, prefix: I, init: B, mut f: F) -> B
+    where
+        I: Iterator<Item = K>,
+        F: FnMut(B, &V) -> B,
+    {
+        match self.try_fold_prefix(prefix, init, |acc, value| {
+            Ok::<B, std::convert::Infallible>(f(acc, value))
+        }) {
+            Ok(acc) => acc,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Reduces the values whose key starts with `prefix` like `fold_prefix`, stopping at the
+    /// first error returned by `f`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut quotas = Trie::new();
+    /// quotas.insert("a/x".bytes(), 200u8);
+    /// quotas.insert("a/y".bytes(), 100u8);
+    /// let total = quotas.try_fold_prefix("a/".bytes(), 0u8, |acc, q| acc.checked_add(*q).ok_or(acc));
+    /// assert_eq!(total, Err(200));
+    /// ```
+    pub fn try_fold_prefix<I, B, E, F>(&self, prefix: I, init: B, mut f: F) -> Result<B, E>
+    where
+        I: Iterator<Item = K>,
+        F: FnMut(B, &V) -> Result<B, E>,
+    {
+        let mut acc = init;
+        let mut stack: Vec<&TrieNode<K, V>> = self.find_node(prefix).into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if let Some(value) = &node.value {
+                acc = f(acc, value)?;
+            }
+            stack.extend(node.children.iter().rev().map(|(_, child)| child));
+        }
+        Ok(acc)
+    }
+
+    /// Returns the entries whose key is within `max_distance` edits (Levenshtein distance) of `key`
+    ///
+    /// Branches are pruned as soon as no key below them can be close enough. Results are
+    /// returned with their distance, in no particular order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("hello".bytes(), 1);
+    /// trie.insert("help".bytes(), 2);
+    /// trie.insert("world".bytes(), 3);
+    ///
+    /// let mut matches = trie.find_fuzzy("helo".bytes(), 1);
+    /// matches.sort();
+    /// assert_eq!(matches, vec![(b"hello".to_vec(), 1, &1), (b"help".to_vec(), 1, &2)]);
+    /// assert!(trie.find_fuzzy("xyz".bytes(), 1).is_empty());
+    /// ```
+    pub fn find_fuzzy<I: Iterator<Item = K>>(
+        &self,
+        key: I,
+        max_distance: usize,
+    ) -> Vec<(Vec<K>, usize, &V)> {
+        let key: Vec<K> = key.collect();
+        let row: Vec<usize> = (0..=key.len()).collect();
+        let mut matches = Vec::new();
+        if let Some(value) = &self.root.value {
+            if key.len() <= max_distance {
+                matches.push((Vec::new(), key.len(), value));
+            }
+        }
+        Self::collect_fuzzy(
+            &self.root,
+            &key,
+            &row,
+            max_distance,
+            &mut Vec::new(),
+            &mut matches,
+        );
+        matches
+    }
+
+    fn collect_fuzzy<'a>(
+        node: &'a TrieNode<K, V>,
+        key: &[K],
+        prev_row: &[usize],
+        max_distance: usize,
+        path: &mut Vec<K>,
+        matches: &mut Vec<(Vec<K>, usize, &'a V)>,
+    ) {
+        for (k, child) in &node.children {
+            // Next row of the edit distance matrix between `key` and `path + k`
+            let mut row = Vec::with_capacity(prev_row.len());
+            row.push(prev_row[0] + 1);
+            for j in 1..prev_row.len() {
+                let substitution = prev_row[j - 1] + usize::from(key[j - 1] != *k);
+                row.push(substitution.min(prev_row[j] + 1).min(row[j - 1] + 1));
+            }
+            path.push(k.clone());
+            let distance = row[key.len()];
+            if let (Some(value), true) = (&child.value, distance <= max_distance) {
+                matches.push((path.clone(), distance, value));
+            }
+            if row.iter().min().is_some_and(|d| *d <= max_distance) {
+                Self::collect_fuzzy(child, key, &row, max_distance, path, matches);
+            }
+            path.pop();
+        }
+    }
+
     /// Checks if the `Trie` is empty
     ///
+    /// Runs in constant time by looking at the root only, so the empty nodes left by
+    /// `remove_no_prune` count as content until `prune` removes them.
+    ///
     /// # Example
     ///
     /// ```rust
     /// use ptrie::Trie;
     ///
-    /// let t = Trie::<char, f64>::new();
+    /// let mut t = Trie::new();
+    /// assert!(t.is_empty());
+    /// t.insert("a".bytes(), 1.0);
+    /// t.remove_no_prune("a".bytes());
+    /// assert!(!t.is_empty());
+    /// t.prune();
     /// assert!(t.is_empty());
     /// ```
     pub fn is_empty(&self) -> bool {
-        self.root.children.is_empty()
+        self.root.value.is_none() && self.root.children.is_empty()
     }
 
     /// Clears the trie
@@ -269,9 +746,12 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     /// ```
     pub fn clear(&mut self) {
         self.root = TrieNode::default();
+        if let Some(order) = self.insertion_order.as_mut() {
+            order.clear();
+        }
     }
 
-    /// Adds a new key to the `Trie`
+    /// Adds a new key to the `Trie`, returning the value previously stored for this key
     ///
     /// # Example
     ///
@@ -280,75 +760,974 @@ impl<K: Eq + Ord + Clone, V> Trie<K, V> {
     ///
     /// let mut t = Trie::new();
     /// let data = "test".bytes();
-    /// t.insert(data.clone(), 42);
-    /// t.insert(data, 42);
-    /// t.insert("test2".bytes(), 43);
+    /// assert_eq!(t.insert(data.clone(), 42), None);
+    /// assert_eq!(t.insert(data, 43), Some(42));
+    /// t.insert("test2".bytes(), 44);
     /// assert!(!t.is_empty());
     /// ```
-    pub fn insert<I: Iterator<Item = K>>(
-        &mut self,
-        key: I,
-        value_cb: impl FnMut(&mut TrieNode<K, V>, Option<usize>),
-    ) -> Option<&mut V> {
-        self.root.insert(key.enumerate(), value_cb, None)
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V> {
+        self.forget_budget_estimate();
+        self.insert_unbudgeted(key, value)
     }
 
-    pub fn remove_subtree<I: Iterator<Item = K>>(&mut self, key: I) {
-        self.root.remove_subtree(key.peekable())
+    /// Inserts like `insert`, leaving the estimate of the memory budget to the caller
+    fn insert_unbudgeted<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V> {
+        let Some(order) = self.insertion_order.as_mut() else {
+            return self.root.find_or_create_node(key).value.replace(value);
+        };
+        let key: Vec<K> = key.collect();
+        let previous = self
+            .root
+            .find_or_create_node(key.iter().cloned())
+            .value
+            .replace(value);
+        if previous.is_none() {
+            order.push(key);
+        }
+        previous
     }
 
-    /// Finds the node in the `Trie` for a given key
+    /// Adds a new key to the `Trie` like `insert`, enforcing the maximum depth and the memory
+    /// budget of the builder
     ///
-    /// Internal API
-    fn find_node<I: Iterator<Item = K>>(&self, key: I) -> Option<&TrieNode<K, V>> {
-        self.root.find_node(key)
+    /// Keys longer than the maximum depth are rejected with `TrieError::DepthExceeded` and leave
+    /// the `Trie` untouched, which keeps untrusted input from growing arbitrarily deep chains.
+    /// Keys needing more nodes than the memory budget has left are rejected with
+    /// `TrieError::BudgetExceeded`, or make room by evicting the oldest keys, depending on the
+    /// `BudgetPolicy`. Other inserting methods do not enforce the budget, the next call counts
+    /// the nodes they created.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::builder().max_depth(4).build();
+    /// assert_eq!(t.insert_checked("abcd".bytes(), 1).unwrap(), None);
+    /// assert!(t.insert_checked("abcde".bytes(), 2).is_err());
+    /// assert!(t.node("abcd".bytes()).unwrap().is_leaf());
+    /// ```
+    pub fn insert_checked<I: Iterator<Item = K>>(
+        &mut self,
+        key: I,
+        value: V,
+    ) -> Result<Option<V>, TrieError> {
+        if self.max_depth.is_none() && self.memory_budget.is_none() {
+            return Ok(self.insert(key, value));
+        }
+        let key: Vec<K> = match self.max_depth {
+            Some(max_depth) => key.take(max_depth.saturating_add(1)).collect(),
+            None => key.collect(),
+        };
+        if let Some(max_depth) = self.max_depth.filter(|max| key.len() > *max) {
+            return Err(TrieError::DepthExceeded(format!(
+                "Key longer than the maximum depth of {}",
+                max_depth
+            )));
+        }
+        if self.memory_budget.is_some() {
+            self.reserve_budget(&key)?;
+        }
+        Ok(self.insert_unbudgeted(key.into_iter(), value))
     }
 
-    fn find_node_mut<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&mut TrieNode<K, V>> {
-        self.root.find_node_mut(key)
+    /// Marks the estimate of the memory budget as unknown, after creating nodes without
+    /// reserving them
+    fn forget_budget_estimate(&mut self) {
+        if let Some(budget) = self.memory_budget.as_mut() {
+            budget.estimate = None;
+        }
+    }
+
+    /// Makes room in the memory budget for the nodes of `key`, updating the estimate
+    fn reserve_budget(&mut self, key: &[K]) -> Result<(), TrieError> {
+        let node_size = crate::builder::node_footprint::<K, V>();
+        let budget = self.memory_budget.as_mut().expect("checked by the caller");
+        let mut estimate = match budget.estimate {
+            Some(estimate) => estimate,
+            None => self.root.count_nodes() * node_size,
+        };
+        let mut counted = budget.estimate.is_none();
+        loop {
+            let needed = (key.len() - self.root.existing_depth(key)) * node_size;
+            if estimate + needed <= budget.bytes {
+                budget.estimate = Some(estimate + needed);
+                return Ok(());
+            }
+            if !counted {
+                estimate = self.root.count_nodes() * node_size;
+                counted = true;
+                continue;
+            }
+            // Keys which would not fit in an empty `Trie` evict nothing
+            let fits_alone = key.len() * node_size <= budget.bytes;
+            let oldest = match (budget.policy, self.insertion_order.as_mut()) {
+                (BudgetPolicy::EvictOldest, Some(order)) if fits_alone && !order.is_empty() => {
+                    order.remove(0)
+                }
+                _ => {
+                    budget.estimate = Some(estimate);
+                    return Err(TrieError::BudgetExceeded(format!(
+                        "Key needs {} bytes, {} of the budget of {} are left",
+                        needed,
+                        budget.bytes.saturating_sub(estimate),
+                        budget.bytes
+                    )));
+                }
+            };
+            estimate -= self.root.removal_frees(&oldest) * node_size;
+            self.root.remove(oldest.into_iter(), true);
+        }
     }
 
-    /// Iterate the nodes in the `Trie`
+    /// Adds a new key to the `Trie` only if it has no value yet
+    ///
+    /// Returns a mutable reference to the inserted value, or an `OccupiedError` giving access to
+    /// the value already stored, so duplicate keys can be detected instead of silently replaced.
     ///
     /// # Example
     ///
-    /// ```
+    /// ```rust
     /// use ptrie::Trie;
     ///
     /// let mut t = Trie::new();
-    /// let test = "test".bytes();
-    /// let tes = "tes".bytes();
+    /// assert_eq!(t.try_insert("port".bytes(), 80).ok(), Some(&mut 80));
     ///
-    /// t.insert(test.clone(), String::from("test"));
-    /// t.insert(tes.clone(), String::from("tes"));
-    /// for (k, v) in t.iter() {
-    ///     assert!(std::str::from_utf8(&k).unwrap().starts_with("tes"));
-    ///     assert!(v.starts_with("tes"));
-    /// }
+    /// let err = t.try_insert("port".bytes(), 8080).unwrap_err();
+    /// assert_eq!(*err.existing, 80);
+    /// assert_eq!(err.value, 8080);
+    /// assert_eq!(t.get("port".bytes()), Some(&80));
     /// ```
-    pub fn iter(&self) -> TrieIterator<K, V> {
-        TrieIterator::new(&self)
+    pub fn try_insert<I: Iterator<Item = K>>(
+        &mut self,
+        key: I,
+        value: V,
+    ) -> Result<&mut V, OccupiedError<'_, V>> {
+        self.forget_budget_estimate();
+        let node = match self.insertion_order.as_mut() {
+            None => self.root.find_or_create_node(key),
+            Some(order) => {
+                let key: Vec<K> = key.collect();
+                let node = self.root.find_or_create_node(key.iter().cloned());
+                if node.value.is_none() {
+                    order.push(key);
+                }
+                node
+            }
+        };
+        if node.value.is_none() {
+            return Ok(node.value.insert(value));
+        }
+        let existing = node.value.as_mut().expect("checked above");
+        Err(OccupiedError { existing, value })
     }
-}
 
-/// Implement the `Default` trait for `Trie` since we have a constructor that does not need arguments
-impl<T: Eq + Ord + Clone, U> Default for Trie<T, U> {
-    fn default() -> Self {
-        Self::new()
+    /// Adds a new key to the `Trie`, handling an existing value according to `policy`
+    ///
+    /// Returns the value left out of the `Trie`: the previous value with
+    /// `DuplicatePolicy::Overwrite`, the new value with `DuplicatePolicy::KeepFirst` when the key
+    /// already had one. `DuplicatePolicy::Error` fails like `try_insert`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::trie::DuplicatePolicy;
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("a".bytes(), 1);
+    ///
+    /// assert_eq!(t.insert_with_policy("a".bytes(), 2, DuplicatePolicy::KeepFirst).unwrap(), Some(2));
+    /// assert_eq!(t.get("a".bytes()), Some(&1));
+    /// assert_eq!(t.insert_with_policy("a".bytes(), 3, DuplicatePolicy::Overwrite).unwrap(), Some(1));
+    /// assert!(t.insert_with_policy("a".bytes(), 4, DuplicatePolicy::Error).is_err());
+    /// assert_eq!(t.get("a".bytes()), Some(&3));
+    /// ```
+    pub fn insert_with_policy<I: Iterator<Item = K>>(
+        &mut self,
+        key: I,
+        value: V,
+        policy: DuplicatePolicy,
+    ) -> Result<Option<V>, OccupiedError<'_, V>> {
+        match policy {
+            DuplicatePolicy::Overwrite => Ok(self.insert(key, value)),
+            DuplicatePolicy::KeepFirst => match self.try_insert(key, value) {
+                Ok(_) => Ok(None),
+                Err(OccupiedError { value, .. }) => Ok(Some(value)),
+            },
+            DuplicatePolicy::Error => self.try_insert(key, value).map(|_| None),
+        }
     }
-}
-
-/// Iterator for the `Trie` struct
-pub struct TrieIterator<'a, K: Eq + Ord + Clone, V> {
-    // Stack with node reference and current path
-    stack: Vec<(&'a TrieNode<K, V>, Vec<K>)>,
-}
 
-impl<'a, K: Eq + Ord + Clone, V> TrieIterator<'a, K, V> {
-    fn new(trie: &'a Trie<K, V>) -> Self {
-        TrieIterator {
+    /// Gets a mutable reference to the value of a key, inserting the result of `default` first
+    /// when the key is not in the `Trie`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// *t.get_or_insert_with("a".bytes(), || 0) += 1;
+    /// *t.get_or_insert_with("a".bytes(), || 0) += 1;
+    /// assert_eq!(t.get("a".bytes()), Some(&2));
+    /// ```
+    pub fn get_or_insert_with<I: Iterator<Item = K>, F: FnOnce() -> V>(
+        &mut self,
+        key: I,
+        default: F,
+    ) -> &mut V {
+        self.forget_budget_estimate();
+        let node = match self.insertion_order.as_mut() {
+            None => self.root.find_or_create_node(key),
+            Some(order) => {
+                let key: Vec<K> = key.collect();
+                let node = self.root.find_or_create_node(key.iter().cloned());
+                if node.value.is_none() {
+                    order.push(key);
+                }
+                node
+            }
+        };
+        node.value.get_or_insert_with(default)
+    }
+
+    /// Adds a new key to the `Trie`, calling `value_cb` with a view of every node along the key.
+    ///
+    /// The callback receives the index of the key element leading to the node, `None` for the root.
+    /// Returns the value stored at the end of the key, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_with("abc".bytes(), |mut node, ix| {
+    ///     if ix == Some(2) {
+    ///         node.set_value(String::from("abc"));
+    ///     }
+    /// });
+    /// assert_eq!(t.get("abc".bytes()), Some(&String::from("abc")));
+    /// assert!(!t.contains_key("ab".bytes()));
+    /// ```
+    pub fn insert_with<I: Iterator<Item = K>>(
+        &mut self,
+        key: I,
+        value_cb: impl FnMut(NodeMut<'_, K, V>, Option<usize>),
+    ) -> Option<&mut V> {
+        self.forget_budget_estimate();
+        self.root.insert(key.enumerate(), value_cb, None)
+    }
+
+    /// Read-only view of the root node of the `Trie`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("ab".bytes(), 1);
+    /// t.insert("ac".bytes(), 2);
+    ///
+    /// let a = t.root().child(&b'a').unwrap();
+    /// assert!(!a.has_value());
+    /// let children: Vec<u8> = a.children().map(|(k, _)| *k).collect();
+    /// assert_eq!(children, vec![b'b', b'c']);
+    /// ```
+    pub fn root(&self) -> NodeRef<'_, K, V> {
+        NodeRef::new(&self.root)
+    }
+
+    /// Handle to the node reached by a key, even if no value is stored there
+    ///
+    /// Handles stay valid until the structure of the `Trie` changes (new or removed nodes).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("abc".bytes(), 1);
+    ///
+    /// let handle = t.handle("ab".bytes()).unwrap();
+    /// assert_eq!(handle.depth(), 2);
+    /// assert_eq!(t.key_of(&handle), Some(b"ab".to_vec()));
+    /// assert!(t.handle("abd".bytes()).is_none());
+    /// ```
+    pub fn handle<I: Iterator<Item = K>>(&self, key: I) -> Option<NodeHandle> {
+        let mut node = &self.root;
+        let mut handle = NodeHandle::root();
+        for k in key {
+            let ix = node.children.binary_search_by_key(&&k, |(k, _)| k).ok()?;
+            handle.push(ix);
+            node = &node.children[ix].1;
+        }
+        Some(handle)
+    }
+
+    /// Reconstructs the full key of the node pointed by a handle
+    ///
+    /// Returns `None` when the handle does not point to a node of this `Trie` anymore.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("test".bytes(), 1);
+    ///
+    /// let handle = t.handle("test".bytes()).unwrap();
+    /// assert_eq!(t.key_of(&handle), Some(b"test".to_vec()));
+    /// t.clear();
+    /// assert_eq!(t.key_of(&handle), None);
+    /// ```
+    pub fn key_of(&self, handle: &NodeHandle) -> Option<Vec<K>> {
+        let mut node = &self.root;
+        let mut key = Vec::with_capacity(handle.depth());
+        for &ix in handle.path() {
+            let (k, next) = node.children.get(ix)?;
+            key.push(k.clone());
+            node = next;
+        }
+        Some(key)
+    }
+
+    /// Read-only view of the node pointed by a handle
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("ab".bytes(), 1);
+    ///
+    /// let handle = t.handle("ab".bytes()).unwrap();
+    /// assert_eq!(t.node_at(&handle).and_then(|n| n.value()), Some(&1));
+    /// ```
+    pub fn node_at(&self, handle: &NodeHandle) -> Option<NodeRef<'_, K, V>> {
+        let mut node = &self.root;
+        for &ix in handle.path() {
+            node = &node.children.get(ix)?.1;
+        }
+        Some(NodeRef::new(node))
+    }
+
+    /// Read-only view of the node reached by a key, even if no value is stored there
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("abc".bytes(), 1);
+    ///
+    /// let node = t.node("ab".bytes()).unwrap();
+    /// assert!(!node.has_value());
+    /// assert_eq!(node.child_count(), 1);
+    /// assert!(t.node("abd".bytes()).is_none());
+    /// ```
+    pub fn node<I: Iterator<Item = K>>(&self, key: I) -> Option<NodeRef<'_, K, V>> {
+        self.find_node(key).map(NodeRef::new)
+    }
+
+    /// Removes a key from the `Trie`, returning its value
+    ///
+    /// Nodes left without value nor children are pruned, so the structure only contains the
+    /// remaining keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("test".bytes(), 1);
+    /// t.insert("tea".bytes(), 2);
+    ///
+    /// assert_eq!(t.remove("test".bytes()), Some(1));
+    /// assert_eq!(t.remove("test".bytes()), None);
+    /// assert!(t.node("tes".bytes()).is_none());
+    /// assert_eq!(t.get("tea".bytes()), Some(&2));
+    /// ```
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        self.remove_with(key, true)
+    }
+
+    /// Removes a key from the `Trie` without pruning the nodes left empty
+    ///
+    /// Keeping the nodes makes re-inserting similar keys cheaper, `prune` cleans them up later.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("test".bytes(), 1);
+    ///
+    /// assert_eq!(t.remove_no_prune("test".bytes()), Some(1));
+    /// assert!(t.node("test".bytes()).is_some());
+    /// assert!(!t.contains_key("test".bytes()));
+    /// assert_eq!(t.prune(), 4);
+    /// assert!(t.node("t".bytes()).is_none());
+    /// ```
+    pub fn remove_no_prune<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        self.remove_with(key, false)
+    }
+
+    /// Removes the nodes without value nor children, returning how many were removed
+    pub fn prune(&mut self) -> usize {
+        bulk_span!("ptrie::prune");
+        let removed = self.root.prune();
+        debug_event!(removed, "pruned empty nodes");
+        removed
+    }
+
+    /// Rewrites the `Trie` in its most compact form after many mutations, returning the number
+    /// of empty nodes removed
+    ///
+    /// Empty nodes are pruned, children sorted again if a deserialized `Trie` left them
+    /// unsorted, and the spare capacity of the child lists and of the insertion order released.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for i in 0..100u8 {
+    ///     t.insert([b'k', i].into_iter(), i);
+    /// }
+    /// for i in 0..100u8 {
+    ///     t.remove_no_prune([b'k', i].into_iter());
+    /// }
+    /// t.insert("a".bytes(), 0);
+    /// assert_eq!(t.compact(), 101);
+    /// assert_eq!(t.root().child_count(), 1);
+    /// ```
+    pub fn compact(&mut self) -> usize {
+        bulk_span!("ptrie::compact");
+        let removed = self.root.prune();
+        self.root.shrink();
+        if let Some(order) = self.insertion_order.as_mut() {
+            order.shrink_to_fit();
+        }
+        debug_event!(removed, "compacted");
+        removed
+    }
+
+    fn remove_with<I: Iterator<Item = K>>(&mut self, key: I, prune: bool) -> Option<V> {
+        let Some(order) = self.insertion_order.as_mut() else {
+            return self.root.remove(key, prune);
+        };
+        let key: Vec<K> = key.collect();
+        let removed = self.root.remove(key.iter().cloned(), prune);
+        if removed.is_some() {
+            order.retain(|k| *k != key);
+        }
+        removed
+    }
+
+    /// Removes a key and all the keys starting with it
+    ///
+    /// Every key starts with the empty key, so removing it clears the `Trie`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("a".bytes(), 1);
+    /// t.insert("abc".bytes(), 2);
+    /// t.insert("abd".bytes(), 3);
+    ///
+    /// t.remove_subtree("ab".bytes());
+    /// assert_eq!(t.iter().count(), 1);
+    /// assert!(t.node("ab".bytes()).is_none());
+    /// ```
+    pub fn remove_subtree<I: Iterator<Item = K>>(&mut self, key: I) {
+        bulk_span!("ptrie::remove_subtree");
+        let mut key = key.peekable();
+        if key.peek().is_none() {
+            return self.clear();
+        }
+        let Some(order) = self.insertion_order.as_mut() else {
+            return self.root.remove_subtree(key);
+        };
+        let key: Vec<K> = key.collect();
+        order.retain(|k| !k.starts_with(&key));
+        self.root.remove_subtree(key.into_iter().peekable())
+    }
+
+    /// Removes all the keys starting with any of `prefixes`, returning how many were removed
+    ///
+    /// Prefixes covered by another one of the list are skipped, so each subtree is detached
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// for key in ["user/1/name", "user/1/mail", "user/2/name", "user/3/name", "group/1"] {
+    ///     t.insert(key.bytes(), ());
+    /// }
+    ///
+    /// let removed = t.remove_prefixes(["user/1/", "user/1/name", "group/", "team/"].map(str::bytes));
+    /// assert_eq!(removed, 3);
+    /// assert_eq!(t.iter().count(), 2);
+    /// ```
+    pub fn remove_prefixes<P, I>(&mut self, prefixes: P) -> usize
+    where
+        P: IntoIterator<Item = I>,
+        I: Iterator<Item = K>,
+    {
+        bulk_span!("ptrie::remove_prefixes");
+        let mut sorted: Vec<Vec<K>> = prefixes.into_iter().map(Iterator::collect).collect();
+        sorted.sort_unstable();
+        // Sorting puts the keys starting with a prefix right after it
+        let mut covering: Vec<Vec<K>> = Vec::with_capacity(sorted.len());
+        for prefix in sorted {
+            if !covering.last().is_some_and(|last| prefix.starts_with(last)) {
+                covering.push(prefix);
+            }
+        }
+        if covering.first().is_some_and(Vec::is_empty) {
+            let removed = self.root.count_values();
+            self.clear();
+            return removed;
+        }
+        let mut removed = 0;
+        for prefix in &covering {
+            if let Some(node) = self.find_node(prefix.iter().cloned()) {
+                removed += node.count_values();
+                self.root.remove_subtree(prefix.iter().cloned().peekable());
+            }
+        }
+        if let Some(order) = self.insertion_order.as_mut() {
+            order.retain(|key| {
+                let below = covering.partition_point(|prefix| prefix <= key);
+                !(below > 0 && key.starts_with(&covering[below - 1]))
+            });
+        }
+        debug_event!(removed, "removed prefixes");
+        removed
+    }
+
+    /// Removes the entries for which `pred` holds, returning how many were removed
+    ///
+    /// The entries are visited in ascending key order in a single traversal, and the nodes
+    /// left empty are pruned on the way back. Should `pred` panic, the entries below the nodes
+    /// being visited are lost.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut sessions = Trie::new();
+    /// sessions.insert("alice/laptop".bytes(), 1700);
+    /// sessions.insert("alice/phone".bytes(), 1200);
+    /// sessions.insert("bob/laptop".bytes(), 900);
+    ///
+    /// let expired = sessions.remove_matching(|_, &last_seen| last_seen < 1500);
+    /// assert_eq!(expired, 2);
+    /// assert!(sessions.contains_key("alice/laptop".bytes()));
+    /// assert!(sessions.root().child(&b'b').is_none());
+    ///
+    /// assert_eq!(sessions.remove_matching(|key, _| key.starts_with(b"alice/")), 1);
+    /// assert!(sessions.is_empty());
+    /// ```
+    pub fn remove_matching(&mut self, mut pred: impl FnMut(&[K], &V) -> bool) -> usize {
+        bulk_span!("ptrie::remove_matching");
+        let mut keys = self.insertion_order.as_ref().map(|_| Vec::new());
+        let removed = self.root.remove_matching(&mut Vec::new(), &mut pred, &mut keys);
+        if let (Some(order), Some(keys)) = (self.insertion_order.as_mut(), keys) {
+            // The keys are removed in ascending order
+            order.retain(|key| keys.binary_search(key).is_err());
+        }
+        debug_event!(removed, "removed matching entries");
+        removed
+    }
+
+    /// Returns a deep copy of all the entries starting with `prefix`, keeping their full keys.
+    ///
+    /// The original `Trie` is left untouched, this is the non-destructive counterpart of
+    /// `remove_subtree`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("tenant1/a".bytes(), 1);
+    /// t.insert("tenant1/b".bytes(), 2);
+    /// t.insert("tenant2/a".bytes(), 3);
+    ///
+    /// let tenant1 = t.clone_subtree("tenant1/".bytes());
+    /// assert_eq!(tenant1.get("tenant1/a".bytes()), Some(&1));
+    /// assert_eq!(tenant1.get("tenant1/b".bytes()), Some(&2));
+    /// assert!(!tenant1.contains_key("tenant2/a".bytes()));
+    /// assert!(t.contains_key("tenant1/a".bytes()));
+    /// assert!(t.clone_subtree("tenant3/".bytes()).is_empty());
+    /// ```
+    pub fn clone_subtree<I: Iterator<Item = K>>(&self, prefix: I) -> Trie<K, V>
+    where
+        V: Clone,
+    {
+        bulk_span!("ptrie::clone_subtree");
+        let prefix: Vec<K> = prefix.collect();
+        let mut trie = Trie::new();
+        if let Some(order) = &self.insertion_order {
+            trie.insertion_order =
+                Some(order.iter().filter(|k| k.starts_with(&prefix)).cloned().collect());
+        }
+        if let Some(node) = self.find_node(prefix.iter().cloned()) {
+            *trie.root.find_or_create_node(prefix.into_iter()) = node.clone();
+        }
+        trie
+    }
+
+    /// Copy of the `Trie` with `f` applied to every value, in a single traversal
+    ///
+    /// The insertion order and the depth limit are kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("apple".bytes(), String::from("fruit"));
+    /// t.insert("leek".bytes(), String::from("vegetable"));
+    ///
+    /// let lengths = t.clone_map(|category| category.len());
+    /// assert_eq!(lengths.get("apple".bytes()), Some(&5));
+    /// assert_eq!(lengths.get("leek".bytes()), Some(&9));
+    /// assert_eq!(t.get("apple".bytes()).map(String::as_str), Some("fruit"));
+    /// ```
+    pub fn clone_map<W>(&self, mut f: impl FnMut(&V) -> W) -> Trie<K, W> {
+        bulk_span!("ptrie::clone_map");
+        Trie {
+            root: self.root.map_clone(&mut f),
+            insertion_order: self.insertion_order.clone(),
+            max_depth: self.max_depth,
+            memory_budget: self.memory_budget.clone(),
+            child_order: None,
+        }
+    }
+
+    /// Finds the node in the `Trie` for a given key
+    ///
+    /// Internal API
+    fn find_node<I: Iterator<Item = K>>(&self, key: I) -> Option<&TrieNode<K, V>> {
+        self.root.find_node(key)
+    }
+
+    fn find_node_mut<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&mut TrieNode<K, V>> {
+        self.root.find_node_mut(key)
+    }
+
+    /// Iterate the nodes in the `Trie`, in ascending key order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// let test = "test".bytes();
+    /// let tes = "tes".bytes();
+    ///
+    /// t.insert(test.clone(), String::from("test"));
+    /// t.insert(tes.clone(), String::from("tes"));
+    /// for (k, v) in t.iter() {
+    ///     assert!(std::str::from_utf8(&k).unwrap().starts_with("tes"));
+    ///     assert!(v.starts_with("tes"));
+    /// }
+    /// ```
+    pub fn iter(&self) -> TrieIterator<'_, K, V> {
+        TrieIterator::new(self)
+    }
+
+    /// Iterate the entries whose key starts with `prefix`, with their full key, in ascending key order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("app".bytes(), 1);
+    /// t.insert("apple".bytes(), 2);
+    /// t.insert("banana".bytes(), 3);
+    ///
+    /// let keys: Vec<Vec<u8>> = t.iter_prefix("app".bytes()).map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec![b"app".to_vec(), b"apple".to_vec()]);
+    /// assert_eq!(t.iter_prefix("c".bytes()).count(), 0);
+    /// ```
+    pub fn iter_prefix<I: Iterator<Item = K>>(&self, prefix: I) -> TrieIterator<'_, K, V> {
+        let prefix: Vec<K> = prefix.collect();
+        match self.find_node(prefix.iter().cloned()) {
+            Some(node) => TrieIterator {
+                stack: vec![(node, prefix)],
+                order: None,
+            },
+            None => TrieIterator {
+                stack: Vec::new(),
+                order: None,
+            },
+        }
+    }
+
+    /// Iterate the entries in the `Trie` with the children of each node in the order of the
+    /// sort key configured with `TrieBuilder::child_order`, ties and unconfigured `Trie`s
+    /// being in key order
+    ///
+    /// Entries come depth first, a key before the longer keys it prefixes. Only this iterator and
+    /// `iter_prefix_presented` follow the configured order, the others stay in key order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::builder()
+    ///     .child_order(|_, hits: Option<&i64>| -hits.copied().unwrap_or(0))
+    ///     .build();
+    /// t.insert("cat".bytes(), 3);
+    /// t.insert("car".bytes(), 10);
+    /// t.insert("cab".bytes(), 1);
+    ///
+    /// let keys: Vec<String> = t
+    ///     .iter_presented()
+    ///     .map(|(k, _)| String::from_utf8(k).unwrap())
+    ///     .collect();
+    /// assert_eq!(keys, vec!["car", "cat", "cab"]);
+    /// ```
+    pub fn iter_presented(&self) -> TrieIterator<'_, K, V> {
+        TrieIterator {
+            stack: vec![(&self.root, Vec::new())],
+            order: self.child_order,
+        }
+    }
+
+    /// Iterate the entries whose key starts with `prefix` in the order of `iter_presented`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::builder().child_order(|k: &u8, _: Option<&()>| -i64::from(*k)).build();
+    /// for word in ["ab", "ac", "b"] {
+    ///     t.insert(word.bytes(), ());
+    /// }
+    ///
+    /// let keys: Vec<Vec<u8>> = t.iter_prefix_presented("a".bytes()).map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec![b"ac".to_vec(), b"ab".to_vec()]);
+    /// ```
+    pub fn iter_prefix_presented<I: Iterator<Item = K>>(
+        &self,
+        prefix: I,
+    ) -> TrieIterator<'_, K, V> {
+        let mut iter = self.iter_prefix(prefix);
+        iter.order = self.child_order;
+        iter
+    }
+
+    /// Iterate the entries in the `Trie` in ascending key order, lending each key from a buffer
+    /// reused across steps instead of allocating it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("tes".bytes(), 1);
+    /// t.insert("test".bytes(), 2);
+    /// t.insert("b".bytes(), 3);
+    ///
+    /// let mut entries = t.iter_lending();
+    /// let mut total_len = 0;
+    /// while let Some((key, _)) = entries.next_ref() {
+    ///     total_len += key.len();
+    /// }
+    /// assert_eq!(total_len, 8);
+    /// ```
+    pub fn iter_lending(&self) -> LendingIter<'_, K, V> {
+        LendingIter {
+            stack: vec![(&self.root, 0)],
+            key: Vec::new(),
+            fresh: true,
+        }
+    }
+
+    /// Iterate the nodes in the `Trie`, with mutable references to the values, in ascending key order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("test".bytes(), 1);
+    /// t.insert("tes".bytes(), 2);
+    /// for (_, v) in t.iter_mut() {
+    ///     *v *= 10;
+    /// }
+    /// assert_eq!(t.get("test".bytes()), Some(&10));
+    /// assert_eq!(t.get("tes".bytes()), Some(&20));
+    /// ```
+    pub fn iter_mut(&mut self) -> TrieIteratorMut<'_, K, V> {
+        TrieIteratorMut::new(self)
+    }
+
+    /// Check the structure of the `Trie`, reporting the first inconsistency found
+    ///
+    /// Every node must keep its children sorted by edge key, with no edge key repeated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert("abc".bytes(), 1);
+    /// t.insert("abd".bytes(), 2);
+    /// assert_eq!(t.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        let mut stack = vec![(0, &self.root)];
+        while let Some((depth, node)) = stack.pop() {
+            node.check_children(depth)?;
+            stack.extend(node.children.iter().map(|(_, child)| (depth + 1, child)));
+        }
+        Ok(())
+    }
+
+    /// Iterate the entries in the order their keys were first inserted
+    ///
+    /// Returns `None` when the `Trie` was not built with insertion order tracking. Overwriting
+    /// the value of a key keeps its original position. Values stored through the callbacks of
+    /// `insert_with` or `iter_prefixes` are not tracked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::builder().insertion_order(true).build();
+    /// t.insert("zeta".bytes(), 1);
+    /// t.insert("alpha".bytes(), 2);
+    /// t.insert("zeta".bytes(), 3);
+    ///
+    /// let entries: Vec<(&[u8], &i32)> = t.iter_insertion_order().unwrap().collect();
+    /// assert_eq!(entries, vec![(&b"zeta"[..], &3), (&b"alpha"[..], &2)]);
+    /// assert!(Trie::<u8, i32>::new().iter_insertion_order().is_none());
+    /// ```
+    pub fn iter_insertion_order(&self) -> Option<impl Iterator<Item = (&[K], &V)> + '_> {
+        let order = self.insertion_order.as_ref()?;
+        Some(order.iter().filter_map(move |key| {
+            self.get(key.iter().cloned()).map(|value| (key.as_slice(), value))
+        }))
+    }
+}
+
+/// Error for a key whose first `matched` elements exist in the `Trie`, out of `len`
+fn not_found((matched, len): (usize, usize)) -> TrieError {
+    TrieError::NotFound(format!(
+        "Key not found: {} of its {} elements matched",
+        matched, len
+    ))
+}
+
+/// Behavior of `Trie::insert_with_policy` when the key already has a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Replace the existing value, like `insert`
+    #[default]
+    Overwrite,
+    /// Keep the existing value and drop the new one
+    KeepFirst,
+    /// Fail with an `OccupiedError`, like `try_insert`
+    Error,
+}
+
+/// Implement the `Default` trait for `Trie` since we have a constructor that does not need arguments
+impl<T: Eq + Ord + Clone, U> Default for Trie<T, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Inserts every key and value, like repeated calls to `insert`
+impl<K: Eq + Ord + Clone, V, I: IntoIterator<Item = K>> Extend<(I, V)> for Trie<K, V> {
+    fn extend<T: IntoIterator<Item = (I, V)>>(&mut self, entries: T) {
+        self.extend_with_progress(entries, |_| {});
+    }
+}
+
+/// Builds a `Trie` from keys and values
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::Trie;
+///
+/// let t: Trie<u8, i32> = [("ab".bytes(), 1), ("b".bytes(), 2)].into_iter().collect();
+/// assert_eq!(t.get("ab".bytes()), Some(&1));
+/// ```
+impl<K: Eq + Ord + Clone, V, I: IntoIterator<Item = K>> FromIterator<(I, V)> for Trie<K, V> {
+    fn from_iter<T: IntoIterator<Item = (I, V)>>(entries: T) -> Self {
+        let mut trie = Trie::new();
+        trie.extend(entries);
+        trie
+    }
+}
+
+impl<'a, K: Eq + Ord + Clone, V> IntoIterator for &'a Trie<K, V> {
+    type Item = (Vec<K>, &'a V);
+    type IntoIter = TrieIterator<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Eq + Ord + Clone, V> IntoIterator for &'a mut Trie<K, V> {
+    type Item = (Vec<K>, &'a mut V);
+    type IntoIter = TrieIteratorMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Iterator for the `Trie` struct
+pub struct TrieIterator<'a, K: Eq + Ord + Clone, V> {
+    // Stack with node reference and current path
+    stack: Vec<(&'a TrieNode<K, V>, Vec<K>)>,
+    // Sort key of the children, key order when `None`
+    order: Option<ChildOrder<K, V>>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> TrieIterator<'a, K, V> {
+    fn new(trie: &'a Trie<K, V>) -> Self {
+        TrieIterator {
             // Start with root node and empty path
             stack: vec![(&trie.root, Vec::new())],
+            order: None,
         }
     }
 }
@@ -358,12 +1737,19 @@ impl<'a, K: Eq + Ord + Clone, V> Iterator for TrieIterator<'a, K, V> {
     type Item = (Vec<K>, &'a V);
     fn next(&mut self) -> Option<Self::Item> {
         while let Some((node, path)) = self.stack.pop() {
-            // Push children to the stack with updated path
-            for (key_part, child) in &node.children {
+            // Push children to the stack with updated path, the first to visit last to be popped first
+            let first = self.stack.len();
+            for (key_part, child) in node.children.iter().rev() {
                 let mut new_path = path.clone();
                 new_path.push(key_part.clone());
                 self.stack.push((child, new_path));
             }
+            if let Some(order) = self.order {
+                // Stable, so ties stay in reverse key order
+                self.stack[first..].sort_by_key(|(child, path)| {
+                    Reverse(order(&path[path.len() - 1], child.value.as_ref()))
+                });
+            }
             // Return value if it exists
             if let Some(ref value) = node.value {
                 return Some((path, value));
@@ -372,3 +1758,78 @@ impl<'a, K: Eq + Ord + Clone, V> Iterator for TrieIterator<'a, K, V> {
         None
     }
 }
+
+/// Iterator over mutable references to the values of the `Trie` struct
+pub struct TrieIteratorMut<'a, K: Eq + Ord + Clone, V> {
+    // Stack with node reference and current path
+    stack: Vec<(&'a mut TrieNode<K, V>, Vec<K>)>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> TrieIteratorMut<'a, K, V> {
+    fn new(trie: &'a mut Trie<K, V>) -> Self {
+        TrieIteratorMut {
+            // Start with root node and empty path
+            stack: vec![(&mut trie.root, Vec::new())],
+        }
+    }
+}
+
+impl<'a, K: Eq + Ord + Clone, V> Iterator for TrieIteratorMut<'a, K, V> {
+    // Yield key-value pairs
+    type Item = (Vec<K>, &'a mut V);
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, path)) = self.stack.pop() {
+            // Split the node so the value and the children can be borrowed separately
+            let TrieNode { value, children } = node;
+            for (key_part, child) in children.iter_mut().rev() {
+                let mut new_path = path.clone();
+                new_path.push(key_part.clone());
+                self.stack.push((child, new_path));
+            }
+            if let Some(value) = value.as_mut() {
+                return Some((path, value));
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the entries of a `Trie`, lending the keys from an internal buffer, created
+/// with `Trie::iter_lending()`
+///
+/// The key of an entry is borrowed from the iterator until the next step, so it is not an
+/// `Iterator`: entries are read with `next_ref()` in a `while let` loop.
+pub struct LendingIter<'a, K: Eq + Ord + Clone, V> {
+    // Nodes of the current path with the index of the next child to visit
+    stack: Vec<(&'a TrieNode<K, V>, usize)>,
+    // Key of the node on top of the stack
+    key: Vec<K>,
+    // Whether the value of the node on top of the stack is still to be yielded
+    fresh: bool,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> LendingIter<'a, K, V> {
+    /// Next entry in ascending key order, its key valid until the next call
+    pub fn next_ref(&mut self) -> Option<(&[K], &'a V)> {
+        loop {
+            let (node, next) = self.stack.last_mut()?;
+            let node: &'a TrieNode<K, V> = node;
+            if std::mem::take(&mut self.fresh) {
+                if let Some(value) = node.value.as_ref() {
+                    return Some((&self.key, value));
+                }
+            }
+            if let Some((key_part, child)) = node.children.get(*next) {
+                *next += 1;
+                self.key.push(key_part.clone());
+                self.stack.push((child, 0));
+                self.fresh = true;
+                continue;
+            }
+            self.stack.pop();
+            if !self.stack.is_empty() {
+                self.key.pop();
+            }
+        }
+    }
+}
@@ -1,16 +1,22 @@
 //! Struct and functions for the `Trie` nodes
 
+use crate::error::InvariantError;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{clone::Clone, iter::Peekable};
+use std::{borrow::Borrow, clone::Clone, cmp::Ordering, fmt, iter::Peekable};
+
+/// Depth below which `Debug` elides the children of a node
+pub const DEBUG_MAX_DEPTH: usize = 32;
 
 /// A node in the `Trie`, it holds a value, and a list of children nodes
+///
+/// `Debug`, `Clone` and `Drop` do not recurse, or only up to `DEBUG_MAX_DEPTH`, so arbitrarily
+/// deep tries built from untrusted keys cannot overflow the stack.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone)]
 pub struct TrieNode<K: Eq + Ord + Clone, V> {
-    pub value: Option<V>,
+    pub(crate) value: Option<V>,
     /// sorted
-    pub children: Vec<(K, TrieNode<K, V>)>,
+    pub(crate) children: Vec<(K, TrieNode<K, V>)>,
 }
 
 impl<K: Eq + Ord + Clone, V> TrieNode<K, V> {
@@ -21,20 +27,21 @@ impl<K: Eq + Ord + Clone, V> TrieNode<K, V> {
         }
     }
 
-    /// Insert a node in the trie
+    /// Insert a node in the trie, calling `value_cb` on every node along the key
     pub fn insert<I: Iterator<Item = (usize, K)>>(
         &mut self,
         mut key: I,
-        mut value_cb: impl FnMut(&mut TrieNode<K, V>, Option<usize>),
+        mut value_cb: impl FnMut(NodeMut<'_, K, V>, Option<usize>),
         cur: Option<usize>,
     ) -> Option<&mut V> {
-        value_cb(self, cur);
+        value_cb(NodeMut::new(self), cur);
         if let Some((iterx, part)) = key.next() {
-            match self.children.binary_search_by_key(&&part, |(k, n)| k) {
+            match self.children.binary_search_by_key(&&part, |(k, _)| k) {
                 Ok(ix) => self.children[ix].1.insert(key, value_cb, Some(iterx)),
                 Err(ix) => {
                     let new_node = TrieNode::new();
                     self.children.insert(ix, (part, new_node));
+                    debug_assert!(self.check_children(0).is_ok());
                     self.children
                         .get_mut(ix)
                         .unwrap()
@@ -47,39 +54,341 @@ impl<K: Eq + Ord + Clone, V> TrieNode<K, V> {
         }
     }
 
+    /// Find the node for a key, creating the missing nodes along the way
+    pub fn find_or_create_node<I: Iterator<Item = K>>(&mut self, key: I) -> &mut Self {
+        let mut node = self;
+        for part in key {
+            let ix = match node.children.binary_search_by_key(&&part, |(k, _)| k) {
+                Ok(ix) => ix,
+                Err(ix) => {
+                    node.children.insert(ix, (part, TrieNode::new()));
+                    debug_assert!(node.check_children(0).is_ok());
+                    ix
+                }
+            };
+            node = &mut node.children[ix].1;
+        }
+        node
+    }
+
+    /// Number of values in this node and its descendants
+    pub(crate) fn count_values(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += usize::from(node.value.is_some());
+            stack.extend(node.children.iter().map(|(_, child)| child));
+        }
+        count
+    }
+
+    /// Number of descendants of the node
+    pub(crate) fn count_nodes(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            count += node.children.len();
+            stack.extend(node.children.iter().map(|(_, child)| child));
+        }
+        count
+    }
+
+    /// Number of elements of `key` leading to existing nodes
+    pub(crate) fn existing_depth(&self, key: &[K]) -> usize {
+        let mut node = self;
+        for (depth, k) in key.iter().enumerate() {
+            match node.child_index_borrowed(k).ok() {
+                Some(ix) => node = &node.children[ix].1,
+                None => return depth,
+            }
+        }
+        key.len()
+    }
+
+    /// Number of nodes a pruning removal of `key` detaches
+    pub(crate) fn removal_frees(&self, key: &[K]) -> usize {
+        // Depth of the deepest node on the path which stays after the removal
+        let mut kept = 0;
+        let mut node = self;
+        for (depth, k) in key.iter().enumerate() {
+            if node.value.is_some() || node.children.len() > 1 {
+                kept = depth;
+            }
+            match node.child_index_borrowed(k).ok() {
+                Some(ix) => node = &node.children[ix].1,
+                None => return 0,
+            }
+        }
+        if node.value.is_none() || !node.children.is_empty() {
+            return 0;
+        }
+        key.len() - kept
+    }
+
+    /// Stores in `out[slot]` the value of each `(key, slot)` of `keys`, which are sorted and
+    /// distinct, descending into each child once
+    pub(crate) fn values_mut<'a>(
+        &'a mut self,
+        keys: &mut [(&[K], usize)],
+        out: &mut [Option<&'a mut V>],
+    ) {
+        let TrieNode { value, children } = self;
+        let mut rest = keys;
+        // The empty key sorts first
+        if rest.first().is_some_and(|(key, _)| key.is_empty()) {
+            out[rest[0].1] = value.as_mut();
+            rest = &mut std::mem::take(&mut rest)[1..];
+        }
+        let mut children = children.iter_mut().peekable();
+        while let Some(&(first, _)) = rest.first() {
+            let edge = &first[0];
+            let end = rest.partition_point(|(key, _)| key[0] == *edge);
+            let (group, tail) = std::mem::take(&mut rest).split_at_mut(end);
+            rest = tail;
+            while children.next_if(|(k, _)| k < edge).is_some() {}
+            if let Some((_, child)) = children.next_if(|(k, _)| k == edge) {
+                for (key, _) in group.iter_mut() {
+                    *key = &key[1..];
+                }
+                child.values_mut(group, out);
+            }
+        }
+    }
+
+    /// Check that the children are sorted by edge key without duplicates
+    pub(crate) fn check_children(&self, depth: usize) -> Result<(), InvariantError> {
+        for (index, pair) in self.children.windows(2).enumerate() {
+            match pair[0].0.cmp(&pair[1].0) {
+                Ordering::Less => {}
+                Ordering::Equal => return Err(InvariantError::DuplicateEdge { depth, index }),
+                Ordering::Greater => return Err(InvariantError::UnsortedChildren { depth, index }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the node of a key with all its children, pruning the ancestors left empty
     pub fn remove_subtree<I: Iterator<Item = K>>(&mut self, mut key: Peekable<I>) {
         if let Some(next) = key.next() {
-            if let Some(ix) = self.children.binary_search_by_key(&&next, |(k, n)| k).ok() {
+            if let Ok(ix) = self.children.binary_search_by_key(&&next, |(k, _)| k) {
                 if key.peek().is_none() {
                     self.children.remove(ix);
                 } else {
-                    self.children[ix].1.remove_subtree(key);
+                    let child = &mut self.children[ix].1;
+                    child.remove_subtree(key);
+                    if child.is_empty() {
+                        self.children.remove(ix);
+                    }
                 }
             }
         }
     }
 
+    /// Remove the value of a key, and the nodes left without value nor children when `prune`
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I, prune: bool) -> Option<V> {
+        let mut path = Vec::new();
+        let mut node = &mut *self;
+        for k in key {
+            let ix = node.children.binary_search_by_key(&&k, |(c, _)| c).ok()?;
+            path.push(ix);
+            node = &mut node.children[ix].1;
+        }
+        let removed = node.value.take()?;
+        if prune && node.children.is_empty() && !path.is_empty() {
+            // Detach the chain below the deepest ancestor which is kept for its value or
+            // its other children
+            let mut cut = 0;
+            let mut node = &*self;
+            for (depth, &ix) in path.iter().enumerate() {
+                if node.value.is_some() || node.children.len() > 1 {
+                    cut = depth;
+                }
+                node = &node.children[ix].1;
+            }
+            let mut node = &mut *self;
+            for &ix in &path[..cut] {
+                node = &mut node.children[ix].1;
+            }
+            node.children.remove(path[cut]);
+        }
+        Some(removed)
+    }
+
+    /// Remove all the descendants without value nor children, returning the number removed
+    pub fn prune(&mut self) -> usize {
+        // Each frame holds a detached node with its edge, its children left to visit and the
+        // children kept so far
+        let children = std::mem::take(&mut self.children);
+        let capacity = children.len();
+        let mut stack = vec![(None, children.into_iter(), Vec::with_capacity(capacity))];
+        let mut removed = 0;
+        loop {
+            let (_, pending, _) = stack.last_mut().expect("the root is popped last");
+            if let Some((k, mut child)) = pending.next() {
+                let children = std::mem::take(&mut child.children);
+                let capacity = children.len();
+                stack.push((
+                    Some((k, child)),
+                    children.into_iter(),
+                    Vec::with_capacity(capacity),
+                ));
+                continue;
+            }
+            let (edge, _, kept) = stack.pop().expect("checked above");
+            let Some((k, mut node)) = edge else {
+                self.children = kept;
+                return removed;
+            };
+            node.children = kept;
+            if node.is_empty() {
+                removed += 1;
+            } else {
+                let (_, _, parent_kept) = stack.last_mut().expect("the root is popped last");
+                parent_kept.push((k, node));
+            }
+        }
+    }
+
+    /// Removes the values of the node and its descendants for which `pred` holds, given the
+    /// key of each value from `path`, and the nodes this leaves empty. Returns the number of
+    /// values removed, pushing their keys in ascending order to `removed` if given.
+    ///
+    /// The children of each node on the way down are detached and put back once visited, so
+    /// the depth of the keys is not bounded by the call stack.
+    pub(crate) fn remove_matching<F: FnMut(&[K], &V) -> bool>(
+        &mut self,
+        path: &mut Vec<K>,
+        pred: &mut F,
+        removed: &mut Option<Vec<Vec<K>>>,
+    ) -> usize {
+        let mut take_value = |node: &mut Self, path: &[K]| -> usize {
+            if !node.value.as_ref().is_some_and(|value| pred(path, value)) {
+                return 0;
+            }
+            node.value = None;
+            if let Some(removed) = removed {
+                removed.push(path.to_vec());
+            }
+            1
+        };
+        let count = take_value(self, path);
+        let children = std::mem::take(&mut self.children);
+        let capacity = children.len();
+        // Each frame holds the detached node with its edge, its children left to visit, the
+        // children kept so far and the number of values removed in its subtree
+        let mut stack = vec![(
+            None,
+            children.into_iter(),
+            Vec::with_capacity(capacity),
+            count,
+        )];
+        loop {
+            let (_, pending, _, _) = stack.last_mut().expect("the root is popped last");
+            if let Some((k, mut child)) = pending.next() {
+                path.push(k.clone());
+                let count = take_value(&mut child, path);
+                let children = std::mem::take(&mut child.children);
+                let capacity = children.len();
+                stack.push((
+                    Some((k, child)),
+                    children.into_iter(),
+                    Vec::with_capacity(capacity),
+                    count,
+                ));
+                continue;
+            }
+            let (edge, _, kept, count) = stack.pop().expect("checked above");
+            let Some((k, mut node)) = edge else {
+                self.children = kept;
+                return count;
+            };
+            node.children = kept;
+            path.pop();
+            let (_, _, parent_kept, parent_count) =
+                stack.last_mut().expect("the root is popped last");
+            *parent_count += count;
+            // Only prune the nodes emptied here, like `remove`
+            if count == 0 || !node.is_empty() {
+                parent_kept.push((k, node));
+            }
+        }
+    }
+
+    /// Sorts the children of every node left unsorted and releases their spare capacity
+    pub(crate) fn shrink(&mut self) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if !node.children.is_sorted_by(|(a, _), (b, _)| a <= b) {
+                node.children.sort_by(|(a, _), (b, _)| a.cmp(b));
+            }
+            node.children.shrink_to_fit();
+            stack.extend(node.children.iter_mut().map(|(_, child)| child));
+        }
+    }
+
+    /// Whether the node has neither a value nor children
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none() && self.children.is_empty()
+    }
+
     /// Recursively find a node searching through children
-    pub fn find_node<I: Iterator<Item = K>>(&self, mut key: I) -> Option<&Self> {
-        if let Some(p) = key.next() {
-            self.children
-                .binary_search_by_key(&&p, |(k, n)| k)
-                .ok() // each prefix must exist
-                .and_then(|f| self.children[f].1.find_node(key))
-        } else {
-            Some(self)
+    pub fn find_node<I: Iterator<Item = K>>(&self, key: I) -> Option<&Self> {
+        let mut node = self;
+        for p in key {
+            // each prefix must exist
+            let ix = node.children.binary_search_by_key(&&p, |(k, _)| k).ok()?;
+            node = &node.children[ix].1;
         }
+        Some(node)
     }
 
-    pub fn find_node_mut<I: Iterator<Item = K>>(&mut self, mut key: I) -> Option<&mut Self> {
-        if let Some(p) = key.next() {
-            self.children
-                .binary_search_by_key(&&p, |(k, n)| k)
-                .ok() // each prefix must exist
-                .and_then(|f| self.children[f].1.find_node_mut(key))
-        } else {
-            Some(self)
+    /// Same as `find_node`, with key elements borrowed from the `K` they stand for
+    pub(crate) fn find_node_borrowed<'q, Q, I>(&self, key: I) -> Option<&Self>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized + 'q,
+        I: Iterator<Item = &'q Q>,
+    {
+        let mut node = self;
+        for p in key {
+            let ix = node.child_index_borrowed(p).ok()?;
+            node = &node.children[ix].1;
+        }
+        Some(node)
+    }
+
+    /// Position of the child of edge `p`, or where it would be inserted
+    pub(crate) fn child_index_borrowed<Q: Ord + ?Sized>(&self, p: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.children.binary_search_by(|(k, _)| k.borrow().cmp(p))
+    }
+
+    pub fn find_node_mut<I: Iterator<Item = K>>(&mut self, key: I) -> Option<&mut Self> {
+        let mut node = self;
+        for p in key {
+            let ix = node.children.binary_search_by_key(&&p, |(k, _)| k).ok()?;
+            node = &mut node.children[ix].1;
         }
+        Some(node)
+    }
+
+    /// Same as `find_node_mut`, failing with the number of elements matched and the key length
+    pub(crate) fn find_node_mut_or_matched<I: Iterator<Item = K>>(
+        &mut self,
+        mut key: I,
+    ) -> Result<&mut Self, (usize, usize)> {
+        let mut node = self;
+        let mut matched = 0;
+        while let Some(p) = key.next() {
+            match node.children.binary_search_by_key(&&p, |(k, _)| k) {
+                Ok(ix) => node = &mut node.children[ix].1,
+                Err(_) => return Err((matched, matched + 1 + key.count())),
+            }
+            matched += 1;
+        }
+        Ok(node)
     }
 
     pub fn set_value(&mut self, value: V) {
@@ -100,3 +409,245 @@ impl<T: Eq + Ord + Clone, U> Default for TrieNode<T, U> {
         Self::new()
     }
 }
+
+impl<K: Eq + Ord + Clone, V: Clone> Clone for TrieNode<K, V> {
+    fn clone(&self) -> Self {
+        self.map_clone(&mut V::clone)
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> TrieNode<K, V> {
+    /// Copies the nodes with `f` applied to the values, with an explicit stack holding for
+    /// each level the source node, the index of its next child to copy and its copy under
+    /// construction
+    pub(crate) fn map_clone<W>(&self, f: &mut impl FnMut(&V) -> W) -> TrieNode<K, W> {
+        let mut shell = |node: &TrieNode<K, V>| TrieNode {
+            value: node.value.as_ref().map(&mut *f),
+            children: Vec::with_capacity(node.children.len()),
+        };
+        let mut stack = vec![(self, 0, shell(self))];
+        loop {
+            let (source, next, _) = stack.last_mut().expect("the root is popped last");
+            if let Some((_, child)) = source.children.get(*next) {
+                *next += 1;
+                stack.push((child, 0, shell(child)));
+                continue;
+            }
+            let (_, _, copy) = stack.pop().expect("checked above");
+            match stack.last_mut() {
+                Some((parent, next, parent_copy)) => {
+                    let key = parent.children[*next - 1].0.clone();
+                    parent_copy.children.push((key, copy));
+                }
+                None => return copy,
+            }
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Drop for TrieNode<K, V> {
+    /// Detaches the descendants before dropping them one at a time
+    fn drop(&mut self) {
+        let mut stack: Vec<TrieNode<K, V>> =
+            self.children.drain(..).map(|(_, child)| child).collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.children.drain(..).map(|(_, child)| child));
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for TrieNode<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        DebugNode {
+            node: self,
+            depth: 0,
+        }
+        .fmt(f)
+    }
+}
+
+/// Formats a node and its children until `DEBUG_MAX_DEPTH`
+struct DebugNode<'a, K: Eq + Ord + Clone, V> {
+    node: &'a TrieNode<K, V>,
+    depth: usize,
+}
+
+impl<K: Eq + Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for DebugNode<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = f.debug_struct("TrieNode");
+        out.field("value", &self.node.value);
+        if self.node.children.is_empty() {
+            return out.finish();
+        }
+        if self.depth >= DEBUG_MAX_DEPTH {
+            return out.finish_non_exhaustive();
+        }
+        let children: Vec<(&K, DebugNode<'_, K, V>)> = self
+            .node
+            .children
+            .iter()
+            .map(|(k, child)| {
+                let child = DebugNode {
+                    node: child,
+                    depth: self.depth + 1,
+                };
+                (k, child)
+            })
+            .collect();
+        out.field("children", &children).finish()
+    }
+}
+
+/// Read-only view of a node in the `Trie`
+///
+/// Exposes the value and the children of a node without giving access to the
+/// underlying storage, so the sorted order of the children cannot be broken.
+#[derive(Debug)]
+pub struct NodeRef<'a, K: Eq + Ord + Clone, V> {
+    node: &'a TrieNode<K, V>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> NodeRef<'a, K, V> {
+    pub(crate) fn new(node: &'a TrieNode<K, V>) -> Self {
+        NodeRef { node }
+    }
+
+    /// Value stored at this node, if any
+    pub fn value(&self) -> Option<&'a V> {
+        self.node.value.as_ref()
+    }
+
+    /// Whether a value is stored at this node
+    pub fn has_value(&self) -> bool {
+        self.node.value.is_some()
+    }
+
+    /// Number of direct children of this node
+    pub fn child_count(&self) -> usize {
+        self.node.children.len()
+    }
+
+    /// Whether this node has no children
+    pub fn is_leaf(&self) -> bool {
+        self.node.children.is_empty()
+    }
+
+    /// Child reached by following the edge `key`
+    pub fn child(&self, key: &K) -> Option<NodeRef<'a, K, V>> {
+        let children = &self.node.children;
+        children
+            .binary_search_by_key(&key, |(k, _)| k)
+            .ok()
+            .map(|ix| NodeRef::new(&children[ix].1))
+    }
+
+    /// Children of this node with their edge key, in ascending key order
+    pub fn children(&self) -> impl Iterator<Item = (&'a K, NodeRef<'a, K, V>)> + 'a {
+        self.node.children.iter().map(|(k, n)| (k, NodeRef::new(n)))
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Clone for NodeRef<'_, K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Copy for NodeRef<'_, K, V> {}
+
+/// Mutable view of a node in the `Trie`
+///
+/// Allows reading and replacing the value of a node, while the structure of the
+/// children can only be changed through the `Trie` API.
+#[derive(Debug)]
+pub struct NodeMut<'a, K: Eq + Ord + Clone, V> {
+    node: &'a mut TrieNode<K, V>,
+}
+
+impl<'a, K: Eq + Ord + Clone, V> NodeMut<'a, K, V> {
+    pub(crate) fn new(node: &'a mut TrieNode<K, V>) -> Self {
+        NodeMut { node }
+    }
+
+    /// Value stored at this node, if any
+    pub fn value(&self) -> Option<&V> {
+        self.node.value.as_ref()
+    }
+
+    /// Mutable reference to the value stored at this node, if any
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        self.node.value.as_mut()
+    }
+
+    /// Consumes the view, returning a mutable reference to the value with the lifetime of the node
+    pub fn into_value_mut(self) -> Option<&'a mut V> {
+        self.node.value.as_mut()
+    }
+
+    /// Stores a value at this node, returning the previous one
+    pub fn set_value(&mut self, value: V) -> Option<V> {
+        self.node.value.replace(value)
+    }
+
+    /// Removes the value stored at this node
+    pub fn take_value(&mut self) -> Option<V> {
+        self.node.value.take()
+    }
+
+    /// Whether a value is stored at this node
+    pub fn has_value(&self) -> bool {
+        self.node.value.is_some()
+    }
+
+    /// Number of direct children of this node
+    pub fn child_count(&self) -> usize {
+        self.node.children.len()
+    }
+
+    /// Whether this node has no children
+    pub fn is_leaf(&self) -> bool {
+        self.node.children.is_empty()
+    }
+
+    /// Child reached by following the edge `key`
+    pub fn child_mut(&mut self, key: &K) -> Option<NodeMut<'_, K, V>> {
+        let children = &mut self.node.children;
+        match children.binary_search_by_key(&key, |(k, _)| k) {
+            Ok(ix) => Some(NodeMut::new(&mut children[ix].1)),
+            Err(_) => None,
+        }
+    }
+
+    /// Read-only view of this node
+    pub fn as_node_ref(&self) -> NodeRef<'_, K, V> {
+        NodeRef::new(self.node)
+    }
+}
+
+/// Position of a node in the `Trie`, as the index of the child followed at each level
+///
+/// A handle is cheap to keep around while the `Trie` is borrowed elsewhere, and can be turned
+/// back into the node or its key. It is invalidated by structural changes of the `Trie`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NodeHandle {
+    path: Vec<usize>,
+}
+
+impl NodeHandle {
+    pub(crate) fn root() -> Self {
+        NodeHandle { path: Vec::new() }
+    }
+
+    pub(crate) fn push(&mut self, ix: usize) {
+        self.path.push(ix);
+    }
+
+    pub(crate) fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// Length of the key leading to the node
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+}
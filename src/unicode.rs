@@ -0,0 +1,81 @@
+//! Keys made of the extended grapheme clusters of a text
+//!
+//! A byte or `char` key splits what a reader sees as one character when it is made of several
+//! code points, like an emoji with a skin tone or a letter with a combining accent, so a prefix
+//! query can match half of it. Keying by grapheme clusters keeps them whole.
+
+use crate::trie::Trie;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl<V> Trie<String, V> {
+    /// Inserts a value under the grapheme clusters of `text`, returning the previous one
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_graphemes("e\u{301}t\u{e9}", 1);
+    /// assert_eq!(t.get_graphemes("e\u{301}t\u{e9}"), Some(&1));
+    /// // The accent is part of the first cluster, `e` alone is not a prefix
+    /// assert_eq!(t.longest_grapheme_prefix("e"), None);
+    /// ```
+    pub fn insert_graphemes(&mut self, text: &str, value: V) -> Option<V> {
+        self.insert(text.graphemes(true).map(String::from), value)
+    }
+
+    /// Value of the grapheme clusters of `text`
+    pub fn get_graphemes(&self, text: &str) -> Option<&V> {
+        self.get_borrowed(text.graphemes(true))
+    }
+
+    /// Removes the grapheme clusters of `text`, returning their value
+    pub fn remove_graphemes(&mut self, text: &str) -> Option<V> {
+        self.remove(text.graphemes(true).map(String::from))
+    }
+
+    /// Longest stored text `text` starts with, ending on a cluster boundary, with its value
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_graphemes("\u{1f44b}\u{1f3fd}", "wave");
+    /// assert_eq!(
+    ///     t.longest_grapheme_prefix("\u{1f44b}\u{1f3fd} hi"),
+    ///     Some(("\u{1f44b}\u{1f3fd}", &"wave"))
+    /// );
+    /// assert_eq!(t.longest_grapheme_prefix("\u{1f44b} hi"), None);
+    /// ```
+    pub fn longest_grapheme_prefix<'t>(&self, text: &'t str) -> Option<(&'t str, &V)> {
+        let (ix, value) = self.find_prefixes_borrowed(text.graphemes(true)).pop()?;
+        let end = text
+            .grapheme_indices(true)
+            .nth(ix + 1)
+            .map_or(text.len(), |(offset, _)| offset);
+        Some((&text[..end], value))
+    }
+
+    /// Stored texts starting with the grapheme clusters of `prefix`, in key order
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_graphemes("ca", 1);
+    /// t.insert_graphemes("caf\u{e9}", 2);
+    /// t.insert_graphemes("cafe\u{301}", 3);
+    /// let found: Vec<_> = t.grapheme_completions("caf").into_iter().map(|(_, v)| *v).collect();
+    /// assert_eq!(found, vec![3, 2]);
+    /// ```
+    pub fn grapheme_completions(&self, prefix: &str) -> Vec<(String, &V)> {
+        self.iter_prefix(prefix.graphemes(true).map(String::from))
+            .map(|(key, value)| (key.concat(), value))
+            .collect()
+    }
+}
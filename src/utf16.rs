@@ -0,0 +1,90 @@
+//! Keys made of UTF-16 code units, the string encoding of JavaScript and Windows
+//!
+//! A `Trie<u16, V>` is queried with the code units of the caller as is, without transcoding
+//! them to UTF-8. Code units are not characters: a character outside the Basic Multilingual
+//! Plane is a pair of surrogates, which the surrogate-aware queries never split.
+
+use crate::trie::Trie;
+
+fn is_high_surrogate(unit: u16) -> bool {
+    (0xd800..0xdc00).contains(&unit)
+}
+
+fn is_low_surrogate(unit: u16) -> bool {
+    (0xdc00..0xe000).contains(&unit)
+}
+
+/// Whether `units[..len]` ends between the two surrogates of a pair
+fn splits_pair(units: &[u16], len: usize) -> bool {
+    len > 0
+        && is_high_surrogate(units[len - 1])
+        && units.get(len).copied().is_some_and(is_low_surrogate)
+}
+
+impl<V> Trie<u16, V> {
+    /// Inserts a value under the UTF-16 encoding of `text`, returning the previous one
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_str_utf16("h\u{e9}llo", 1);
+    /// let query: Vec<u16> = "h\u{e9}llo".encode_utf16().collect();
+    /// assert_eq!(t.get_utf16(&query), Some(&1));
+    /// ```
+    pub fn insert_str_utf16(&mut self, text: &str, value: V) -> Option<V> {
+        self.insert(text.encode_utf16(), value)
+    }
+
+    /// Value of a key given as code units
+    pub fn get_utf16(&self, units: &[u16]) -> Option<&V> {
+        self.get(units.iter().copied())
+    }
+
+    /// Length in code units and value of the longest key `units` starts with, never ending
+    /// between the two surrogates of a pair
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_str_utf16("a", "a");
+    /// // Half of U+1F600, a key only a raw code unit insertion can create
+    /// t.insert("a\u{1f600}".encode_utf16().take(2), "broken");
+    ///
+    /// let query: Vec<u16> = "a\u{1f600}!".encode_utf16().collect();
+    /// assert_eq!(t.longest_prefix_utf16(&query), Some((1, &"a")));
+    /// ```
+    pub fn longest_prefix_utf16(&self, units: &[u16]) -> Option<(usize, &V)> {
+        self.find_prefixes(units.iter().copied())
+            .into_iter()
+            .map(|(ix, value)| (ix + 1, value))
+            .rfind(|(len, _)| !splits_pair(units, *len))
+    }
+
+    /// Keys starting with `prefix`, decoded to strings, with their values in key order
+    ///
+    /// Keys that are not valid UTF-16, such as a lone surrogate, are decoded with replacement
+    /// characters.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ptrie::Trie;
+    ///
+    /// let mut t = Trie::new();
+    /// t.insert_str_utf16("caf\u{e9}", 1);
+    /// t.insert_str_utf16("cab", 2);
+    /// let prefix: Vec<u16> = "caf".encode_utf16().collect();
+    /// assert_eq!(t.completions_utf16(&prefix), vec![("caf\u{e9}".to_string(), &1)]);
+    /// ```
+    pub fn completions_utf16(&self, prefix: &[u16]) -> Vec<(String, &V)> {
+        self.iter_prefix(prefix.iter().copied())
+            .map(|(key, value)| (String::from_utf16_lossy(&key), value))
+            .collect()
+    }
+}
@@ -0,0 +1,134 @@
+//! Vocabulary for tokenizers, encoding text by greedy longest match
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+use std::collections::HashMap;
+
+/// What to do with text that does not start with any token of the vocabulary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownPolicy {
+    /// Skip the unknown character
+    #[default]
+    Skip,
+    /// Emit this id for the unknown character
+    Token(u32),
+    /// Fail the encoding
+    Error,
+}
+
+/// Vocabulary mapping tokens to ids, and ids back to tokens
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::vocab::{UnknownPolicy, Vocab};
+///
+/// let vocab: Vocab = [("un", 1), ("unhappy", 2), ("happy", 3), ("ness", 4), ("[UNK]", 0)]
+///     .into_iter()
+///     .collect();
+/// let vocab = vocab.with_unknown(UnknownPolicy::Token(0));
+///
+/// assert_eq!(vocab.encode("unhappyness").unwrap(), vec![2, 4]);
+/// assert_eq!(vocab.encode("un happy!").unwrap(), vec![1, 0, 3, 0]);
+/// assert_eq!(vocab.decode(&[2, 4]), Some("unhappyness".to_string()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Vocab {
+    tokens: Trie<u8, u32>,
+    ids: HashMap<u32, String>,
+    unknown: UnknownPolicy,
+}
+
+impl Vocab {
+    /// Creates an empty vocabulary, skipping unknown characters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the policy for text not matching any token
+    pub fn with_unknown(mut self, policy: UnknownPolicy) -> Self {
+        self.unknown = policy;
+        self
+    }
+
+    /// Adds a token, returning the id previously assigned to it
+    ///
+    /// Each token has one id and each id one token, so the previous id of the token and the
+    /// previous token of the id are forgotten.
+    pub fn insert(&mut self, token: &str, id: u32) -> Option<u32> {
+        let previous = self.tokens.insert(token.bytes(), id);
+        if let Some(old_id) = previous.filter(|old_id| *old_id != id) {
+            self.ids.remove(&old_id);
+        }
+        if let Some(old_token) = self.ids.insert(id, token.to_string()) {
+            if old_token != token {
+                self.tokens.remove(old_token.bytes());
+            }
+        }
+        previous
+    }
+
+    /// Id of a token
+    pub fn id(&self, token: &str) -> Option<u32> {
+        self.tokens.get(token.bytes()).copied()
+    }
+
+    /// Token of an id
+    pub fn token(&self, id: u32) -> Option<&str> {
+        self.ids.get(&id).map(String::as_str)
+    }
+
+    /// Splits `text` in ids by repeatedly taking the longest token it starts with
+    ///
+    /// Fails with `TrieError::NotFound` on unknown text when the policy is `UnknownPolicy::Error`.
+    pub fn encode(&self, text: &str) -> Result<Vec<u32>, TrieError> {
+        let mut ids = Vec::new();
+        let mut pos = 0;
+        while pos < text.len() {
+            let rest = &text[pos..];
+            if let Some((ix, id)) = self.tokens.find_prefixes(rest.bytes()).pop() {
+                ids.push(*id);
+                pos += ix + 1;
+                continue;
+            }
+            match self.unknown {
+                UnknownPolicy::Skip => {}
+                UnknownPolicy::Token(id) => ids.push(id),
+                UnknownPolicy::Error => {
+                    return Err(TrieError::NotFound(format!(
+                        "No token matches the text at byte {}: {:?}",
+                        pos,
+                        rest.chars().take(16).collect::<String>()
+                    )))
+                }
+            }
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+        }
+        Ok(ids)
+    }
+
+    /// Concatenates the tokens of `ids`, `None` if an id is not in the vocabulary
+    pub fn decode(&self, ids: &[u32]) -> Option<String> {
+        ids.iter().map(|id| self.token(*id)).collect()
+    }
+
+    /// Number of tokens in the vocabulary
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Checks if the vocabulary is empty
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+impl<T: AsRef<str>> FromIterator<(T, u32)> for Vocab {
+    fn from_iter<I: IntoIterator<Item = (T, u32)>>(iter: I) -> Self {
+        let mut vocab = Vocab::new();
+        for (token, id) in iter {
+            vocab.insert(token.as_ref(), id);
+        }
+        vocab
+    }
+}
@@ -0,0 +1,72 @@
+//! JavaScript bindings for WebAssembly builds, with string keys and JSON values
+
+use crate::trie::Trie;
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// Trie exported to JavaScript as `Trie`, keyed by the UTF-8 bytes of strings
+///
+/// Values cross the boundary as JSON text, so any JSON-serializable JavaScript value can be
+/// stored with `JSON.stringify` and read back with `JSON.parse`.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::wasm::WasmTrie;
+///
+/// let mut trie = WasmTrie::new();
+/// trie.insert("car", r#"{"id": 1}"#).unwrap();
+/// trie.insert("cart", "2").unwrap();
+/// assert!(trie.insert("cat", "{oops").is_err());
+///
+/// assert_eq!(trie.get("car"), Some(r#"{"id":1}"#.to_string()));
+/// assert_eq!(trie.longest_prefix("carts"), Some("2".to_string()));
+/// assert_eq!(trie.completions("ca", 10), vec!["car", "cart"]);
+/// ```
+#[wasm_bindgen(js_name = Trie)]
+#[derive(Debug, Clone, Default)]
+pub struct WasmTrie {
+    trie: Trie<u8, Value>,
+}
+
+#[wasm_bindgen(js_class = Trie)]
+impl WasmTrie {
+    /// Creates an empty trie
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        WasmTrie { trie: Trie::new() }
+    }
+
+    /// Stores the JSON value of a key, failing with a message when `json` is not valid JSON
+    pub fn insert(&mut self, key: &str, json: &str) -> Result<(), String> {
+        let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        self.trie.insert(key.bytes(), value);
+        Ok(())
+    }
+
+    /// JSON value of exactly this key
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.trie.get(key.bytes()).map(Value::to_string)
+    }
+
+    /// Removes a key, returning whether it was present
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.trie.remove(key.bytes()).is_some()
+    }
+
+    /// JSON value of the longest key `text` starts with
+    #[wasm_bindgen(js_name = longestPrefix)]
+    pub fn longest_prefix(&self, text: &str) -> Option<String> {
+        self.trie.find_longest_prefix(text.bytes()).map(Value::to_string)
+    }
+
+    /// Up to `limit` keys starting with `prefix`, in lexicographic order
+    pub fn completions(&self, prefix: &str, limit: usize) -> Vec<String> {
+        self.trie
+            .complete_page(prefix.bytes(), None, limit)
+            .entries
+            .into_iter()
+            .map(|(key, _)| String::from_utf8_lossy(&key).into_owned())
+            .collect()
+    }
+}
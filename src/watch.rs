@@ -0,0 +1,172 @@
+//! Change notifications for the keys under watched prefixes
+
+use crate::trie::Trie;
+use std::collections::{vec_deque, BTreeMap, VecDeque};
+
+/// Identifier of a watch, returned by `WatchedTrie::watch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WatchId(u64);
+
+/// What happened to a watched key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Inserted,
+    /// The key already had a value, which was replaced
+    Replaced,
+    Removed,
+}
+
+/// Change of a key under the prefix of a watch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<K> {
+    pub watch: WatchId,
+    pub kind: ChangeKind,
+    pub key: Vec<K>,
+}
+
+/// Trie queueing a `Change` for each watch whose prefix starts the key of an insertion or a
+/// removal
+///
+/// Changes are queued in the order of the mutations and read with `drain_changes`, so a service
+/// can update its derived state incrementally instead of diffing snapshots. Reads go through
+/// `trie`.
+///
+/// # Example
+///
+/// ```rust
+/// use ptrie::watch::{ChangeKind, WatchedTrie};
+///
+/// let mut routes = WatchedTrie::new();
+/// let api = routes.watch("/api/".bytes());
+/// routes.insert("/api/users".bytes(), 1);
+/// routes.insert("/static/app.js".bytes(), 2);
+/// routes.insert("/api/users".bytes(), 3);
+///
+/// let changes: Vec<_> = routes.drain_changes().map(|c| (c.watch, c.kind)).collect();
+/// assert_eq!(changes, vec![(api, ChangeKind::Inserted), (api, ChangeKind::Replaced)]);
+/// assert_eq!(routes.trie().get("/api/users".bytes()), Some(&3));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatchedTrie<K: Eq + Ord + Clone, V> {
+    trie: Trie<K, V>,
+    /// Watches by prefix, to find those of a key with a prefix search
+    watches: Trie<K, Vec<WatchId>>,
+    prefixes: BTreeMap<WatchId, Vec<K>>,
+    next_id: u64,
+    changes: VecDeque<Change<K>>,
+}
+
+impl<K: Eq + Ord + Clone, V> WatchedTrie<K, V> {
+    /// Creates an empty trie without watches
+    pub fn new() -> Self {
+        WatchedTrie {
+            trie: Trie::new(),
+            watches: Trie::new(),
+            prefixes: BTreeMap::new(),
+            next_id: 0,
+            changes: VecDeque::new(),
+        }
+    }
+
+    /// The entries, for lookups
+    pub fn trie(&self) -> &Trie<K, V> {
+        &self.trie
+    }
+
+    /// Starts queueing the changes of the keys starting with `prefix`
+    ///
+    /// The empty prefix watches every key.
+    pub fn watch<I: Iterator<Item = K>>(&mut self, prefix: I) -> WatchId {
+        let prefix: Vec<K> = prefix.collect();
+        let id = WatchId(self.next_id);
+        self.next_id += 1;
+        self.watches
+            .get_or_insert_with(prefix.iter().cloned(), Vec::new)
+            .push(id);
+        self.prefixes.insert(id, prefix);
+        id
+    }
+
+    /// Stops a watch, returning whether it existed
+    ///
+    /// Its changes already queued are kept.
+    pub fn unwatch(&mut self, id: WatchId) -> bool {
+        let Some(prefix) = self.prefixes.remove(&id) else {
+            return false;
+        };
+        if let Some(ids) = self.watches.get_mut(prefix.iter().cloned()) {
+            ids.retain(|watch| *watch != id);
+            if ids.is_empty() {
+                self.watches.remove(prefix.into_iter());
+            }
+        }
+        true
+    }
+
+    /// Inserts a value, returning the previous one
+    pub fn insert<I: Iterator<Item = K>>(&mut self, key: I, value: V) -> Option<V> {
+        let key: Vec<K> = key.collect();
+        let previous = self.trie.insert(key.iter().cloned(), value);
+        let kind = match previous {
+            Some(_) => ChangeKind::Replaced,
+            None => ChangeKind::Inserted,
+        };
+        self.notify(key, kind);
+        previous
+    }
+
+    /// Removes a key, returning its value
+    pub fn remove<I: Iterator<Item = K>>(&mut self, key: I) -> Option<V> {
+        let key: Vec<K> = key.collect();
+        let value = self.trie.remove(key.iter().cloned())?;
+        self.notify(key, ChangeKind::Removed);
+        Some(value)
+    }
+
+    /// Removes every key starting with `prefix`, queueing a removal for each of them
+    pub fn remove_subtree<I: Iterator<Item = K>>(&mut self, prefix: I) {
+        let prefix: Vec<K> = prefix.collect();
+        let removed: Vec<Vec<K>> = self
+            .trie
+            .iter_prefix(prefix.iter().cloned())
+            .map(|(key, _)| key)
+            .collect();
+        self.trie.remove_subtree(prefix.into_iter());
+        for key in removed {
+            self.notify(key, ChangeKind::Removed);
+        }
+    }
+
+    /// Takes the queued changes, oldest first
+    pub fn drain_changes(&mut self) -> vec_deque::Drain<'_, Change<K>> {
+        self.changes.drain(..)
+    }
+
+    /// Queues a change for the watches of every prefix of `key`
+    fn notify(&mut self, key: Vec<K>, kind: ChangeKind) {
+        // Prefix searches skip the empty key, which watches everything
+        let everything = self.watches.get(std::iter::empty()).into_iter().flatten();
+        let ids: Vec<WatchId> = everything
+            .chain(
+                self.watches
+                    .find_prefixes(key.iter().cloned())
+                    .into_iter()
+                    .flat_map(|(_, ids)| ids),
+            )
+            .copied()
+            .collect();
+        for watch in ids {
+            self.changes.push_back(Change {
+                watch,
+                kind,
+                key: key.clone(),
+            });
+        }
+    }
+}
+
+impl<K: Eq + Ord + Clone, V> Default for WatchedTrie<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -57,6 +57,71 @@ mod tests {
         assert!(!t.contains_key(data));
     }
 
+    #[test]
+    fn remove_prunes_empty_chains() {
+        let mut t = Trie::new();
+        t.insert("abc".bytes(), 1);
+        t.insert("abd".bytes(), 2);
+        t.insert("a".bytes(), 3);
+
+        assert_eq!(t.remove("abc".bytes()), Some(1));
+        assert!(t.node("ab".bytes()).is_some());
+        assert_eq!(t.remove("abd".bytes()), Some(2));
+        assert!(t.node("ab".bytes()).is_none());
+        assert_eq!(t.remove("a".bytes()), Some(3));
+        assert!(t.is_empty());
+        assert!(t.root().is_leaf());
+
+        t.insert("xyz".bytes(), 4);
+        t.remove_no_prune("xyz".bytes());
+        t.insert("xy".bytes(), 5);
+        assert_eq!(t.prune(), 1);
+        assert!(t.root().child(&b'x').is_some());
+    }
+
+    #[cfg(feature = "model")]
+    #[test]
+    fn matches_btreemap_model() {
+        use ptrie::model::{random_ops, ModelChecker};
+
+        for seed in 0..20 {
+            ModelChecker::new().run(&random_ops(seed, 2000, b"abcd", 6));
+        }
+    }
+
+    #[test]
+    fn errors_carry_context() {
+        use ptrie::error::TrieError;
+        use std::error::Error;
+
+        let mut t = Trie::new();
+        t.insert("abc".bytes(), 1);
+        let err = t.set_value("abxyz".bytes(), 2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Key not found: 2 of its 5 elements matched"
+        );
+        assert!(err.source().is_none());
+
+        let err = TrieError::from(std::io::Error::other("disk full"));
+        assert_eq!(err.source().unwrap().to_string(), "disk full");
+    }
+
+    #[test]
+    fn try_insert_keeps_first_value() {
+        let mut t = Trie::builder().insertion_order(true).build();
+        *t.try_insert("b".bytes(), 1).unwrap() += 1;
+        t.insert("a".bytes(), 3);
+
+        let err = t.try_insert("b".bytes(), 10).unwrap_err();
+        assert_eq!((*err.existing, err.value), (2, 10));
+        *err.existing = 5;
+
+        assert_eq!(t.get("b".bytes()), Some(&5));
+        let order: Vec<_> = t.iter_insertion_order().unwrap().map(|(k, _)| k.to_vec()).collect();
+        assert_eq!(order, vec![b"b".to_vec(), b"a".to_vec()]);
+    }
+
     #[test]
     fn find_prefixes() {
         let mut trie = Trie::new();
@@ -64,9 +129,607 @@ mod tests {
         trie.insert("abcd".bytes(), "ABCD");
         trie.insert("abcde".bytes(), "ABCDE");
         let prefixes = trie.find_prefixes("abcd".bytes());
-        assert_eq!(prefixes, vec![&"ABC", &"ABCD"]);
-        assert_eq!(trie.find_prefixes("efghij".bytes()), Vec::<&&str>::new());
-        assert_eq!(trie.find_prefixes("abz".bytes()), Vec::<&&str>::new());
+        assert_eq!(prefixes, vec![(2, &"ABC"), (3, &"ABCD")]);
+        assert_eq!(trie.find_prefixes("efghij".bytes()), vec![]);
+        assert_eq!(trie.find_prefixes("abz".bytes()), vec![]);
+    }
+
+    #[test]
+    fn zero_length_queries() {
+        let mut t = Trie::new();
+        t.insert("a".bytes(), 1);
+        assert!(!t.contains_key("".bytes()));
+        assert_eq!(t.get("".bytes()), None);
+        assert_eq!(t.find_postfixes("".bytes()), vec![&1]);
+
+        t.insert("".bytes(), 0);
+        assert!(t.contains_key("".bytes()));
+        assert_eq!(t.get("".bytes()), Some(&0));
+        assert_eq!(t.find_prefixes("".bytes()), vec![]);
+        assert_eq!(t.find_prefixes("ab".bytes()), vec![(0, &1)]);
+        assert_eq!(t.find_longest_prefix("".bytes()), None);
+        assert_eq!(t.find_longest_prefix("b".bytes()), None);
+        assert_eq!(t.find_postfixes("".bytes()), vec![&0, &1]);
+
+        t.remove_subtree("".bytes());
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn deep_trie_debug_clone_drop() {
+        let mut t = Trie::new();
+        t.insert(std::iter::repeat_n(0u8, 200_000), 1);
+        let copy = t.clone();
+        assert_eq!(copy.get(std::iter::repeat_n(0u8, 200_000)), Some(&1));
+        assert!(format!("{:?}", copy).contains(".."));
+        drop(t);
+    }
+
+    #[test]
+    fn deep_trie_removals() {
+        let mut t = Trie::new();
+        t.insert(std::iter::repeat_n(0u8, 200_000), 1);
+        t.insert(std::iter::repeat_n(0u8, 100_000), 2);
+        assert_eq!(t.remove_matching(|_, &v| v == 1), 1);
+        assert_eq!(t.get(std::iter::repeat_n(0u8, 100_000)), Some(&2));
+        assert!(t.get(std::iter::repeat_n(0u8, 100_001)).is_none());
+        let mut node = t.root();
+        for _ in 0..100_000 {
+            node = node.child(&0).unwrap();
+        }
+        assert!(node.is_leaf());
+        assert_eq!(t.remove_matching(|_, _| true), 1);
+        assert!(t.is_empty());
+
+        t.insert(std::iter::repeat_n(0u8, 200_000), 1);
+        t.remove_no_prune(std::iter::repeat_n(0u8, 200_000));
+        assert_eq!(t.prune(), 200_000);
+        assert!(t.is_empty());
+
+        t.insert(std::iter::repeat_n(0u8, 200_000), 1);
+        t.insert(std::iter::repeat_n(0u8, 1), 1);
+        assert_eq!(t.dedup_descendant_values(|a, b| a == b), 1);
+        assert_eq!(t.iter().count(), 1);
+        assert!(t.root().child(&0).unwrap().is_leaf());
+    }
+
+    #[test]
+    fn deep_trie_templates() {
+        let mut t = Trie::new();
+        t.insert(std::iter::repeat_n(0u8, 200_000), ());
+        t.insert(std::iter::repeat_n(0u8, 199_999).chain([1]), ());
+        let templates = t.templates(2, 10);
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].prefix.len(), 199_999);
+        assert_eq!(templates[0].count, 2);
+    }
+
+    #[test]
+    fn dump_sketches_subtree() {
+        use ptrie::dump::DumpOptions;
+
+        let mut t = Trie::new();
+        for key in ["ab", "abc", "ad", "b"] {
+            t.insert(key.chars(), key.len());
+        }
+        let mut out = Vec::new();
+        t.dump("".chars(), &DumpOptions::default(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[]\n\
+             |-- 'a'\n\
+             |   |-- 'b' = 2\n\
+             |   |   `-- 'c' = 3\n\
+             |   `-- 'd' = 2\n\
+             `-- 'b' = 1\n"
+        );
+
+        let mut out = Vec::new();
+        let options = DumpOptions {
+            show_values: false,
+            ..DumpOptions::default()
+        };
+        t.dump("z".chars(), &options, &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn set_algebra_matches_key_sets() {
+        let a: Trie<u8, u32> = ["", "a", "ab", "abc", "b"].iter().map(|k| (k.bytes(), 1)).collect();
+        let b: Trie<u8, u32> = ["a", "abc", "abd", "c"].iter().map(|k| (k.bytes(), 10)).collect();
+        let keys = |t: &Trie<u8, u32>| -> Vec<(String, u32)> {
+            t.iter().map(|(k, v)| (String::from_utf8(k).unwrap(), *v)).collect()
+        };
+
+        let union = a.union_with(&b, |x, y| x + y);
+        assert_eq!(
+            keys(&union),
+            vec![
+                ("".to_string(), 1),
+                ("a".to_string(), 11),
+                ("ab".to_string(), 1),
+                ("abc".to_string(), 11),
+                ("abd".to_string(), 10),
+                ("b".to_string(), 1),
+                ("c".to_string(), 10),
+            ]
+        );
+        assert!(union.validate().is_ok());
+
+        let both = a.intersect_with(&b, |x, y| x * y);
+        assert_eq!(
+            keys(&both),
+            vec![("a".to_string(), 10), ("abc".to_string(), 10)]
+        );
+
+        let only_a = a.difference(&b);
+        assert_eq!(
+            keys(&only_a),
+            vec![
+                ("".to_string(), 1),
+                ("ab".to_string(), 1),
+                ("b".to_string(), 1)
+            ]
+        );
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn watch_queues_changes_under_prefixes() {
+        use ptrie::watch::{ChangeKind, WatchedTrie};
+
+        let mut t = WatchedTrie::new();
+        let all = t.watch("".bytes());
+        let api = t.watch("/api".bytes());
+        t.insert("/api/a".bytes(), 1);
+        t.insert("/b".bytes(), 2);
+        t.insert("/api/c".bytes(), 3);
+        assert!(t.unwatch(all));
+        assert!(!t.unwatch(all));
+        t.remove_subtree("/".bytes());
+        assert_eq!(t.remove("/b".bytes()), None);
+
+        let changes: Vec<_> = t
+            .drain_changes()
+            .map(|c| (c.watch, c.kind, String::from_utf8(c.key).unwrap()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (all, ChangeKind::Inserted, "/api/a".to_string()),
+                (api, ChangeKind::Inserted, "/api/a".to_string()),
+                (all, ChangeKind::Inserted, "/b".to_string()),
+                (all, ChangeKind::Inserted, "/api/c".to_string()),
+                (api, ChangeKind::Inserted, "/api/c".to_string()),
+                (api, ChangeKind::Removed, "/api/a".to_string()),
+                (api, ChangeKind::Removed, "/api/c".to_string()),
+            ]
+        );
+        assert!(t.trie().is_empty());
+        assert_eq!(t.drain_changes().count(), 0);
+    }
+
+    #[test]
+    fn lazy_trie_loads_and_evicts_subtrees() {
+        use ptrie::error::TrieError;
+        use ptrie::lazy::LazyTrie;
+
+        let mut loads = 0;
+        let loader = |prefix: &[u8]| {
+            loads += 1;
+            if prefix == b"zz" {
+                return Err(TrieError::NotFound("shard zz".to_string()));
+            }
+            let mut shard = Trie::new();
+            for suffix in ["", "1", "2"] {
+                let key = [prefix, suffix.as_bytes()].concat();
+                let len = key.len();
+                shard.insert(key.into_iter(), len);
+            }
+            Ok(shard)
+        };
+
+        let mut t = LazyTrie::new(loader, 2, 2);
+        assert_eq!(t.get("ab1".bytes()).unwrap(), Some(&3));
+        assert_eq!(t.get("cd".bytes()).unwrap(), Some(&2));
+        assert_eq!(t.get("ab2".bytes()).unwrap(), Some(&3));
+        assert_eq!(t.get("ef1".bytes()).unwrap(), Some(&3));
+        assert_eq!(t.resident(), 2);
+        assert!(!t.evict("cd".bytes()));
+        assert!(t.evict("ab9".bytes()));
+        assert!(t.contains_key("zz1".bytes()).is_err());
+        assert_eq!(t.resident(), 1);
+        drop(t);
+        assert_eq!(loads, 4);
+    }
+
+    #[test]
+    fn journal_rolls_back_nested_batches() {
+        use ptrie::journal::JournaledTrie;
+
+        let mut t = JournaledTrie::new();
+        t.insert("a".bytes(), 1);
+        t.insert("ab".bytes(), 2);
+        let before = t.trie().to_debug_string();
+
+        t.begin();
+        t.insert("a".bytes(), 10);
+        t.begin();
+        t.remove_subtree("a".bytes());
+        t.insert("c".bytes(), 3);
+        assert!(t.commit());
+        assert_eq!(t.depth(), 1);
+        assert_eq!(t.trie().to_debug_string(), "\"c\" => 3\n");
+        assert!(t.rollback());
+        assert_eq!(t.trie().to_debug_string(), before);
+
+        t.begin();
+        t.remove("ab".bytes());
+        assert!(t.commit());
+        assert!(!t.rollback());
+        assert!(!t.trie().contains_key("ab".bytes()));
+    }
+
+    #[test]
+    fn bloom_trie_rejects_misses() {
+        use ptrie::bloom::BloomTrie;
+
+        let mut t = BloomTrie::with_capacity(200, 0.01);
+        for i in 0..200 {
+            t.insert(format!("key{}", i).bytes(), i);
+        }
+        assert!((0..200).all(|i| t.get(format!("key{}", i).bytes()) == Some(&i)));
+        assert!((0..200).all(|i| !t.contains_key(format!("miss{}", i).bytes())));
+
+        assert_eq!(t.remove("key7".bytes()), Some(7));
+        t.rebuild();
+        assert!(!t.contains_key("key7".bytes()));
+        assert_eq!(t.trie().iter_prefix("key1".bytes()).count(), 111);
+    }
+
+    #[test]
+    fn ordered_keys_follow_domain_order() {
+        use ptrie::ordering::{ordered, Descending, Ordered};
+
+        let mut t: Trie<Ordered<u8, Descending>, usize> = Trie::new();
+        for (i, word) in ["a", "ab", "b", "ba"].iter().enumerate() {
+            t.insert(ordered(word.bytes()), i);
+        }
+        let values: Vec<usize> = t.iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![2, 3, 0, 1]);
+        assert_eq!(t.rank(ordered("a".bytes())), 2);
+        assert_eq!(t.find_longest_prefix(ordered("bz".bytes())), Some(&2));
+    }
+
+    #[test]
+    fn hash_trie_prunes_on_remove() {
+        use ptrie::hash_trie::HashTrie;
+
+        let mut t = HashTrie::new();
+        for (i, key) in ["", "a", "abc", "abd", "xyz"].iter().enumerate() {
+            t.insert(key.bytes(), i);
+        }
+        assert_eq!(t.len(), 5);
+        assert_eq!(t.remove("xyz".bytes()), Some(4));
+        assert_eq!(t.remove("xy".bytes()), None);
+        assert_eq!(t.remove("abc".bytes()), Some(2));
+        assert_eq!(t.remove("".bytes()), Some(0));
+        *t.get_mut("abd".bytes()).unwrap() += 10;
+        assert_eq!(t.find_longest_prefix("abdz".bytes()), Some((3, &13)));
+
+        let mut entries: Vec<_> = t.iter().map(|(k, v)| (k, *v)).collect();
+        entries.sort();
+        assert_eq!(entries, vec![(b"a".to_vec(), 1), (b"abd".to_vec(), 13)]);
+        assert_eq!(t.len(), 2);
+
+        let mut deep = HashTrie::new();
+        deep.insert(std::iter::repeat_n(0u8, 200_000), ());
+        drop(deep);
+    }
+
+    #[test]
+    fn trie_set_algebra() {
+        use ptrie::set::TrieSet;
+
+        let a: TrieSet<char> = ["ab", "abc", "b"].iter().map(|k| k.chars()).collect();
+        let b: TrieSet<char> = ["abc", "c"].iter().map(|k| k.chars()).collect();
+        let strings = |s: &TrieSet<char>| -> Vec<String> {
+            s.iter().map(|k| k.into_iter().collect()).collect()
+        };
+        assert_eq!(strings(&a.union(&b)), vec!["ab", "abc", "b", "c"]);
+        assert_eq!(strings(&a.intersection(&b)), vec!["abc"]);
+        assert_eq!(strings(&a.difference(&b)), vec!["ab", "b"]);
+        assert_eq!(a.prefix_lens("abcd".chars()), vec![2, 3]);
+    }
+
+    #[test]
+    fn int_keys_iterate_in_numeric_order() {
+        use ptrie::int_key::{decode_key, encode_key};
+
+        let values = [i64::MIN, -300, -1, 0, 1, 255, 256, i64::MAX];
+        let mut t = Trie::new();
+        for v in values.iter().rev() {
+            t.insert(encode_key(v), ());
+        }
+        let decoded: Vec<i64> = t.iter().map(|(k, _)| decode_key(&k).unwrap()).collect();
+        assert_eq!(decoded, values);
+        assert_eq!(decode_key::<u16>(&[1]), None);
+        assert_eq!(decode_key::<(u8, u16)>(&[1, 2, 3]), Some((1, 0x0203)));
+    }
+
+    #[test]
+    fn remove_prefixes_keeps_insertion_order_in_sync() {
+        let mut t = Trie::builder().insertion_order(true).build();
+        for key in ["b/2", "a/1", "c", "a/2", "b/1", "ab"] {
+            t.insert(key.bytes(), ());
+        }
+        assert_eq!(t.remove_prefixes(["b/", "a/", "a/1"].map(str::bytes)), 4);
+        let order: Vec<&[u8]> = t.iter_insertion_order().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(order, vec![b"c".as_slice(), b"ab"]);
+        assert!(t.validate().is_ok());
+        assert_eq!(t.remove_prefixes([std::iter::empty()]), 2);
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn memory_budget_evicts_oldest_keys() {
+        use ptrie::builder::{node_footprint, BudgetPolicy};
+
+        let node = node_footprint::<u8, u32>();
+        let mut t = Trie::builder()
+            .memory_budget(6 * node, BudgetPolicy::EvictOldest)
+            .build();
+        t.insert_checked("abc".bytes(), 1).unwrap();
+        t.insert_checked("abd".bytes(), 2).unwrap();
+        t.insert_checked("xy".bytes(), 3).unwrap();
+        // Evicting "abc" frees a single node, "abd" goes too
+        t.insert_checked("pqr".bytes(), 4).unwrap();
+        let keys: Vec<Vec<u8>> = t.iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![b"pqr".to_vec(), b"xy".to_vec()]);
+        assert_eq!(t.root().child_count(), 2);
+
+        t.remove("xy".bytes());
+        t.insert_checked("pqs".bytes(), 5).unwrap();
+        assert!(t.contains_key("pqr".bytes()));
+        assert!(t.insert_checked("abcdefg".bytes(), 6).is_err());
+        assert_eq!(t.iter().count(), 2);
+    }
+
+    #[test]
+    fn memory_budget_counts_nodes_of_plain_inserts() {
+        use ptrie::builder::{node_footprint, BudgetPolicy};
+
+        let node = node_footprint::<u8, u32>();
+        let mut t = Trie::builder().memory_budget(4 * node, BudgetPolicy::Reject).build();
+        t.insert_checked("a".bytes(), 1).unwrap();
+        t.insert("bcd".bytes(), 2);
+        assert!(t.insert_checked("e".bytes(), 3).is_err());
+        t.remove("bcd".bytes());
+        assert!(t.insert_checked("e".bytes(), 3).is_ok());
+    }
+
+    #[test]
+    fn lending_iter_matches_iter() {
+        let mut t = Trie::new();
+        for (i, key) in ["", "b", "abc", "ab", "a", "bcd", "ba"].iter().enumerate() {
+            t.insert(key.bytes(), i);
+        }
+        t.remove_no_prune("abc".bytes());
+        let mut lent = Vec::new();
+        let mut entries = t.iter_lending();
+        while let Some((key, value)) = entries.next_ref() {
+            lent.push((key.to_vec(), value));
+        }
+        assert_eq!(lent, t.iter().collect::<Vec<_>>());
+        assert!(entries.next_ref().is_none());
+    }
+
+    #[test]
+    fn get_disjoint_mut_reaches_nested_keys() {
+        let mut t = Trie::new();
+        for (i, key) in ["", "a", "ab", "abc", "b"].iter().enumerate() {
+            t.insert(key.bytes(), i);
+        }
+        let [abc, root, a, missing, ab] =
+            t.get_disjoint_mut(["abc", "", "a", "abd", "ab"].map(str::bytes));
+        assert_eq!(missing, None);
+        let (abc, root, a, ab) = (abc.unwrap(), root.unwrap(), a.unwrap(), ab.unwrap());
+        std::mem::swap(abc, root);
+        std::mem::swap(a, ab);
+        let values: Vec<usize> = t.iter().map(|(_, v)| *v).collect();
+        assert_eq!(values, vec![3, 2, 1, 0, 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_disjoint_mut_rejects_equal_keys() {
+        let mut t = Trie::new();
+        t.insert("a".bytes(), 1);
+        let _ = t.get_disjoint_mut(["a", "a"].map(str::bytes));
+    }
+
+    #[test]
+    fn sorted_shards_merge_with_policies() {
+        use ptrie::trie::DuplicatePolicy;
+
+        let shards = || {
+            vec![
+                vec![(vec![1u8], 'a'), (vec![1, 2], 'a'), (vec![3], 'a')].into_iter(),
+                vec![(vec![], 'b'), (vec![1, 2], 'b')].into_iter(),
+                vec![(vec![0], 'c'), (vec![1, 2], 'c'), (vec![4], 'c')].into_iter(),
+            ]
+        };
+        let t = Trie::from_sorted_shards(shards(), DuplicatePolicy::Overwrite).unwrap();
+        let entries: Vec<(Vec<u8>, char)> = t.iter().map(|(k, v)| (k, *v)).collect();
+        assert_eq!(
+            entries,
+            vec![
+                (vec![], 'b'),
+                (vec![0], 'c'),
+                (vec![1], 'a'),
+                (vec![1, 2], 'c'),
+                (vec![3], 'a'),
+                (vec![4], 'c'),
+            ]
+        );
+        assert!(Trie::from_sorted_shards(shards(), DuplicatePolicy::Error).is_err());
+    }
+
+    #[test]
+    fn path_trie_priorities_order_sibling_params() {
+        use ptrie::path_trie::PathTrie;
+
+        let mut router = PathTrie::new();
+        router.insert_with_priority("/a/:x/end", "x", 1).unwrap();
+        router.insert_with_priority("/a/:y/*rest", "y", 5).unwrap();
+        router.insert_with_priority("/a/:z", "z", 3).unwrap();
+        router.insert("/a/fixed", "fixed").unwrap();
+
+        // Static segments win whatever the priorities
+        assert_eq!(router.route_with_priority("/a/fixed").unwrap().priority, 0);
+        // The branch of priority 5 is tried first, then the one of priority 3
+        let found = router.route_with_priority("/a/1/end").unwrap();
+        assert_eq!((*found.value, found.params.get("y")), ("y", Some("1")));
+        let found = router.route_with_priority("/a/1").unwrap();
+        assert_eq!((*found.value, found.priority), ("z", 3));
+
+        // Lowering the priority of a pattern reorders its branch
+        router.insert_with_priority("/a/:y/*rest", "y", 0).unwrap();
+        assert_eq!(*router.route("/a/1/end").unwrap().0, "x");
+
+        let mut by_name_desc = PathTrie::new().with_tie_breaker(|a, b| b.cmp(a));
+        by_name_desc.insert("/:a", 1).unwrap();
+        by_name_desc.insert("/:b", 2).unwrap();
+        assert_eq!(*by_name_desc.route("/v").unwrap().0, 2);
+    }
+
+    #[test]
+    fn child_order_only_changes_presented_iteration() {
+        let mut t = Trie::builder()
+            .child_order(|_, count: Option<&i64>| -count.copied().unwrap_or(0))
+            .build();
+        for (word, count) in [("to", 5), ("tea", 9), ("ten", 2), ("i", 7), ("in", 1)] {
+            t.insert(word.bytes(), count);
+        }
+        let words = |iter: &mut dyn Iterator<Item = (Vec<u8>, &i64)>| -> Vec<String> {
+            iter.map(|(k, _)| String::from_utf8(k).unwrap()).collect()
+        };
+        assert_eq!(words(&mut t.iter()), vec!["i", "in", "tea", "ten", "to"]);
+        assert_eq!(
+            words(&mut t.iter_presented()),
+            vec!["i", "in", "to", "tea", "ten"]
+        );
+        assert_eq!(
+            words(&mut t.iter_prefix_presented("te".bytes())),
+            vec!["tea", "ten"]
+        );
+        assert_eq!(t.get("ten".bytes()), Some(&2));
+    }
+
+    #[test]
+    fn remove_matching_keeps_insertion_order_in_sync() {
+        let mut t = Trie::builder().insertion_order(true).build();
+        for (i, word) in ["delta", "alpha", "dune", "beta", "a"].iter().enumerate() {
+            t.insert(word.bytes(), i);
+        }
+        assert_eq!(t.remove_matching(|key, _| key.first() == Some(&b'd')), 2);
+        assert_eq!(t.remove_matching(|_, &i| i == 4), 1);
+        let order: Vec<&[u8]> = t.iter_insertion_order().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(order, vec![&b"alpha"[..], b"beta"]);
+        assert!(t.root().child(&b'd').is_none());
+        assert!(t.validate().is_ok());
+    }
+
+    #[test]
+    fn list_children_includes_keys_above_depth() {
+        let mut t = Trie::new();
+        t.insert("a".bytes(), ());
+        t.insert("ab".bytes(), ());
+        t.insert("c".bytes(), ());
+        let listing: Vec<(Vec<u8>, bool, bool)> = t
+            .list_children(std::iter::empty(), 2)
+            .into_iter()
+            .map(|entry| (entry.name, entry.has_value, entry.has_children))
+            .collect();
+        assert_eq!(
+            listing,
+            vec![
+                (b"a".to_vec(), true, true),
+                (b"ab".to_vec(), true, false),
+                (b"c".to_vec(), true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn packed_kmers_order_like_ascii() {
+        use ptrie::dna::PackedKmer;
+
+        let mut seqs = ["C", "AC", "", "A", "TTTT", "AA", "GATTACA", "G"];
+        let mut kmers: Vec<PackedKmer> = seqs
+            .iter()
+            .map(|seq| PackedKmer::from_ascii(seq.as_bytes()).unwrap())
+            .collect();
+        seqs.sort_unstable();
+        kmers.sort_unstable();
+        let sorted: Vec<String> = kmers.iter().map(ToString::to_string).collect();
+        assert_eq!(sorted, seqs);
+    }
+
+    #[test]
+    fn external_builder_matches_insertion() {
+        use ptrie::external::ExternalBuilder;
+
+        let entries: Vec<(String, u32)> =
+            (0..500u32).map(|i| (format!("k{}", (i * 7919) % 211), i)).collect();
+        let mut builder = ExternalBuilder::new().run_entries(64);
+        builder.extend(entries.iter().map(|(k, v)| (k.chars(), *v))).unwrap();
+        assert_eq!(builder.runs(), 7);
+        let built = builder.build().unwrap();
+
+        let expected: Trie<char, u32> = entries.iter().map(|(k, v)| (k.chars(), *v)).collect();
+        assert_eq!(built.to_debug_string(), expected.to_debug_string());
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi_round_trip() {
+        use ptrie::ffi::*;
+
+        unsafe {
+            let trie = ptrie_new();
+            assert_eq!(ptrie_insert(trie, b"ab".as_ptr(), 2, b"v1".as_ptr(), 2), 0);
+            assert_eq!(ptrie_insert(trie, b"ab".as_ptr(), 2, b"v2".as_ptr(), 2), 1);
+            assert_eq!(
+                ptrie_insert(std::ptr::null_mut(), b"ab".as_ptr(), 2, b"".as_ptr(), 0),
+                -1
+            );
+
+            let (mut value, mut len, mut prefix_len) = (std::ptr::null(), 0, 0);
+            assert!(ptrie_get(trie, b"ab".as_ptr(), 2, &mut value, &mut len));
+            assert_eq!(std::slice::from_raw_parts(value, len), b"v2");
+            assert!(!ptrie_get(trie, b"a".as_ptr(), 1, &mut value, &mut len));
+
+            assert!(ptrie_longest_prefix(
+                trie,
+                b"abc".as_ptr(),
+                3,
+                &mut prefix_len,
+                &mut value,
+                &mut len
+            ));
+            assert_eq!(prefix_len, 2);
+            assert!(ptrie_remove(trie, b"ab".as_ptr(), 2));
+            assert!(!ptrie_longest_prefix(
+                trie,
+                b"abc".as_ptr(),
+                3,
+                &mut prefix_len,
+                &mut value,
+                &mut len
+            ));
+            ptrie_free(trie);
+        }
     }
 
     #[test]
@@ -83,6 +746,202 @@ mod tests {
         }
     }
 
+    #[test]
+    fn into_iterator() {
+        let mut t = Trie::new();
+        t.insert("a".bytes(), 1);
+        t.insert("ab".bytes(), 2);
+
+        for (_, v) in &mut t {
+            *v += 1;
+        }
+        let mut sum = 0;
+        for (k, v) in &t {
+            assert!(k.starts_with(b"a"));
+            sum += v;
+        }
+        assert_eq!(sum, 5);
+    }
+
+    #[test]
+    fn find_fuzzy_distances() {
+        let mut t = Trie::new();
+        t.insert("".bytes(), 0);
+        t.insert("ab".bytes(), 1);
+        t.insert("ba".bytes(), 2);
+        t.insert("abcd".bytes(), 3);
+
+        let mut matches = t.find_fuzzy("ab".bytes(), 2);
+        matches.sort();
+        assert_eq!(
+            matches,
+            vec![
+                (vec![], 2, &0),
+                (b"ab".to_vec(), 0, &1),
+                (b"abcd".to_vec(), 2, &3),
+                (b"ba".to_vec(), 2, &2),
+            ]
+        );
+        assert_eq!(t.find_fuzzy("ab".bytes(), 0).len(), 1);
+    }
+
+    #[test]
+    fn complete_page_walks_all_keys() {
+        let words = ["a", "ab", "abc", "abd", "b", "ba", "bab", "c"];
+        let mut t = Trie::new();
+        for w in words.iter().rev() {
+            t.insert(w.bytes(), *w);
+        }
+
+        let mut seen = Vec::new();
+        let mut after: Option<Vec<u8>> = None;
+        loop {
+            let page = t.complete_page("".bytes(), after.as_deref(), 3);
+            seen.extend(page.entries.iter().map(|(_, v)| **v));
+            match page.next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, words);
+        assert!(t.complete_page("z".bytes(), None, 3).entries.is_empty());
+    }
+
+    #[test]
+    fn node_view() {
+        let mut t = Trie::new();
+        t.insert("ab".bytes(), 1);
+        t.insert("abc".bytes(), 2);
+        t.insert("ad".bytes(), 3);
+
+        let a = t.node("a".bytes()).unwrap();
+        assert!(!a.has_value());
+        assert_eq!(a.child_count(), 2);
+        let ab = a.child(&b'b').unwrap();
+        assert_eq!(ab.value(), Some(&1));
+        assert!(!ab.is_leaf());
+
+        let mut visited = Vec::new();
+        t.iter_prefixes("abc".bytes(), |i, mut node| {
+            visited.push(i);
+            node.set_value(0);
+        });
+        assert_eq!(visited, vec![1, 2]);
+        assert_eq!(t.get("abc".bytes()), Some(&0));
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut t = Trie::builder().insertion_order(true).build();
+        t.insert("c".bytes(), 1);
+        t.insert("ab".bytes(), 2);
+        t.insert("a".bytes(), 3);
+        t.insert("b".bytes(), 4);
+        t.remove_subtree("ab".bytes());
+        t.insert("ab".bytes(), 5);
+        t.set_value("c".bytes(), 6).unwrap();
+
+        let keys: Vec<&[u8]> = t.iter_insertion_order().unwrap().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![&b"c"[..], b"a", b"b", b"ab"]);
+
+        t.clear();
+        assert_eq!(t.iter_insertion_order().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn path_trie_backtracking() {
+        use ptrie::path_trie::PathTrie;
+
+        let mut router = PathTrie::new();
+        router.insert("/users/me/settings", 1).unwrap();
+        router.insert("/users/:id/posts", 2).unwrap();
+        router.insert("/files/*path", 3).unwrap();
+        router.insert("/", 4).unwrap();
+        assert!(router.insert("/bad/*rest/more", 5).is_err());
+
+        let (value, params) = router.route("/users/me/posts").unwrap();
+        assert_eq!(*value, 2);
+        assert_eq!(params.get("id"), Some("me"));
+        assert_eq!(router.route("/").map(|(v, _)| *v), Some(4));
+        assert!(router.route("/files").is_none());
+        assert_eq!(
+            router.route("/files/a/b/").unwrap().1.get("path"),
+            Some("a/b")
+        );
+    }
+
+    #[test]
+    fn uri_shortener_rebind() {
+        use ptrie::curie::UriShortener;
+
+        let mut shortener = UriShortener::new();
+        shortener.insert("ex", "https://example.org/");
+        shortener.insert("ex2", "https://example.org/v2/");
+        assert_eq!(
+            shortener.insert("ex2", "https://example.com/"),
+            Some("https://example.org/v2/".to_string())
+        );
+
+        let curie = shortener.compress("https://example.org/v2/a").unwrap();
+        assert_eq!(curie.to_string(), "ex:v2/a");
+        assert_eq!(
+            shortener.compress("https://example.com/b").unwrap().prefix,
+            "ex2"
+        );
+    }
+
+    #[test]
+    fn vocab_unknown_policies() {
+        use ptrie::vocab::{UnknownPolicy, Vocab};
+
+        let vocab: Vocab = [("é", 1), ("a", 2)].into_iter().collect();
+        assert_eq!(vocab.encode("aüé").unwrap(), vec![2, 1]);
+        let vocab = vocab.with_unknown(UnknownPolicy::Error);
+        assert!(vocab.encode("aüé").is_err());
+        assert_eq!(vocab.decode(&[1, 2]), Some("éa".to_string()));
+        assert_eq!(vocab.decode(&[3]), None);
+    }
+
+    #[test]
+    fn vocab_reassignments_stay_consistent() {
+        use ptrie::vocab::Vocab;
+
+        let mut vocab: Vocab = [("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(vocab.insert("a", 3), Some(1));
+        assert_eq!(vocab.token(1), None);
+        assert_eq!(vocab.insert("c", 2), None);
+        assert_eq!(vocab.id("b"), None);
+        assert_eq!(vocab.len(), 2);
+        assert_eq!(vocab.encode("abc").unwrap(), vec![3, 2]);
+        assert_eq!(vocab.decode(&[3, 2]), Some("ac".to_string()));
+    }
+
+    #[test]
+    fn keyword_filter_boundaries() {
+        use ptrie::keyword::KeywordFilter;
+
+        let filter: KeywordFilter = ["café", "caf"].into_iter().collect();
+        assert_eq!(filter.scan("cafés café caf"), vec![7..12, 13..16]);
+        assert_eq!(filter.censor("un café", 'x'), "un xxxx");
+        assert!(filter.scan("").is_empty());
+    }
+
+    #[cfg(feature = "ipnet")]
+    #[test]
+    fn ip_table_iter() {
+        use ptrie::ip_table::IpPrefixTable;
+
+        let mut table = IpPrefixTable::new();
+        table.insert("10.1.2.3/8".parse().unwrap(), 1);
+        table.insert("2001:db8::/32".parse().unwrap(), 2);
+        table.insert("0.0.0.0/0".parse().unwrap(), 3);
+        assert_eq!(table.get("10.0.0.0/8".parse().unwrap()), Some(&1));
+
+        let mut nets: Vec<String> = table.iter().map(|(net, _)| net.to_string()).collect();
+        nets.sort();
+        assert_eq!(nets, vec!["0.0.0.0/0", "10.0.0.0/8", "2001:db8::/32"]);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serde_serialize() {